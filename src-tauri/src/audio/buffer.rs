@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Length of each in-memory chunk, in seconds of audio.
+const CHUNK_SECS: usize = 1;
+/// Audio kept in memory before older chunks are moved to the spill file.
+const MEMORY_LIMIT_SECS: usize = 120;
+/// Prefix of spill file names, used to find leftovers after a crash.
+const SPILL_FILE_PREFIX: &str = "capture-";
+const SPILL_FILE_EXTENSION: &str = "f32";
+
+/// Directory for capture spill files inside the app data dir.
+pub fn get_spill_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("capture")
+}
+
+/// Distinguishes spill files created in the same instant.
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Mono capture buffer with bounded memory use.
+///
+/// Samples are stored in fixed-size chunks. Once more than
+/// [`MEMORY_LIMIT_SECS`] of audio is buffered, the oldest chunks are appended
+/// to a temporary file and freed, so a forgotten recording can't grow without
+/// bound in RAM. The spill file is deleted when the buffer is dropped.
+pub struct CaptureBuffer {
+    chunk_len: usize,
+    memory_limit: usize,
+    spill_dir: Option<PathBuf>,
+    spill: Option<SpillFile>,
+    /// Chunks not yet spilled; all but the last are full.
+    chunks: VecDeque<Vec<f32>>,
+    len: usize,
+}
+
+/// Raw little-endian f32 samples moved out of memory.
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    len: usize,
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to remove capture spill file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+impl CaptureBuffer {
+    /// Create a buffer for audio at `sample_rate`. Without a `spill_dir`
+    /// everything stays in memory.
+    pub fn new(sample_rate: u32, spill_dir: Option<PathBuf>) -> Self {
+        let rate = (sample_rate as usize).max(1);
+        Self::with_limits(rate * CHUNK_SECS, rate * MEMORY_LIMIT_SECS, spill_dir)
+    }
+
+    /// Create a buffer with explicit chunk size and in-memory limit, in samples.
+    pub fn with_limits(chunk_len: usize, memory_limit: usize, spill_dir: Option<PathBuf>) -> Self {
+        Self {
+            chunk_len: chunk_len.max(1),
+            memory_limit,
+            spill_dir,
+            spill: None,
+            chunks: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Total number of samples captured, on disk and in memory.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of samples currently moved out to the spill file.
+    pub fn spilled_len(&self) -> usize {
+        self.spill.as_ref().map_or(0, |s| s.len)
+    }
+
+    /// Append samples, spilling the oldest chunks once over the memory limit.
+    pub fn push(&mut self, mut samples: &[f32]) {
+        while !samples.is_empty() {
+            let chunk = match self.chunks.back_mut() {
+                Some(chunk) if chunk.len() < self.chunk_len => chunk,
+                _ => {
+                    self.chunks.push_back(Vec::with_capacity(self.chunk_len));
+                    self.chunks.back_mut().expect("chunk was just pushed")
+                }
+            };
+            let n = (self.chunk_len - chunk.len()).min(samples.len());
+            chunk.extend_from_slice(&samples[..n]);
+            samples = &samples[n..];
+            self.len += n;
+        }
+        self.spill_excess();
+    }
+
+    /// Move full chunks to disk until the in-memory part is within the limit.
+    fn spill_excess(&mut self) {
+        while self.len - self.spilled_len() > self.memory_limit && self.chunks.len() > 1 {
+            let Some(dir) = self.spill_dir.as_deref() else {
+                return;
+            };
+            if self.spill.is_none() {
+                match SpillFile::create(dir) {
+                    Ok(spill) => self.spill = Some(spill),
+                    Err(e) => {
+                        // Keep recording in memory rather than losing audio
+                        log::warn!("Failed to create capture spill file: {}", e);
+                        self.spill_dir = None;
+                        return;
+                    }
+                }
+            }
+            let (Some(spill), Some(chunk)) = (self.spill.as_mut(), self.chunks.front()) else {
+                return;
+            };
+            if let Err(e) = spill.append(chunk) {
+                log::warn!("Failed to write capture spill file: {}", e);
+                self.spill_dir = None;
+                return;
+            }
+            self.chunks.pop_front();
+        }
+    }
+
+    /// Copy of the samples from `offset` to the end.
+    pub fn read_from(&mut self, offset: usize) -> Result<Vec<f32>, String> {
+        let mut out = Vec::with_capacity(self.len.saturating_sub(offset));
+        self.stream_from(offset, &mut |samples| {
+            out.extend_from_slice(samples);
+            Ok(())
+        })?;
+        Ok(out)
+    }
+
+    /// Hand the samples from `offset` to the end to `sink`, one chunk at a
+    /// time, without loading spilled audio into memory all at once.
+    pub fn stream_from(
+        &mut self,
+        offset: usize,
+        sink: &mut dyn FnMut(&[f32]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let spilled = self.spilled_len();
+        if offset < spilled {
+            if let Some(spill) = self.spill.as_mut() {
+                spill.stream(offset, self.chunk_len, sink)?;
+            }
+        }
+
+        let mut position = spilled;
+        for chunk in &self.chunks {
+            let end = position + chunk.len();
+            if end > offset {
+                sink(&chunk[offset.saturating_sub(position)..])?;
+            }
+            position = end;
+        }
+        Ok(())
+    }
+
+    /// Collect the whole recording into memory.
+    pub fn to_vec(&mut self) -> Result<Vec<f32>, String> {
+        self.read_from(0)
+    }
+}
+
+impl SpillFile {
+    fn create(dir: &Path) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let path = dir.join(format!(
+            "{}{}-{}-{}.{}",
+            SPILL_FILE_PREFIX,
+            std::process::id(),
+            nanos,
+            SPILL_COUNTER.fetch_add(1, Ordering::Relaxed),
+            SPILL_FILE_EXTENSION
+        ));
+        // Append mode keeps writes at the end even after reads seek elsewhere
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self { file, path, len: 0 })
+    }
+
+    fn append(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.file.write_all(&bytes)?;
+        self.len += samples.len();
+        Ok(())
+    }
+
+    fn stream(
+        &mut self,
+        offset: usize,
+        block_len: usize,
+        sink: &mut dyn FnMut(&[f32]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let read_err = |e: std::io::Error| format!("Failed to read capture spill file: {}", e);
+        self.file
+            .seek(SeekFrom::Start(offset as u64 * 4))
+            .map_err(read_err)?;
+
+        let mut bytes = vec![0u8; block_len * 4];
+        let mut samples = Vec::with_capacity(block_len);
+        let mut remaining = self.len - offset;
+        while remaining > 0 {
+            let n = remaining.min(block_len);
+            self.file.read_exact(&mut bytes[..n * 4]).map_err(read_err)?;
+            samples.clear();
+            samples.extend(
+                bytes[..n * 4]
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            );
+            sink(&samples)?;
+            remaining -= n;
+        }
+        Ok(())
+    }
+}
+
+/// Delete spill files left behind by a session that didn't shut down cleanly.
+pub fn remove_stale_spill_files(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_spill = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(SPILL_FILE_PREFIX))
+            && path.extension().and_then(|e| e.to_str()) == Some(SPILL_FILE_EXTENSION);
+        if is_spill {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!("Failed to remove stale spill file {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("ttc_test_capture_buffer").join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn ramp(start: usize, len: usize) -> Vec<f32> {
+        (start..start + len).map(|i| i as f32).collect()
+    }
+
+    #[test]
+    fn test_in_memory_roundtrip() {
+        let mut buffer = CaptureBuffer::with_limits(4, 100, None);
+        buffer.push(&ramp(0, 10));
+        buffer.push(&ramp(10, 3));
+        assert_eq!(buffer.len(), 13);
+        assert_eq!(buffer.spilled_len(), 0);
+        assert_eq!(buffer.to_vec().unwrap(), ramp(0, 13));
+        assert_eq!(buffer.read_from(11).unwrap(), ramp(11, 2));
+    }
+
+    #[test]
+    fn test_spills_oldest_chunks_past_limit() {
+        let dir = temp_dir("spill");
+        let mut buffer = CaptureBuffer::with_limits(4, 8, Some(dir.clone()));
+        for i in 0..10 {
+            buffer.push(&ramp(i * 3, 3));
+        }
+
+        assert_eq!(buffer.len(), 30);
+        assert!(buffer.spilled_len() >= 20, "spilled {}", buffer.spilled_len());
+        assert!(buffer.len() - buffer.spilled_len() <= 8 + 4);
+        assert_eq!(buffer.to_vec().unwrap(), ramp(0, 30));
+        // Reads that start inside the spilled part and inside memory
+        assert_eq!(buffer.read_from(5).unwrap(), ramp(5, 25));
+        assert_eq!(buffer.read_from(27).unwrap(), ramp(27, 3));
+        assert!(buffer.read_from(30).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_spill_file_removed_on_drop() {
+        let dir = temp_dir("drop");
+        let mut buffer = CaptureBuffer::with_limits(2, 2, Some(dir.clone()));
+        buffer.push(&ramp(0, 10));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        drop(buffer);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_stream_delivers_bounded_chunks() {
+        let dir = temp_dir("stream");
+        let mut buffer = CaptureBuffer::with_limits(4, 4, Some(dir));
+        buffer.push(&ramp(0, 22));
+
+        let mut sizes = Vec::new();
+        let mut all = Vec::new();
+        buffer
+            .stream_from(0, &mut |samples| {
+                sizes.push(samples.len());
+                all.extend_from_slice(samples);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(all, ramp(0, 22));
+        assert!(sizes.iter().all(|&n| n <= 4));
+    }
+
+    #[test]
+    fn test_remove_stale_spill_files() {
+        let dir = temp_dir("stale");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("capture-1-2-0.f32"), [0u8; 4]).unwrap();
+        std::fs::write(dir.join("keep.wav"), [0u8; 4]).unwrap();
+
+        remove_stale_spill_files(&dir);
+
+        let names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("keep.wav")]);
+    }
+}
@@ -1,152 +1,958 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::Stream;
 use serde::Serialize;
 
+use super::buffer::CaptureBuffer;
+use super::level::{InputLevelMetrics, LevelMonitor, LevelWarningKind};
+use super::preroll::PreRollBuffer;
+use super::resample::ResampledCapture;
+use super::ring::{frame_ring, ConsumerThread, FrameConsumer};
+use super::source::AudioSource;
+use super::spectrum::{SpectrumAnalyzer, SpectrumFrame};
+use super::vad::{SilenceDetector, SilenceDetectorConfig};
+
+/// Sample rate the transcription engines expect. Capturing at this rate
+/// natively means recordings skip the resampler.
+const PREFERRED_SAMPLE_RATE: u32 = 16000;
+
 /// Information about an available audio input device.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AudioDeviceInfo {
+    /// Stable identifier to store in preferences; see [`list_input_devices`].
+    pub id: String,
     pub name: String,
     pub is_default: bool,
+    /// Most input channels the device offers in any configuration.
+    pub channels: u16,
+    /// Whether the device captures system output rather than a microphone.
+    pub is_loopback: bool,
 }
 
 /// List available audio input devices.
-#[allow(deprecated)] // cpal::DeviceTrait::name() — stable replacement not yet widely adopted
+///
+/// Each device gets a stable id: the backend's own device id where cpal
+/// exposes one, otherwise the host, the device name and its position among
+/// devices with the same name.
 pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>, String> {
     let host = cpal::default_host();
-    let default_name = host
-        .default_input_device()
-        .and_then(|d| d.name().ok());
+    let default = host.default_input_device();
 
+    let result = enumerate_input_devices(&host)?
+        .into_iter()
+        .map(|input| AudioDeviceInfo {
+            is_default: default.as_ref().is_some_and(|d| input.is(d)),
+            channels: max_input_channels(&input.device),
+            is_loopback: is_loopback_device(&input.id, &input.name),
+            id: input.id,
+            name: input.name,
+        })
+        .collect();
+    Ok(result)
+}
+
+/// An input device with the identifiers used to find it again.
+struct InputDevice {
+    device: cpal::Device,
+    id: String,
+    name: String,
+    /// Whether `id` came from the backend rather than the name fallback.
+    native_id: bool,
+}
+
+impl InputDevice {
+    /// Whether `device` is this device.
+    #[allow(deprecated)] // cpal::DeviceTrait::name()
+    fn is(&self, device: &cpal::Device) -> bool {
+        if self.native_id {
+            device.id().is_ok_and(|id| id.to_string() == self.id)
+        } else {
+            device.name().is_ok_and(|name| name == self.name)
+        }
+    }
+}
+
+/// Enumerate input devices along with their stable ids.
+#[allow(deprecated)] // cpal::DeviceTrait::name() — stable replacement not yet widely adopted
+fn enumerate_input_devices(host: &cpal::Host) -> Result<Vec<InputDevice>, String> {
     let devices = host
         .input_devices()
         .map_err(|e| format!("Failed to enumerate input devices: {}", e))?;
 
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
     let mut result = Vec::new();
     for device in devices {
-        if let Ok(name) = device.name() {
-            let is_default = default_name.as_deref() == Some(&name);
-            result.push(AudioDeviceInfo { name, is_default });
-        }
+        let Ok(name) = device.name() else {
+            continue;
+        };
+        let occurrence = occurrences.entry(name.clone()).or_insert(0);
+        let (id, native_id) = match device.id() {
+            Ok(id) => (id.to_string(), true),
+            Err(_) => (fallback_device_id(host.id().name(), &name, *occurrence), false),
+        };
+        *occurrence += 1;
+        result.push(InputDevice {
+            device,
+            id,
+            name,
+            native_id,
+        });
     }
     Ok(result)
 }
 
-/// Handle to an active audio capture stream.
+/// Device id for backends that don't provide one. `occurrence` tells apart
+/// identical devices, e.g. two of the same USB microphone.
+fn fallback_device_id(host: &str, name: &str, occurrence: usize) -> String {
+    format!("{}:name:{}#{}", host.to_lowercase(), name, occurrence)
+}
+
+/// Whether a device records what the system plays, going by the names
+/// PulseAudio/PipeWire monitors, Windows "Stereo Mix" and common macOS
+/// loopback drivers use.
+pub fn is_loopback_device(id: &str, name: &str) -> bool {
+    const MARKERS: &[&str] = &[".monitor", "monitor of", "loopback", "stereo mix", "blackhole"];
+    let id = id.to_lowercase();
+    let name = name.to_lowercase();
+    MARKERS
+        .iter()
+        .any(|marker| id.contains(marker) || name.contains(marker))
+}
+
+/// Largest channel count across the device's supported input configs.
+fn max_input_channels(device: &cpal::Device) -> u16 {
+    let supported = device
+        .supported_input_configs()
+        .ok()
+        .and_then(|configs| configs.map(|c| c.channels()).max());
+    supported
+        .or_else(|| device.default_input_config().ok().map(|c| c.channels()))
+        .unwrap_or(1)
+}
+
+/// Handle to an active capture session on a cpal input device.
 pub struct AudioCapture {
-    stream: Stream,
-    buffer: Arc<Mutex<Vec<f32>>>,
+    source: CaptureSource,
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    sample_rate: u32,
+    device_id: String,
+    device_name: String,
+    loss: Arc<LossSignal>,
+    levels: Arc<Mutex<LevelMonitor>>,
+}
+
+/// What keeps samples flowing into a capture session.
+enum CaptureSource {
+    /// A stream opened just for this session, and the thread processing its frames.
+    Stream {
+        stream: Stream,
+        consumer: ConsumerThread,
+        processor: Arc<Mutex<SessionProcessor>>,
+    },
+    /// A session attached to the always-on warm microphone.
+    Warm(WarmAttachment),
+}
+
+/// Read-only view of an in-progress capture buffer, usable from other threads.
+#[derive(Clone)]
+pub struct CaptureTap {
+    buffer: Arc<Mutex<CaptureBuffer>>,
     sample_rate: u32,
 }
 
+impl CaptureTap {
+    pub(super) fn new(buffer: Arc<Mutex<CaptureBuffer>>, sample_rate: u32) -> Self {
+        Self { buffer, sample_rate }
+    }
+
+    /// Copy of the mono samples captured after `offset`.
+    pub fn samples_since(&self, offset: usize) -> Vec<f32> {
+        let Ok(mut buffer) = self.buffer.lock() else {
+            return Vec::new();
+        };
+        buffer.read_from(offset).unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            Vec::new()
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// Levels for the visualizer, sent for every ~50ms of captured audio.
+#[derive(Debug, Clone)]
+pub struct AmplitudeFrame {
+    /// Mean absolute amplitude over consecutive slices of the window.
+    pub amplitudes: Vec<f32>,
+    pub rms: f32,
+    /// Frequency bands, when [`CaptureOptions::spectrum`] is set.
+    pub spectrum: Option<SpectrumFrame>,
+}
+
 /// Callback type for amplitude updates during capture.
-pub type AmplitudeCallback = Box<dyn Fn(Vec<f32>, f32) + Send + 'static>;
+pub type AmplitudeCallback = Box<dyn Fn(AmplitudeFrame) + Send + 'static>;
+
+/// Callback fired once when hands-free silence detection ends the session.
+pub type SilenceCallback = Box<dyn Fn() + Send + 'static>;
+
+/// Automatically end a session after speech followed by trailing silence.
+pub struct AutoStop {
+    pub config: SilenceDetectorConfig,
+    pub on_silence: SilenceCallback,
+}
+
+/// Callback fired when the input level suggests a bad microphone setup.
+pub type LevelWarningCallback = Box<dyn Fn(LevelWarningKind) + Send + 'static>;
+
+/// Callback fired with the number of seconds left before the recording limit.
+pub type LimitWarningCallback = Box<dyn Fn(u32) + Send + 'static>;
+
+/// Cap on how long a session may record.
+pub struct DurationLimit {
+    pub max_secs: u32,
+    /// How long before the cutoff `on_warning` fires.
+    pub warning_secs: u32,
+    pub on_warning: LimitWarningCallback,
+    /// Fired once when the limit is reached. Audio after that is discarded.
+    pub on_limit: Box<dyn Fn() + Send + 'static>,
+}
+
+/// Callback fired once when the device a session records from is disconnected.
+/// Called from the audio backend's thread, so it should return quickly.
+pub type DeviceLostCallback = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// Options for opening a capture session.
+#[derive(Default)]
+pub struct CaptureOptions {
+    /// Input device id from [`list_input_devices`]; falls back to the system
+    /// default when missing or not found.
+    pub device_id: Option<String>,
+    /// Zero-based input channel to record; `None` mixes all channels.
+    pub channel: Option<usize>,
+    /// Silence detection for hands-free mode.
+    pub auto_stop: Option<AutoStop>,
+    /// Notified when the input device goes away mid-session.
+    pub on_device_lost: Option<DeviceLostCallback>,
+    /// Maximum recording length.
+    pub limit: Option<DurationLimit>,
+    /// Where long recordings are spilled to disk; kept in memory when unset.
+    pub spill_dir: Option<PathBuf>,
+    /// Include frequency bands in amplitude updates.
+    pub spectrum: bool,
+    /// Notified when the input is clipping or too quiet, at most once per kind.
+    pub on_level_warning: Option<LevelWarningCallback>,
+    /// Receives a 16kHz copy of the session as it is captured, so stopping
+    /// does not have to resample the whole recording.
+    pub resampled: Option<Arc<Mutex<ResampledCapture>>>,
+}
+
+/// Reports a lost device at most once per stream.
+struct LossSignal {
+    fired: AtomicBool,
+    callback: Option<DeviceLostCallback>,
+}
+
+impl LossSignal {
+    fn new(callback: Option<DeviceLostCallback>) -> Arc<Self> {
+        Arc::new(Self {
+            fired: AtomicBool::new(false),
+            callback,
+        })
+    }
+
+    fn trigger(&self) {
+        if !self.fired.swap(true, Ordering::AcqRel) {
+            if let Some(callback) = &self.callback {
+                callback();
+            }
+        }
+    }
+}
+
+/// Whether a stream error means the device is gone and the stream won't recover.
+fn is_device_loss(err: &cpal::StreamError) -> bool {
+    matches!(
+        err,
+        cpal::StreamError::DeviceNotAvailable | cpal::StreamError::StreamInvalidated
+    )
+}
+
+/// Human-readable device name, as listed by [`list_input_devices`].
+#[allow(deprecated)] // cpal::DeviceTrait::name()
+fn device_display_name(device: &cpal::Device) -> String {
+    device.name().unwrap_or_else(|_| "Unknown device".to_string())
+}
+
+/// Enforces a [`DurationLimit`] on the samples entering a session.
+struct LimitState {
+    limit: DurationLimit,
+    sample_rate: u32,
+    warned: bool,
+    reached: bool,
+}
+
+impl LimitState {
+    /// Return the part of `samples` that fits under the limit, given that
+    /// `captured` samples are already buffered, firing callbacks as thresholds
+    /// are crossed.
+    fn admit<'a>(&mut self, captured: usize, samples: &'a [f32]) -> &'a [f32] {
+        let rate = self.sample_rate as usize;
+        let max_samples = self.limit.max_secs as usize * rate;
+        let warn_at = max_samples.saturating_sub(self.limit.warning_secs as usize * rate);
+
+        let allowed = max_samples.saturating_sub(captured).min(samples.len());
+        let end = captured + allowed;
+        if !self.warned && end >= warn_at && end < max_samples {
+            self.warned = true;
+            let remaining_secs = (max_samples - end).div_ceil(rate.max(1));
+            (self.limit.on_warning)(remaining_secs as u32);
+        }
+        if !self.reached && end >= max_samples {
+            self.reached = true;
+            (self.limit.on_limit)();
+        }
+        &samples[..allowed]
+    }
+}
+
+/// Per-session processing of incoming mono samples: buffering, amplitude
+/// updates, level monitoring, silence detection and the duration limit.
+///
+/// Runs on the capture consumer thread, never on the realtime audio callback.
+pub(super) struct SessionProcessor {
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    sample_rate: u32,
+    captured: usize,
+    amplitude_buffer: Vec<f32>,
+    amplitude_window: usize,
+    amplitude_callback: AmplitudeCallback,
+    spectrum: Option<SpectrumAnalyzer>,
+    levels: Arc<Mutex<LevelMonitor>>,
+    on_level_warning: Option<LevelWarningCallback>,
+    auto_stop: Option<(SilenceDetector, SilenceCallback)>,
+    limit: Option<LimitState>,
+    resampled: Option<Arc<Mutex<ResampledCapture>>>,
+}
+
+impl SessionProcessor {
+    pub(super) fn new(
+        buffer: Arc<Mutex<CaptureBuffer>>,
+        sample_rate: u32,
+        amplitude_callback: AmplitudeCallback,
+        spectrum: bool,
+        on_level_warning: Option<LevelWarningCallback>,
+        auto_stop: Option<AutoStop>,
+        limit: Option<DurationLimit>,
+    ) -> Self {
+        // Amplitude calculation window (every ~50ms of audio)
+        let amplitude_window = (sample_rate as usize / 20).max(1);
+        // Pre-roll seeded into the buffer counts towards the limit
+        let captured = buffer.lock().map(|b| b.len()).unwrap_or(0);
+        Self {
+            buffer,
+            sample_rate,
+            captured,
+            amplitude_buffer: Vec::with_capacity(amplitude_window),
+            amplitude_window,
+            amplitude_callback,
+            spectrum: spectrum.then(|| SpectrumAnalyzer::new(sample_rate, amplitude_window)),
+            levels: Arc::new(Mutex::new(LevelMonitor::new(sample_rate))),
+            on_level_warning,
+            auto_stop: auto_stop.map(|auto_stop| {
+                (
+                    SilenceDetector::new(auto_stop.config, sample_rate),
+                    auto_stop.on_silence,
+                )
+            }),
+            limit: limit.map(|limit| LimitState {
+                limit,
+                sample_rate,
+                warned: false,
+                reached: false,
+            }),
+            resampled: None,
+        }
+    }
+
+    /// Also resample the session into `resampled`, starting with anything
+    /// already in the buffer, such as pre-roll.
+    pub(super) fn resampling_into(mut self, resampled: Option<Arc<Mutex<ResampledCapture>>>) -> Self {
+        if let Some(resampled) = &resampled {
+            let seeded = self.buffer.lock().map_err(|e| e.to_string()).and_then(|mut b| b.to_vec());
+            match (seeded, resampled.lock()) {
+                (Ok(samples), Ok(mut resampled)) => {
+                    resampled.start(self.sample_rate);
+                    resampled.push(&samples);
+                }
+                (Err(e), _) => log::warn!("Failed to read pre-roll for resampling: {}", e),
+                (_, Err(e)) => log::warn!("Failed to lock resampler: {}", e),
+            }
+        }
+        self.resampled = resampled;
+        self
+    }
+
+    /// Level statistics for this session, updated as audio is processed.
+    pub(super) fn levels(&self) -> Arc<Mutex<LevelMonitor>> {
+        Arc::clone(&self.levels)
+    }
+
+    pub(super) fn push(&mut self, mono_samples: &[f32]) {
+        let mono_samples = match self.limit.as_mut() {
+            Some(limit) => limit.admit(self.captured, mono_samples),
+            None => mono_samples,
+        };
+        if mono_samples.is_empty() {
+            return;
+        }
+        self.captured += mono_samples.len();
+
+        // Append to main buffer
+        if let Ok(mut buf) = self.buffer.lock() {
+            buf.push(mono_samples);
+        }
+        if let Some(Ok(mut resampled)) = self.resampled.as_ref().map(|r| r.lock()) {
+            resampled.push(mono_samples);
+        }
+
+        // Collect amplitude samples for visualization
+        self.amplitude_buffer.extend_from_slice(mono_samples);
+        while self.amplitude_buffer.len() >= self.amplitude_window {
+            let samples: Vec<f32> = self.amplitude_buffer.drain(..self.amplitude_window).collect();
+            let rms = calculate_rms(&samples);
+            let amplitudes = downsample_for_visualization(&samples, 48);
+            let spectrum = self.spectrum.as_mut().map(|analyzer| {
+                analyzer.push(&samples);
+                analyzer.analyze()
+            });
+            (self.amplitude_callback)(AmplitudeFrame {
+                amplitudes,
+                rms,
+                spectrum,
+            });
+
+            let warning = match self.levels.lock() {
+                Ok(mut levels) => levels.process(&samples, rms),
+                Err(_) => None,
+            };
+            if let (Some(kind), Some(on_level_warning)) = (warning, self.on_level_warning.as_ref()) {
+                on_level_warning(kind);
+            }
+
+            if let Some((detector, on_silence)) = self.auto_stop.as_mut() {
+                if detector.process(&samples) {
+                    on_silence();
+                }
+            }
+        }
+    }
+}
+
+/// Find the input device with the given id, falling back to the system default.
+fn open_input_device(device_id: Option<&str>) -> Result<InputDevice, String> {
+    let host = cpal::default_host();
+    let mut devices = enumerate_input_devices(&host)?;
+    if let Some(id) = device_id {
+        if let Some(index) = devices.iter().position(|d| d.id == id) {
+            return Ok(devices.swap_remove(index));
+        }
+        log::warn!("Device '{}' not found, falling back to default", id);
+    }
+
+    let default = host
+        .default_input_device()
+        .ok_or_else(|| "No input device available. Check microphone permissions.".to_string())?;
+    match devices.into_iter().find(|d| d.is(&default)) {
+        Some(found) => Ok(found),
+        None => {
+            let name = device_display_name(&default);
+            Ok(InputDevice {
+                id: fallback_device_id(host.id().name(), &name, 0),
+                device: default,
+                name,
+                native_id: false,
+            })
+        }
+    }
+}
 
 impl AudioCapture {
     /// Start capturing audio from the specified or default input device.
     /// Returns the capture handle and the device's native sample rate.
-    #[allow(deprecated)] // cpal::DeviceTrait::name()
-    pub fn start(amplitude_callback: AmplitudeCallback, device_name: Option<&str>) -> Result<Self, String> {
-        let host = cpal::default_host();
-        let device = if let Some(name) = device_name {
-            host.input_devices()
-                .map_err(|e| format!("Failed to enumerate devices: {}", e))?
-                .find(|d| d.name().ok().as_deref() == Some(name))
-                .unwrap_or_else(|| {
-                    log::warn!("Device '{}' not found, falling back to default", name);
-                    host.default_input_device().expect("No default input device")
-                })
-        } else {
-            host.default_input_device()
-                .ok_or_else(|| "No input device available. Check microphone permissions.".to_string())?
+    pub fn start(amplitude_callback: AmplitudeCallback, options: CaptureOptions) -> Result<Self, String> {
+        let InputDevice {
+            device, id, name, ..
+        } = open_input_device(options.device_id.as_deref())?;
+        let config = choose_input_config(&device, options.channel)?;
+        let sample_rate = config.sample_rate();
+
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new(sample_rate, options.spill_dir)));
+        let processor = Arc::new(Mutex::new(SessionProcessor::new(
+            Arc::clone(&buffer),
+            sample_rate,
+            amplitude_callback,
+            options.spectrum,
+            options.on_level_warning,
+            options.auto_stop,
+            options.limit,
+        )
+        .resampling_into(options.resampled)));
+        let levels = processor
+            .lock()
+            .map_err(|e| format!("Failed to lock session: {}", e))?
+            .levels();
+        let loss = LossSignal::new(options.on_device_lost);
+
+        let (stream, consumer) = open_stream(&device, config, options.channel, &processor, &loss)?;
+        stream
+            .play()
+            .map_err(|e| format!("Failed to start audio stream: {}", e))?;
+
+        Ok(Self {
+            source: CaptureSource::Stream {
+                stream,
+                consumer,
+                processor,
+            },
+            buffer,
+            sample_rate,
+            device_id: id,
+            device_name: name,
+            loss,
+            levels,
+        })
+    }
+}
+
+impl AudioSource for AudioCapture {
+    fn tap(&self) -> CaptureTap {
+        CaptureTap::new(Arc::clone(&self.buffer), self.sample_rate)
+    }
+
+    fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    fn input_levels(&self) -> InputLevelMetrics {
+        lock_metrics(&self.levels)
+    }
+
+    /// Used when the device disappears from the device list without a stream error.
+    fn mark_device_lost(&self) {
+        self.loss.trigger();
+    }
+
+    /// Continue the session on the system default input device after the
+    /// current one was lost. Audio captured so far is kept, and the new stream
+    /// is opened at the session's sample rate so the buffer stays consistent.
+    /// The channel selection belonged to the lost device, so the new stream
+    /// mixes all channels. Returns the name of the new device.
+    fn fail_over_to_default(&mut self) -> Result<String, String> {
+        let CaptureSource::Stream { processor, .. } = &self.source else {
+            return Err("Sessions on the warm microphone can't switch devices.".to_string());
+        };
+        let processor = Arc::clone(processor);
+
+        let InputDevice {
+            device, id, name, ..
+        } = open_input_device(None)?;
+        let config = config_with_sample_rate(&device, self.sample_rate)?;
+        let loss = LossSignal::new(self.loss.callback.clone());
+        let (stream, consumer) = open_stream(&device, config, None, &processor, &loss)?;
+
+        // Replacing the source closes the dead stream and drains its last frames
+        // before the new stream starts delivering
+        self.source = CaptureSource::Stream {
+            stream,
+            consumer,
+            processor,
         };
+        if let CaptureSource::Stream { stream, .. } = &self.source {
+            stream
+                .play()
+                .map_err(|e| format!("Failed to start audio stream: {}", e))?;
+        }
+
+        self.loss = loss;
+        self.device_id = id;
+        self.device_name = name;
+        log::warn!("Recording continued on default device '{}'", self.device_name);
+        Ok(self.device_name.clone())
+    }
+
+    /// Stop capturing and return the collected audio buffer and sample rate.
+    fn stop(self: Box<Self>) -> (CaptureBuffer, u32) {
+        let capture = *self;
+        match capture.source {
+            CaptureSource::Stream {
+                stream,
+                consumer,
+                processor,
+            } => {
+                // Close the stream first so the consumer's final drain sees every frame
+                drop(stream);
+                consumer.finish();
+                drop(processor);
+            }
+            CaptureSource::Warm(attachment) => drop(attachment),
+        }
+        (take_buffer(capture.buffer, capture.sample_rate), capture.sample_rate)
+    }
+}
+
+/// Take a finished session's buffer, even if a live transcription tap still holds it.
+pub(super) fn take_buffer(buffer: Arc<Mutex<CaptureBuffer>>, sample_rate: u32) -> CaptureBuffer {
+    match Arc::try_unwrap(buffer) {
+        Ok(mutex) => mutex.into_inner().unwrap_or_else(|e| e.into_inner()),
+        // A live transcription tap is still alive; take the samples from under it
+        Err(arc) => {
+            let mut shared = arc.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *shared, CaptureBuffer::new(sample_rate, None))
+        }
+    }
+}
+
+/// Build a (paused) input stream feeding `processor` through a ring and consumer thread.
+fn open_stream(
+    device: &cpal::Device,
+    config: cpal::SupportedStreamConfig,
+    channel: Option<usize>,
+    processor: &Arc<Mutex<SessionProcessor>>,
+    loss: &Arc<LossSignal>,
+) -> Result<(Stream, ConsumerThread), String> {
+    let channels = config.channels() as usize;
+
+    // The realtime callback only copies frames into the ring; downmixing,
+    // amplitude events and silence detection happen on the consumer thread.
+    let (mut producer, mut frames) = frame_ring(config.sample_rate(), channels, channel);
+
+    let error_loss = Arc::clone(loss);
+    let stream = device
+        .build_input_stream(
+            &config.into(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                producer.write(data);
+            },
+            move |err| {
+                log::error!("Audio capture error: {}", err);
+                if is_device_loss(&err) {
+                    error_loss.trigger();
+                }
+            },
+            None,
+        )
+        .map_err(|e| format!("Failed to build input stream: {}", e))?;
+
+    let processor = Arc::clone(processor);
+    let consumer = ConsumerThread::spawn(move || {
+        if let Ok(mut processor) = processor.lock() {
+            frames.drain(&mut |mono| processor.push(mono));
+        }
+    });
+
+    Ok((stream, consumer))
+}
+
+/// Pick the input configuration for a new stream: native 16kHz float capture
+/// when the device supports it, otherwise float capture at the device's
+/// default rate. Streams are always built with an f32 callback.
+fn choose_input_config(
+    device: &cpal::Device,
+    channel: Option<usize>,
+) -> Result<cpal::SupportedStreamConfig, String> {
+    // The selected channel must exist in the chosen config
+    let min_channels = channel.map_or(1, |c| c + 1);
+    let ranges: Vec<_> = device
+        .supported_input_configs()
+        .map(|ranges| ranges.collect())
+        .unwrap_or_default();
+    if let Some(config) = pick_native_config(&ranges, PREFERRED_SAMPLE_RATE, min_channels) {
+        return Ok(config);
+    }
+    let default = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get input config: {}", e))?;
+    pick_float_fallback(default, &ranges, min_channels)
+        .ok_or_else(|| "Input device does not support 32-bit float capture.".to_string())
+}
+
+/// The device default if it is float, otherwise a float config at the
+/// default's sample rate, or at the highest rate a float range offers.
+fn pick_float_fallback(
+    default: cpal::SupportedStreamConfig,
+    ranges: &[cpal::SupportedStreamConfigRange],
+    min_channels: usize,
+) -> Option<cpal::SupportedStreamConfig> {
+    if default.sample_format() == cpal::SampleFormat::F32 {
+        return Some(default);
+    }
+    pick_native_config(ranges, default.sample_rate(), min_channels).or_else(|| {
+        ranges
+            .iter()
+            .filter(|range| {
+                range.sample_format() == cpal::SampleFormat::F32
+                    && range.channels() as usize >= min_channels
+            })
+            .min_by_key(|range| range.channels())
+            .map(|range| range.with_max_sample_rate())
+    })
+}
+
+/// Find a float config at exactly `sample_rate` with the fewest channels that
+/// still has at least `min_channels`.
+fn pick_native_config(
+    ranges: &[cpal::SupportedStreamConfigRange],
+    sample_rate: u32,
+    min_channels: usize,
+) -> Option<cpal::SupportedStreamConfig> {
+    ranges
+        .iter()
+        .filter(|range| {
+            range.sample_format() == cpal::SampleFormat::F32
+                && range.channels() as usize >= min_channels
+        })
+        .filter_map(|range| range.try_with_sample_rate(sample_rate))
+        .min_by_key(|config| config.channels())
+}
+
+/// Pick an input configuration at exactly `sample_rate`, preferring the device default.
+fn config_with_sample_rate(
+    device: &cpal::Device,
+    sample_rate: u32,
+) -> Result<cpal::SupportedStreamConfig, String> {
+    if let Ok(config) = device.default_input_config() {
+        if config.sample_rate() == sample_rate && config.sample_format() == cpal::SampleFormat::F32 {
+            return Ok(config);
+        }
+    }
+    device
+        .supported_input_configs()
+        .map_err(|e| format!("Failed to query input configs: {}", e))?
+        .filter(|range| range.sample_format() == cpal::SampleFormat::F32)
+        .find_map(|range| range.try_with_sample_rate(sample_rate))
+        .ok_or_else(|| format!("Default input device does not support {} Hz.", sample_rate))
+}
+
+/// State shared between the warm microphone consumer thread and an attached session.
+struct WarmState {
+    frames: FrameConsumer,
+    pre_roll: PreRollBuffer,
+    session: Option<SessionProcessor>,
+    session_loss: Option<Arc<LossSignal>>,
+}
+
+impl WarmState {
+    /// Move pending frames into the attached session, or the pre-roll when idle.
+    fn drain(&mut self) {
+        let Self {
+            frames,
+            pre_roll,
+            session,
+            ..
+        } = self;
+        frames.drain(&mut |mono| match session.as_mut() {
+            Some(session) => session.push(mono),
+            None => pre_roll.push(mono),
+        });
+    }
+}
+
+/// Detaches a session from the warm microphone when dropped.
+struct WarmAttachment(Arc<Mutex<WarmState>>);
+
+impl Drop for WarmAttachment {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.0.lock() {
+            // Hand the session everything captured up to now before detaching
+            state.drain();
+            state.session = None;
+            state.session_loss = None;
+        }
+    }
+}
+
+/// An always-open input stream that keeps the last few hundred milliseconds
+/// of audio, so recordings can include the moment before the hotkey press.
+///
+/// Sessions attach to the running stream instead of opening their own, which
+/// also removes the stream start-up delay from the beginning of a recording.
+pub struct WarmMicrophone {
+    _stream: Stream,
+    // Declared after the stream so it is dropped (and joined) once the stream is closed
+    _consumer: ConsumerThread,
+    state: Arc<Mutex<WarmState>>,
+    lost: Arc<AtomicBool>,
+    sample_rate: u32,
+    device_id: Option<String>,
+    opened_device_id: String,
+    opened_device_name: String,
+    pre_roll_ms: u32,
+    channel: Option<usize>,
+}
 
-        let config = device
-            .default_input_config()
-            .map_err(|e| format!("Failed to get input config: {}", e))?;
+impl WarmMicrophone {
+    /// Open the specified or default input device and start filling the pre-roll buffer.
+    pub fn start(
+        device_id: Option<&str>,
+        pre_roll_ms: u32,
+        channel: Option<usize>,
+    ) -> Result<Self, String> {
+        let InputDevice {
+            device,
+            id: opened_device_id,
+            name: opened_device_name,
+            ..
+        } = open_input_device(device_id)?;
+        let config = choose_input_config(&device, channel)?;
 
         let sample_rate = config.sample_rate();
         let channels = config.channels() as usize;
 
-        // Pre-allocate for up to 10 minutes of recording to avoid reallocations
-        let capacity = estimate_buffer_capacity(sample_rate, 600);
-        let buffer: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::with_capacity(capacity)));
-        let buffer_clone = Arc::clone(&buffer);
-
-        // Amplitude calculation window (every ~50ms of audio)
-        let amplitude_window_size = (sample_rate as usize / 20) * channels;
-        let amplitude_buffer: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::with_capacity(amplitude_window_size)));
-        let amplitude_buffer_clone = Arc::clone(&amplitude_buffer);
+        let (mut producer, frames) = frame_ring(sample_rate, channels, channel);
+        let state = Arc::new(Mutex::new(WarmState {
+            frames,
+            pre_roll: PreRollBuffer::new(PreRollBuffer::capacity_for(sample_rate, pre_roll_ms)),
+            session: None,
+            session_loss: None,
+        }));
+        let lost = Arc::new(AtomicBool::new(false));
 
+        let error_state = Arc::clone(&state);
+        let error_lost = Arc::clone(&lost);
         let stream = device
             .build_input_stream(
                 &config.into(),
                 move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    // Convert stereo to mono if needed, store raw samples
-                    let mono_samples: Vec<f32> = if channels > 1 {
-                        data.chunks(channels)
-                            .map(|chunk| chunk.iter().sum::<f32>() / channels as f32)
-                            .collect()
-                    } else {
-                        data.to_vec()
-                    };
-
-                    // Append to main buffer
-                    if let Ok(mut buf) = buffer_clone.lock() {
-                        buf.extend_from_slice(&mono_samples);
-                    }
-
-                    // Collect amplitude samples for visualization
-                    if let Ok(mut amp_buf) = amplitude_buffer_clone.lock() {
-                        amp_buf.extend_from_slice(&mono_samples);
-                        let window = amplitude_window_size / channels.max(1);
-                        if amp_buf.len() >= window {
-                            let samples: Vec<f32> = amp_buf.drain(..window).collect();
-                            let rms = calculate_rms(&samples);
-                            let amplitudes = downsample_for_visualization(&samples, 48);
-                            amplitude_callback(amplitudes, rms);
+                    producer.write(data);
+                },
+                move |err| {
+                    log::error!("Warm microphone error: {}", err);
+                    if is_device_loss(&err) {
+                        error_lost.store(true, Ordering::Release);
+                        let session_loss = error_state
+                            .lock()
+                            .ok()
+                            .and_then(|state| state.session_loss.clone());
+                        if let Some(loss) = session_loss {
+                            loss.trigger();
                         }
                     }
                 },
-                |err| {
-                    log::error!("Audio capture error: {}", err);
-                },
                 None,
             )
             .map_err(|e| format!("Failed to build input stream: {}", e))?;
 
+        let state_clone = Arc::clone(&state);
+        let consumer = ConsumerThread::spawn(move || {
+            if let Ok(mut state) = state_clone.lock() {
+                state.drain();
+            }
+        });
+
         stream
             .play()
             .map_err(|e| format!("Failed to start audio stream: {}", e))?;
 
         Ok(Self {
-            stream,
-            buffer,
+            _stream: stream,
+            _consumer: consumer,
+            state,
+            lost,
             sample_rate,
+            device_id: device_id.map(str::to_string),
+            opened_device_id,
+            opened_device_name,
+            pre_roll_ms,
+            channel,
         })
     }
 
-    /// Stop capturing and return the collected audio buffer and sample rate.
-    pub fn stop(self) -> (Vec<f32>, u32) {
-        drop(self.stream);
-        let buffer = match Arc::try_unwrap(self.buffer) {
-            Ok(mutex) => mutex.into_inner().unwrap_or_default(),
-            Err(arc) => arc.lock().map(|b| b.clone()).unwrap_or_default(),
-        };
-        (buffer, self.sample_rate)
+    /// Whether this warm microphone was opened with the given settings.
+    pub fn matches(&self, device_id: Option<&str>, pre_roll_ms: u32, channel: Option<usize>) -> bool {
+        self.device_id.as_deref() == device_id
+            && self.pre_roll_ms == pre_roll_ms
+            && self.channel == channel
+    }
+
+    /// Whether the device behind this stream has been disconnected.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Begin a capture session seeded with the buffered pre-roll audio.
+    /// The device and channel in `options` are ignored; the session records
+    /// from whatever this warm microphone was opened with.
+    pub fn attach(
+        &self,
+        amplitude_callback: AmplitudeCallback,
+        options: CaptureOptions,
+    ) -> Result<AudioCapture, String> {
+        if self.is_lost() {
+            return Err("Warm microphone device was disconnected.".to_string());
+        }
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| format!("Failed to lock warm microphone: {}", e))?;
+        if state.session.is_some() {
+            return Err("Warm microphone already has an active session.".to_string());
+        }
+
+        // Flush frames still in the ring so the pre-roll ends at this moment
+        state.drain();
+        let mut initial = CaptureBuffer::new(self.sample_rate, options.spill_dir);
+        initial.push(&state.pre_roll.to_vec());
+        state.pre_roll = PreRollBuffer::new(state.pre_roll.capacity());
+
+        let buffer = Arc::new(Mutex::new(initial));
+        let session = SessionProcessor::new(
+            Arc::clone(&buffer),
+            self.sample_rate,
+            amplitude_callback,
+            options.spectrum,
+            options.on_level_warning,
+            options.auto_stop,
+            options.limit,
+        )
+        .resampling_into(options.resampled);
+        let levels = session.levels();
+        state.session = Some(session);
+        let loss = LossSignal::new(options.on_device_lost);
+        state.session_loss = Some(Arc::clone(&loss));
+
+        Ok(AudioCapture {
+            source: CaptureSource::Warm(WarmAttachment(Arc::clone(&self.state))),
+            buffer,
+            sample_rate: self.sample_rate,
+            device_id: self.opened_device_id.clone(),
+            device_name: self.opened_device_name.clone(),
+            loss,
+            levels,
+        })
     }
 }
 
-/// Estimate buffer capacity for a given sample rate and max duration in seconds.
-/// Pre-allocating avoids repeated reallocations during long recording sessions.
-fn estimate_buffer_capacity(sample_rate: u32, max_duration_secs: u32) -> usize {
-    sample_rate as usize * max_duration_secs as usize
+/// Current metrics of a session's level monitor.
+pub(super) fn lock_metrics(levels: &Mutex<LevelMonitor>) -> InputLevelMetrics {
+    match levels.lock() {
+        Ok(levels) => levels.metrics(),
+        Err(poisoned) => poisoned.into_inner().metrics(),
+    }
 }
 
 /// Calculate root mean square of audio samples.
-fn calculate_rms(samples: &[f32]) -> f32 {
+pub(crate) fn calculate_rms(samples: &[f32]) -> f32 {
     if samples.is_empty() {
         return 0.0;
     }
@@ -210,16 +1016,250 @@ mod tests {
     }
 
     #[test]
-    fn test_preallocate_capacity_for_long_recording() {
-        // 10 minutes at 48kHz mono = 28_800_000 samples
-        let capacity = estimate_buffer_capacity(48000, 600);
-        assert!(capacity >= 28_800_000);
+    fn test_consumer_processes_synthetic_stereo_frames() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const RATE: u32 = 16000;
+        let (mut producer, mut frames) = frame_ring(RATE, 2, None);
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let updates_clone = Arc::clone(&updates);
+        let silences = Arc::new(AtomicUsize::new(0));
+        let silences_clone = Arc::clone(&silences);
+
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new(RATE, None)));
+        let mut processor = SessionProcessor::new(
+            Arc::clone(&buffer),
+            RATE,
+            Box::new(move |frame: AmplitudeFrame| {
+                assert_eq!(frame.amplitudes.len(), 48);
+                let spectrum = frame.spectrum.expect("spectrum enabled");
+                assert_eq!(spectrum.bands.len(), crate::audio::spectrum::SPECTRUM_BANDS);
+                updates_clone.fetch_add(1, Ordering::Relaxed);
+            }),
+            true,
+            None,
+            Some(AutoStop {
+                config: SilenceDetectorConfig {
+                    threshold_rms: 0.02,
+                    trailing_silence_ms: 300,
+                    min_speech_ms: 200,
+                },
+                on_silence: Box::new(move || {
+                    silences_clone.fetch_add(1, Ordering::Relaxed);
+                }),
+            }),
+            None,
+        );
+
+        // 1s of tone on the left channel only, then 1s of silence, delivered in
+        // 10ms callback-sized blocks and drained every few blocks.
+        let block_frames = RATE as usize / 100;
+        let mut frame_index = 0usize;
+        for block in 0..200 {
+            let mut interleaved = Vec::with_capacity(block_frames * 2);
+            for _ in 0..block_frames {
+                let left = if block < 100 {
+                    0.4 * (2.0 * std::f32::consts::PI * 440.0 * frame_index as f32 / RATE as f32).sin()
+                } else {
+                    0.0
+                };
+                interleaved.push(left);
+                interleaved.push(0.0);
+                frame_index += 1;
+            }
+            producer.write(&interleaved);
+            if block % 3 == 0 {
+                frames.drain(&mut |mono| processor.push(mono));
+            }
+        }
+        frames.drain(&mut |mono| processor.push(mono));
+
+        let captured = buffer.lock().unwrap().to_vec().unwrap();
+        assert_eq!(captured.len(), 2 * RATE as usize);
+        // Left-only tone is halved by the downmix
+        let peak = captured.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.2).abs() < 0.01, "Unexpected peak {}", peak);
+        assert!(captured[RATE as usize..].iter().all(|&s| s == 0.0));
+        // One update per 50ms window
+        assert_eq!(updates.load(Ordering::Relaxed), 40);
+        assert_eq!(silences.load(Ordering::Relaxed), 1);
+        assert_eq!(frames.dropped_samples(), 0);
+    }
+
+    #[test]
+    fn test_device_loss_reported_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = Arc::clone(&count);
+        let loss = LossSignal::new(Some(Arc::new(move || {
+            count_clone.fetch_add(1, Ordering::Relaxed);
+        })));
+
+        // Backend error and device-list watcher may both report the same loss
+        loss.trigger();
+        loss.trigger();
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        assert!(is_device_loss(&cpal::StreamError::DeviceNotAvailable));
+        assert!(!is_device_loss(&cpal::StreamError::BufferUnderrun));
+    }
+
+    fn config_range(channels: u16, min: u32, max: u32, format: cpal::SampleFormat) -> cpal::SupportedStreamConfigRange {
+        cpal::SupportedStreamConfigRange::new(
+            channels,
+            min,
+            max,
+            cpal::SupportedBufferSize::Unknown,
+            format,
+        )
+    }
+
+    #[test]
+    fn test_native_config_prefers_16khz_mono() {
+        let ranges = vec![
+            config_range(2, 8000, 48000, cpal::SampleFormat::F32),
+            config_range(1, 8000, 48000, cpal::SampleFormat::F32),
+        ];
+        let config = pick_native_config(&ranges, 16000, 1).unwrap();
+        assert_eq!(config.channels(), 1);
+        assert_eq!(config.sample_rate(), 16000);
+    }
+
+    #[test]
+    fn test_native_config_keeps_selected_channel() {
+        let ranges = vec![
+            config_range(1, 16000, 48000, cpal::SampleFormat::F32),
+            config_range(4, 16000, 48000, cpal::SampleFormat::F32),
+            config_range(8, 16000, 48000, cpal::SampleFormat::F32),
+        ];
+        // Channel index 2 needs at least 3 channels
+        let config = pick_native_config(&ranges, 16000, 3).unwrap();
+        assert_eq!(config.channels(), 4);
+    }
+
+    #[test]
+    fn test_native_config_requires_rate_and_float() {
+        let ranges = vec![
+            config_range(1, 44100, 48000, cpal::SampleFormat::F32),
+            config_range(1, 8000, 48000, cpal::SampleFormat::I16),
+        ];
+        assert!(pick_native_config(&ranges, 16000, 1).is_none());
+    }
+
+    #[test]
+    fn test_fallback_config_is_always_float() {
+        let float = config_range(2, 8000, 48000, cpal::SampleFormat::F32)
+            .try_with_sample_rate(44100)
+            .unwrap();
+        assert_eq!(pick_float_fallback(float.clone(), &[], 1), Some(float));
+
+        let int = config_range(2, 8000, 48000, cpal::SampleFormat::I16)
+            .try_with_sample_rate(44100)
+            .unwrap();
+        let ranges = vec![
+            config_range(2, 8000, 48000, cpal::SampleFormat::I16),
+            config_range(2, 8000, 48000, cpal::SampleFormat::F32),
+        ];
+        let config = pick_float_fallback(int.clone(), &ranges, 1).unwrap();
+        assert_eq!(config.sample_format(), cpal::SampleFormat::F32);
+        assert_eq!(config.sample_rate(), 44100);
+
+        // Without a float range at the default rate, the fastest float range is used
+        let ranges = vec![config_range(1, 48000, 48000, cpal::SampleFormat::F32)];
+        let config = pick_float_fallback(int.clone(), &ranges, 1).unwrap();
+        assert_eq!((config.sample_format(), config.sample_rate()), (cpal::SampleFormat::F32, 48000));
+
+        assert_eq!(pick_float_fallback(int, &[config_range(2, 8000, 48000, cpal::SampleFormat::U16)], 1), None);
+    }
+
+    #[test]
+    fn test_fallback_ids_distinguish_identical_devices() {
+        let first = fallback_device_id("ALSA", "USB Microphone", 0);
+        let second = fallback_device_id("ALSA", "USB Microphone", 1);
+        assert_eq!(first, "alsa:name:USB Microphone#0");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_loopback_devices_recognized_by_name() {
+        assert!(is_loopback_device(
+            "pulse:alsa_output.pci-0000_00_1f.3.analog-stereo.monitor",
+            "Built-in Audio Analog Stereo"
+        ));
+        assert!(is_loopback_device("alsa:name:Monitor of Built-in Audio#0", "Monitor of Built-in Audio"));
+        assert!(is_loopback_device("coreaudio:BlackHole2ch_UID", "BlackHole 2ch"));
+        assert!(is_loopback_device("wasapi:{0.0.1}", "Stereo Mix (Realtek Audio)"));
+        assert!(!is_loopback_device("alsa:name:USB Microphone#0", "USB Microphone"));
+    }
+
+    #[test]
+    fn test_duration_limit_warns_then_cuts_off() {
+        use std::sync::atomic::AtomicUsize;
+
+        const RATE: u32 = 1000;
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let warnings_clone = Arc::clone(&warnings);
+        let cutoffs = Arc::new(AtomicUsize::new(0));
+        let cutoffs_clone = Arc::clone(&cutoffs);
+
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new(RATE, None)));
+        let mut processor = SessionProcessor::new(
+            Arc::clone(&buffer),
+            RATE,
+            Box::new(|_| {}),
+            false,
+            None,
+            None,
+            Some(DurationLimit {
+                max_secs: 10,
+                warning_secs: 3,
+                on_warning: Box::new(move |remaining| warnings_clone.lock().unwrap().push(remaining)),
+                on_limit: Box::new(move || {
+                    cutoffs_clone.fetch_add(1, Ordering::Relaxed);
+                }),
+            }),
+        );
+
+        // 12s of audio in 250ms blocks
+        for _ in 0..48 {
+            processor.push(&[0.1; 250]);
+        }
+
+        assert_eq!(*warnings.lock().unwrap(), vec![3]);
+        assert_eq!(cutoffs.load(Ordering::Relaxed), 1);
+        assert_eq!(buffer.lock().unwrap().len(), 10 * RATE as usize);
     }
 
     #[test]
-    fn test_preallocate_capacity_scales_with_duration() {
-        let short = estimate_buffer_capacity(48000, 60);
-        let long = estimate_buffer_capacity(48000, 600);
-        assert!(long > short);
+    fn test_session_resamples_pre_roll_and_new_audio() {
+        use crate::audio::resample::resampled_len;
+        use crate::preferences::ResamplerQuality;
+
+        const RATE: u32 = 48000;
+        let mut initial = CaptureBuffer::new(RATE, None);
+        initial.push(&[0.2; 4800]);
+        let buffer = Arc::new(Mutex::new(initial));
+        let resampled = Arc::new(Mutex::new(ResampledCapture::new(ResamplerQuality::Fast, None)));
+        let mut processor = SessionProcessor::new(
+            Arc::clone(&buffer),
+            RATE,
+            Box::new(|_| {}),
+            false,
+            None,
+            None,
+            None,
+        )
+        .resampling_into(Some(Arc::clone(&resampled)));
+
+        for _ in 0..10 {
+            processor.push(&[0.2; 4800]);
+        }
+        drop(processor);
+
+        let resampled = Arc::try_unwrap(resampled).ok().unwrap().into_inner().unwrap();
+        let mut output = resampled.finish().unwrap();
+        assert_eq!(output.len(), resampled_len(11 * 4800, RATE));
+        assert!((output.to_vec().unwrap()[8000] - 0.2).abs() < 1e-3);
     }
 }
@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// File extensions accepted by [`decode_audio_file`].
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "flac", "mp3", "ogg", "opus"];
+
+/// Mono audio decoded from a file, at the file's native sample rate.
+#[derive(Debug, Clone)]
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl DecodedAudio {
+    pub fn duration_ms(&self) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        self.samples.len() as u64 * 1000 / self.sample_rate as u64
+    }
+}
+
+/// Decode a WAV, FLAC, MP3, Ogg Vorbis or Ogg Opus file and downmix it to mono.
+///
+/// `on_progress` receives the decoded fraction (0.0..=1.0) when the file
+/// reports its length up front.
+pub fn decode_audio_file(
+    path: &Path,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<DecodedAudio, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported audio format '{}'. Supported formats: {}",
+            extension,
+            SUPPORTED_EXTENSIONS.join(", ")
+        ));
+    }
+
+    // Symphonia has no Opus decoder; these are history recordings, which are mono
+    if extension == "opus" {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to open audio file: {}", e))?;
+        let (samples, sample_rate) = crate::history::opus::decode_ogg_opus(&bytes)?;
+        on_progress(1.0);
+        return Ok(DecodedAudio { samples, sample_rate });
+    }
+
+    let file = File::open(path).map_err(|e| format!("Failed to open audio file: {}", e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    hint.with_extension(&extension);
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Failed to read audio file: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| "Audio file contains no decodable track.".to_string())?;
+    let track_id = track.id;
+    let total_frames = track.codec_params.n_frames;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+
+    let mut samples = Vec::new();
+    let mut sample_buffer: Option<SampleBuffer<f32>> = None;
+    let mut last_percent = 0;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(e) => return Err(format!("Failed to read audio packet: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet loses a few milliseconds; keep going
+            Err(SymphoniaError::DecodeError(e)) => {
+                eprintln!("[decode_audio_file] Skipping corrupt packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        let needed = decoded.capacity() * spec.channels.count();
+        if sample_buffer.as_ref().is_some_and(|b| b.capacity() < needed) {
+            sample_buffer = None;
+        }
+        let buffer = sample_buffer
+            .get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec));
+        buffer.copy_interleaved_ref(decoded);
+        downmix_into(buffer.samples(), spec.channels.count(), &mut samples);
+
+        if let Some(total) = total_frames.filter(|&t| t > 0) {
+            let percent = (samples.len() as u64 * 100 / total).min(100);
+            if percent > last_percent {
+                last_percent = percent;
+                on_progress(percent as f32 / 100.0);
+            }
+        }
+    }
+
+    if sample_rate == 0 {
+        return Err("Audio file does not specify a sample rate.".to_string());
+    }
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+    })
+}
+
+/// Average interleaved frames down to mono, appending to `out`.
+fn downmix_into(data: &[f32], channels: usize, out: &mut Vec<f32>) {
+    if channels > 1 {
+        out.extend(
+            data.chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+    } else {
+        out.extend_from_slice(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downmix_stereo() {
+        let mut out = Vec::new();
+        downmix_into(&[1.0, 0.0, 0.5, -0.5], 2, &mut out);
+        assert_eq!(out, vec![0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_downmix_mono_appends() {
+        let mut out = vec![0.1];
+        downmix_into(&[0.2, 0.3], 1, &mut out);
+        assert_eq!(out, vec![0.1, 0.2, 0.3]);
+    }
+}
@@ -0,0 +1,401 @@
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+
+use super::capture::calculate_rms;
+
+/// Cutoff of the rumble/DC high-pass filter.
+const HIGH_PASS_CUTOFF_HZ: f32 = 80.0;
+
+/// Noise suppression analysis frame length in milliseconds (rounded up to a power of two).
+const NOISE_FRAME_MS: usize = 32;
+/// How much the estimated noise is over-subtracted, to keep residual noise down.
+const NOISE_OVERSUBTRACTION: f32 = 1.5;
+/// Lowest gain applied to a bin, so noise is attenuated rather than gated out.
+const NOISE_GAIN_FLOOR: f32 = 0.1;
+/// Share of the quietest frames averaged into the noise profile.
+const NOISE_REFERENCE_PERCENT: usize = 20;
+/// The noise reference must sit at least this far below the equally sized share
+/// of loudest frames, otherwise the recording has no pauses to learn the noise from.
+const NOISE_REFERENCE_MARGIN_DB: f32 = 10.0;
+
+/// Speech level that automatic gain aims for (about -20 dBFS RMS).
+const AGC_TARGET_RMS: f32 = 0.1;
+/// Never boost by more than this (about +20 dB), so near-silence isn't amplified into noise.
+const AGC_MAX_GAIN: f32 = 10.0;
+/// Peak ceiling after gain.
+const AGC_PEAK_LIMIT: f32 = 0.98;
+
+/// Which preprocessing stages to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcessingChain {
+    pub high_pass: bool,
+    pub noise_suppression: bool,
+    pub auto_gain: bool,
+}
+
+impl ProcessingChain {
+    pub fn is_empty(&self) -> bool {
+        !(self.high_pass || self.noise_suppression || self.auto_gain)
+    }
+
+    /// Run the enabled stages in place, in order: high-pass, noise suppression, gain.
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32) {
+        if samples.is_empty() || sample_rate == 0 {
+            return;
+        }
+        if self.high_pass {
+            high_pass(samples, sample_rate, HIGH_PASS_CUTOFF_HZ);
+        }
+        if self.noise_suppression {
+            suppress_noise(samples, sample_rate);
+        }
+        if self.auto_gain {
+            normalize_gain(samples, sample_rate);
+        }
+    }
+}
+
+/// Second-order Butterworth high-pass; removes DC offset and low-frequency rumble.
+pub fn high_pass(samples: &mut [f32], sample_rate: u32, cutoff_hz: f32) {
+    let omega = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32;
+    let alpha = omega.sin() / std::f32::consts::SQRT_2;
+    let cos = omega.cos();
+    let a0 = 1.0 + alpha;
+    let b0 = (1.0 + cos) / 2.0 / a0;
+    let b1 = -(1.0 + cos) / a0;
+    let b2 = b0;
+    let a1 = -2.0 * cos / a0;
+    let a2 = (1.0 - alpha) / a0;
+
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for sample in samples.iter_mut() {
+        let x0 = *sample;
+        let y0 = b0 * x0 + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+        x2 = x1;
+        x1 = x0;
+        y2 = y1;
+        y1 = y0;
+        *sample = y0;
+    }
+}
+
+/// Spectral noise suppression.
+///
+/// Builds a noise profile by averaging the spectra of the quietest frames in
+/// the recording (the pauses between words), then applies a Wiener-style gain
+/// to every frame. Stationary noise such as fans and room hum is attenuated;
+/// speech, which stands well above the profile, passes largely untouched.
+/// Recordings without clearly quieter frames are left as they are, since their
+/// quietest frames would be speech rather than noise.
+pub fn suppress_noise(samples: &mut [f32], sample_rate: u32) {
+    let frame_len = (sample_rate as usize * NOISE_FRAME_MS / 1000)
+        .next_power_of_two()
+        .max(64);
+    let hop = frame_len / 2;
+    if samples.len() < frame_len * 2 {
+        return;
+    }
+
+    // Pad so every input sample is covered by two frames
+    let mut padded = vec![0.0f32; hop];
+    padded.extend_from_slice(samples);
+    padded.resize(padded.len() + frame_len, 0.0);
+    let frame_starts: Vec<usize> = (0..=padded.len() - frame_len).step_by(hop).collect();
+
+    let mut stft = Stft::new(frame_len);
+
+    // Pass 1: pick the quietest frames as the noise reference, leaving out
+    // the frames that overlap the zero padding
+    let mut energies: Vec<(f32, usize)> = frame_starts
+        .iter()
+        .filter(|&&start| start >= hop && start + frame_len <= hop + samples.len())
+        .map(|&start| {
+            let energy = padded[start..start + frame_len].iter().map(|s| s * s).sum();
+            (energy, start)
+        })
+        .collect();
+    energies.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let reference_count = (energies.len() * NOISE_REFERENCE_PERCENT / 100).max(1);
+    let reference_energy =
+        energies[..reference_count].iter().map(|(e, _)| e).sum::<f32>() / reference_count as f32;
+    let loud_energy = energies[energies.len() - reference_count].0;
+    if reference_energy > loud_energy * 10f32.powf(-NOISE_REFERENCE_MARGIN_DB / 10.0) {
+        return;
+    }
+
+    // Pass 2: average their power spectra
+    let mut noise = vec![0.0f32; frame_len / 2 + 1];
+    for &(_, start) in &energies[..reference_count] {
+        let Some(spectrum) = stft.forward(&padded[start..start + frame_len]) else {
+            return;
+        };
+        for (n, bin) in noise.iter_mut().zip(spectrum.iter()) {
+            *n += bin.norm_sqr() / reference_count as f32;
+        }
+    }
+
+    // Pass 3: attenuate each bin by how far it stands above the noise
+    let mut output = vec![0.0f32; padded.len()];
+    let mut smoothed = vec![0.0f32; noise.len()];
+    for (frame, &start) in frame_starts.iter().enumerate() {
+        let Some(spectrum) = stft.forward(&padded[start..start + frame_len]) else {
+            return;
+        };
+        for (k, bin) in spectrum.iter_mut().enumerate() {
+            // Smoothing the power over time reduces "musical noise" artifacts
+            let power = bin.norm_sqr();
+            smoothed[k] = if frame == 0 {
+                power
+            } else {
+                0.5 * smoothed[k] + 0.5 * power
+            };
+            let gain = if smoothed[k] > 0.0 {
+                (1.0 - NOISE_OVERSUBTRACTION * noise[k] / smoothed[k]).max(NOISE_GAIN_FLOOR)
+            } else {
+                1.0
+            };
+            *bin *= gain;
+        }
+        let Some(frame_out) = stft.inverse() else {
+            return;
+        };
+        for (out, value) in output[start..start + frame_len].iter_mut().zip(frame_out) {
+            *out += value;
+        }
+    }
+
+    samples.copy_from_slice(&output[hop..hop + samples.len()]);
+}
+
+/// Windowed real FFT with matching overlap-add synthesis.
+struct Stft {
+    window: Vec<f32>,
+    forward: std::sync::Arc<dyn RealToComplex<f32>>,
+    inverse: std::sync::Arc<dyn ComplexToReal<f32>>,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+}
+
+impl Stft {
+    fn new(frame_len: usize) -> Self {
+        // sqrt-Hann for both analysis and synthesis: at 50% overlap the product sums to 1
+        let window = (0..frame_len)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / frame_len as f32;
+                (0.5 - 0.5 * phase.cos()).sqrt()
+            })
+            .collect();
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(frame_len);
+        let inverse = planner.plan_fft_inverse(frame_len);
+        let time = forward.make_input_vec();
+        let spectrum = forward.make_output_vec();
+        Self {
+            window,
+            forward,
+            inverse,
+            time,
+            spectrum,
+        }
+    }
+
+    /// Window `frame` and transform it; the spectrum may be modified before [`Stft::inverse`].
+    fn forward(&mut self, frame: &[f32]) -> Option<&mut [Complex<f32>]> {
+        for ((t, s), w) in self.time.iter_mut().zip(frame).zip(&self.window) {
+            *t = s * w;
+        }
+        self.forward.process(&mut self.time, &mut self.spectrum).ok()?;
+        Some(&mut self.spectrum)
+    }
+
+    /// Transform the current spectrum back and apply the synthesis window.
+    fn inverse(&mut self) -> Option<&[f32]> {
+        // The DC and Nyquist bins must stay real for the inverse transform
+        let last = self.spectrum.len() - 1;
+        self.spectrum[0].im = 0.0;
+        self.spectrum[last].im = 0.0;
+        self.inverse.process(&mut self.spectrum, &mut self.time).ok()?;
+        let scale = 1.0 / self.time.len() as f32;
+        for (t, w) in self.time.iter_mut().zip(&self.window) {
+            *t *= w * scale;
+        }
+        Some(&self.time)
+    }
+}
+
+/// Scale the recording so speech sits near [`AGC_TARGET_RMS`].
+///
+/// The level is measured on the loudest 50ms windows rather than the whole
+/// recording, so long pauses don't cause over-amplification.
+pub fn normalize_gain(samples: &mut [f32], sample_rate: u32) {
+    let window = (sample_rate as usize / 20).max(1);
+    let mut levels: Vec<f32> = samples.chunks(window).map(calculate_rms).collect();
+    if levels.is_empty() {
+        return;
+    }
+    levels.sort_by(|a, b| a.total_cmp(b));
+    let speech_level = levels[levels.len() * 9 / 10];
+    if speech_level <= f32::EPSILON {
+        return;
+    }
+
+    let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let mut gain = (AGC_TARGET_RMS / speech_level).min(AGC_MAX_GAIN);
+    if peak * gain > AGC_PEAK_LIMIT {
+        gain = AGC_PEAK_LIMIT / peak;
+    }
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(len: usize, freq: f32, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    /// Deterministic white noise in [-amplitude, amplitude].
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    /// Tone bursts (0.5s on, 0.5s off) over 4 seconds, like speech with pauses.
+    fn bursts() -> Vec<f32> {
+        let mut clean = tone(RATE as usize * 4, 440.0, 0.3);
+        for (i, s) in clean.iter_mut().enumerate() {
+            if (i / (RATE as usize / 2)) % 2 == 1 {
+                *s = 0.0;
+            }
+        }
+        clean
+    }
+
+    fn snr_db(clean: &[f32], processed: &[f32]) -> f32 {
+        let signal: f32 = clean.iter().map(|s| s * s).sum();
+        let error: f32 = clean
+            .iter()
+            .zip(processed)
+            .map(|(c, p)| (c - p) * (c - p))
+            .sum();
+        10.0 * (signal / error).log10()
+    }
+
+    fn mix(a: &[f32], b: &[f32]) -> Vec<f32> {
+        a.iter().zip(b).map(|(x, y)| x + y).collect()
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc_and_rumble() {
+        let clean = tone(RATE as usize * 2, 1000.0, 0.3);
+        let rumble: Vec<f32> = tone(clean.len(), 20.0, 0.2).iter().map(|s| s + 0.1).collect();
+        let mut noisy = mix(&clean, &rumble);
+        let before = snr_db(&clean, &noisy);
+
+        high_pass(&mut noisy, RATE, HIGH_PASS_CUTOFF_HZ);
+        // Skip the filter's settling time
+        let settle = RATE as usize / 10;
+        let after = snr_db(&clean[settle..], &noisy[settle..]);
+        assert!(after > before + 10.0, "SNR {} dB -> {} dB", before, after);
+    }
+
+    #[test]
+    fn test_noise_suppression_improves_snr() {
+        let clean = bursts();
+        let mut noisy = mix(&clean, &noise(clean.len(), 0.1));
+        let before = snr_db(&clean, &noisy);
+
+        suppress_noise(&mut noisy, RATE);
+        let after = snr_db(&clean, &noisy);
+        assert!(after > before + 6.0, "SNR {} dB -> {} dB", before, after);
+    }
+
+    #[test]
+    fn test_noise_suppression_preserves_clean_signal() {
+        let clean = bursts();
+        let mut processed = clean.clone();
+        suppress_noise(&mut processed, RATE);
+        assert_eq!(processed.len(), clean.len());
+        assert!(snr_db(&clean, &processed) > 15.0);
+    }
+
+    #[test]
+    fn test_noise_suppression_skips_input_without_pauses() {
+        let mut continuous = tone(RATE as usize * 4, 440.0, 0.3);
+        let original = continuous.clone();
+        suppress_noise(&mut continuous, RATE);
+        assert_eq!(continuous, original);
+    }
+
+    #[test]
+    fn test_noise_suppression_skips_short_input() {
+        let mut short = noise(100, 0.1);
+        let original = short.clone();
+        suppress_noise(&mut short, RATE);
+        assert_eq!(short, original);
+    }
+
+    #[test]
+    fn test_auto_gain_boosts_quiet_speech() {
+        let mut quiet = tone(RATE as usize, 440.0, 0.02);
+        normalize_gain(&mut quiet, RATE);
+        let rms = calculate_rms(&quiet);
+        assert!((rms - AGC_TARGET_RMS).abs() < 0.01, "RMS {}", rms);
+    }
+
+    #[test]
+    fn test_auto_gain_respects_peak_limit_and_max_gain() {
+        let mut spiky = tone(RATE as usize, 440.0, 0.05);
+        spiky[100] = 0.5;
+        normalize_gain(&mut spiky, RATE);
+        assert!(spiky.iter().all(|s| s.abs() <= AGC_PEAK_LIMIT + 1e-6));
+
+        let mut faint = tone(RATE as usize, 440.0, 0.001);
+        normalize_gain(&mut faint, RATE);
+        let peak = faint.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak <= 0.001 * AGC_MAX_GAIN + 1e-6);
+    }
+
+    #[test]
+    fn test_full_chain_improves_snr() {
+        let clean = bursts();
+        let dc_and_noise: Vec<f32> = noise(clean.len(), 0.1).iter().map(|s| s + 0.05).collect();
+        let mut noisy = mix(&clean, &dc_and_noise);
+        let before = snr_db(&clean, &noisy);
+
+        let chain = ProcessingChain {
+            high_pass: true,
+            noise_suppression: true,
+            auto_gain: false,
+        };
+        chain.process(&mut noisy, RATE);
+        // The high-pass filter's phase shift counts as error against the clean
+        // reference, so the measured gain is lower than for suppression alone
+        let after = snr_db(&clean, &noisy);
+        assert!(after > before + 4.0, "SNR {} dB -> {} dB", before, after);
+    }
+
+    #[test]
+    fn test_empty_chain_is_noop() {
+        let chain = ProcessingChain::default();
+        assert!(chain.is_empty());
+        let mut samples = noise(1000, 0.1);
+        let original = samples.clone();
+        chain.process(&mut samples, RATE);
+        assert_eq!(samples, original);
+    }
+}
@@ -0,0 +1,216 @@
+//! Input level diagnostics: peak level, clipping and noise floor.
+
+use serde::{Deserialize, Serialize};
+
+/// Samples at or above this magnitude count as clipped.
+const CLIP_THRESHOLD: f32 = 0.999;
+/// Warn about clipping once more than this fraction of samples has clipped.
+const CLIPPING_WARNING_RATIO: f32 = 0.001;
+/// Ignore a few stray clipped samples, e.g. from a plosive or a bumped mic.
+const CLIPPING_WARNING_MIN_SAMPLES: u64 = 50;
+/// Warn that input is too quiet when no window has been louder than this by
+/// [`TOO_QUIET_AFTER_SECS`] into the session.
+const TOO_QUIET_DBFS: f32 = -45.0;
+const TOO_QUIET_AFTER_SECS: u32 = 3;
+/// Level reported for digital silence.
+const MIN_DBFS: f32 = -120.0;
+/// Window levels at or below these percentiles are taken as the noise floor
+/// and the speech level.
+const NOISE_FLOOR_PERCENTILE: f32 = 0.1;
+const SPEECH_LEVEL_PERCENTILE: f32 = 0.95;
+
+/// Why the input is likely to transcribe badly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelWarningKind {
+    /// The signal is overdriven; lower the input gain.
+    Clipping,
+    /// The signal is barely above silence; the mic may be muted or too far away.
+    TooQuiet,
+}
+
+/// Summary of a session's input levels, kept with its history entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputLevelMetrics {
+    /// Loudest sample, in dBFS.
+    pub peak_dbfs: f32,
+    /// Fraction of samples that clipped.
+    pub clipping_ratio: f32,
+    /// Level of the quieter stretches between words, in dBFS.
+    pub noise_floor_dbfs: f32,
+    /// Level of the louder stretches, typically speech, in dBFS.
+    pub speech_level_dbfs: f32,
+}
+
+/// Tracks input levels over a session, one analysis window at a time.
+///
+/// Window levels are kept in a 1dB histogram so percentiles stay cheap for
+/// sessions of any length.
+pub struct LevelMonitor {
+    sample_rate: u32,
+    samples: u64,
+    clipped: u64,
+    peak: f32,
+    histogram: Vec<u32>,
+    windows: u32,
+    loudest_window_dbfs: f32,
+    warned_clipping: bool,
+    warned_too_quiet: bool,
+}
+
+impl LevelMonitor {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            samples: 0,
+            clipped: 0,
+            peak: 0.0,
+            histogram: vec![0; -MIN_DBFS as usize + 1],
+            windows: 0,
+            loudest_window_dbfs: MIN_DBFS,
+            warned_clipping: false,
+            warned_too_quiet: false,
+        }
+    }
+
+    /// Add one analysis window. Returns a warning the first time in the
+    /// session that the input is clipping or too quiet.
+    pub fn process(&mut self, window: &[f32], rms: f32) -> Option<LevelWarningKind> {
+        if window.is_empty() {
+            return None;
+        }
+        self.samples += window.len() as u64;
+        for &sample in window {
+            let magnitude = sample.abs();
+            self.peak = self.peak.max(magnitude);
+            if magnitude >= CLIP_THRESHOLD {
+                self.clipped += 1;
+            }
+        }
+        let level = to_dbfs(rms);
+        self.histogram[(-level).round() as usize] += 1;
+        self.windows += 1;
+        self.loudest_window_dbfs = self.loudest_window_dbfs.max(level);
+
+        if !self.warned_clipping
+            && self.clipped >= CLIPPING_WARNING_MIN_SAMPLES
+            && self.clipped as f32 / self.samples as f32 > CLIPPING_WARNING_RATIO
+        {
+            self.warned_clipping = true;
+            return Some(LevelWarningKind::Clipping);
+        }
+        if !self.warned_too_quiet
+            && self.samples >= TOO_QUIET_AFTER_SECS as u64 * self.sample_rate as u64
+            && self.loudest_window_dbfs < TOO_QUIET_DBFS
+        {
+            self.warned_too_quiet = true;
+            return Some(LevelWarningKind::TooQuiet);
+        }
+        None
+    }
+
+    /// Levels over everything processed so far.
+    pub fn metrics(&self) -> InputLevelMetrics {
+        InputLevelMetrics {
+            peak_dbfs: to_dbfs(self.peak),
+            clipping_ratio: if self.samples == 0 {
+                0.0
+            } else {
+                self.clipped as f32 / self.samples as f32
+            },
+            noise_floor_dbfs: self.percentile(NOISE_FLOOR_PERCENTILE),
+            speech_level_dbfs: self.percentile(SPEECH_LEVEL_PERCENTILE),
+        }
+    }
+
+    /// Window level, in dBFS, that `fraction` of windows are at or below.
+    fn percentile(&self, fraction: f32) -> f32 {
+        if self.windows == 0 {
+            return MIN_DBFS;
+        }
+        let target = (fraction * self.windows as f32).ceil().max(1.0) as u32;
+        let mut seen = 0;
+        // Quietest bins are at the end of the histogram
+        for (index, &count) in self.histogram.iter().enumerate().rev() {
+            seen += count;
+            if seen >= target {
+                return -(index as f32);
+            }
+        }
+        0.0
+    }
+}
+
+fn to_dbfs(magnitude: f32) -> f32 {
+    if magnitude <= 0.0 {
+        return MIN_DBFS;
+    }
+    (20.0 * magnitude.log10()).clamp(MIN_DBFS, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::capture::calculate_rms;
+
+    const RATE: u32 = 16000;
+    const WINDOW: usize = 800;
+
+    fn feed(monitor: &mut LevelMonitor, samples: &[f32]) -> Vec<LevelWarningKind> {
+        samples
+            .chunks(WINDOW)
+            .filter_map(|window| monitor.process(window, calculate_rms(window)))
+            .collect()
+    }
+
+    fn tone(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_healthy_speech_has_no_warnings() {
+        let mut monitor = LevelMonitor::new(RATE);
+        let mut audio = vec![0.001; RATE as usize];
+        audio.extend(tone(0.2, RATE as usize * 3));
+        assert!(feed(&mut monitor, &audio).is_empty());
+
+        let metrics = monitor.metrics();
+        assert!((metrics.peak_dbfs - -14.0).abs() < 0.5, "{:?}", metrics);
+        assert_eq!(metrics.clipping_ratio, 0.0);
+        assert!((metrics.noise_floor_dbfs - -60.0).abs() < 1.0, "{:?}", metrics);
+        assert!((metrics.speech_level_dbfs - -17.0).abs() < 1.0, "{:?}", metrics);
+    }
+
+    #[test]
+    fn test_overdriven_input_warns_once() {
+        let mut monitor = LevelMonitor::new(RATE);
+        let clipped: Vec<f32> = tone(3.0, RATE as usize * 2)
+            .into_iter()
+            .map(|s| s.clamp(-1.0, 1.0))
+            .collect();
+        assert_eq!(feed(&mut monitor, &clipped), vec![LevelWarningKind::Clipping]);
+        assert!(monitor.metrics().clipping_ratio > 0.5);
+    }
+
+    #[test]
+    fn test_muted_input_warns_after_a_few_seconds() {
+        let mut monitor = LevelMonitor::new(RATE);
+        let muted = tone(0.002, RATE as usize * (TOO_QUIET_AFTER_SECS as usize - 1));
+        assert!(feed(&mut monitor, &muted).is_empty());
+        assert_eq!(
+            feed(&mut monitor, &tone(0.002, RATE as usize * 2)),
+            vec![LevelWarningKind::TooQuiet]
+        );
+    }
+
+    #[test]
+    fn test_silence_reports_minimum_level() {
+        let mut monitor = LevelMonitor::new(RATE);
+        feed(&mut monitor, &[0.0; WINDOW * 4]);
+        let metrics = monitor.metrics();
+        assert_eq!(metrics.peak_dbfs, MIN_DBFS);
+        assert_eq!(metrics.noise_floor_dbfs, MIN_DBFS);
+    }
+}
@@ -0,0 +1,81 @@
+//! System audio recorded next to the microphone, for transcribing calls.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::capture::{list_input_devices, AudioCapture, CaptureOptions};
+use super::resample::{ResampledCapture, StreamingResampler};
+use super::source::AudioSource;
+use crate::preferences::{ResamplerQuality, SystemAudioSettings};
+
+/// The system audio track of a call recording, captured from a loopback
+/// device and resampled to 16kHz as it arrives.
+pub struct LoopbackCapture {
+    capture: Box<AudioCapture>,
+    resampled: Arc<Mutex<ResampledCapture>>,
+    quality: ResamplerQuality,
+}
+
+impl LoopbackCapture {
+    pub fn start(
+        settings: &SystemAudioSettings,
+        quality: ResamplerQuality,
+        spill_dir: Option<PathBuf>,
+    ) -> Result<Self, String> {
+        let devices = list_input_devices()?;
+        // Capture falls back to the default input for unknown ids, which would
+        // record the microphone twice
+        let device = match settings.device_id.as_deref() {
+            Some(id) => devices
+                .iter()
+                .find(|d| d.id == id)
+                .ok_or_else(|| "The selected system audio device is not available.".to_string())?,
+            None => devices.iter().find(|d| d.is_loopback).ok_or_else(|| {
+                "No system audio device found. Enable a loopback or monitor input first.".to_string()
+            })?,
+        };
+
+        let resampled = Arc::new(Mutex::new(ResampledCapture::new(quality, spill_dir.clone())));
+        let capture = AudioCapture::start(
+            Box::new(|_| {}),
+            CaptureOptions {
+                device_id: Some(device.id.clone()),
+                spill_dir,
+                resampled: Some(Arc::clone(&resampled)),
+                ..Default::default()
+            },
+        )?;
+        Ok(Self {
+            capture: Box::new(capture),
+            resampled,
+            quality,
+        })
+    }
+
+    pub fn device_name(&self) -> &str {
+        self.capture.device_name()
+    }
+
+    /// Stop capturing and return the track at 16kHz.
+    pub fn finish(self) -> Result<Vec<f32>, String> {
+        let (mut buffer, sample_rate) = self.capture.stop();
+        let resampled = Arc::try_unwrap(self.resampled)
+            .ok()
+            .and_then(|resampled| resampled.into_inner().ok())
+            .map(ResampledCapture::finish);
+        match resampled {
+            Some(Ok(mut output)) => output.to_vec(),
+            // Resample the capture buffer after all
+            resampled => {
+                if let Some(Err(e)) = resampled {
+                    log::warn!("Incremental resampling of system audio failed: {}", e);
+                }
+                let mut output = Vec::new();
+                let mut resampler = StreamingResampler::with_quality(sample_rate, self.quality)?;
+                buffer.stream_from(0, &mut |chunk| resampler.push(chunk, &mut output))?;
+                resampler.finish(&mut output)?;
+                Ok(output)
+            }
+        }
+    }
+}
@@ -1,8 +1,24 @@
+pub mod buffer;
 pub mod capture;
+pub mod decode;
+pub mod dsp;
+pub mod level;
+pub mod loopback;
+pub mod playback;
+pub mod preroll;
 pub mod resample;
+pub mod ring;
+pub mod source;
+pub mod spectrum;
+pub mod vad;
+pub mod watcher;
 
 use serde::{Deserialize, Serialize};
 
+use level::{InputLevelMetrics, LevelWarningKind};
+
+use crate::transcription::engine::TranscriptSegment;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecordingSession {
@@ -37,9 +53,50 @@ pub enum TargetWindowRef {
 pub enum AudioEvent {
     RecordingStarted,
     RecordingStopped,
-    AmplitudeUpdate { amplitudes: Vec<f32>, rms: f32 },
+    /// Levels for the visualizer. `bands` and `peaks` are log-spaced frequency
+    /// band levels, present when the overlay style uses the spectrum mode.
+    AmplitudeUpdate {
+        amplitudes: Vec<f32>,
+        rms: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bands: Option<Vec<f32>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        peaks: Option<Vec<f32>>,
+    },
     TranscriptionStarted,
-    TranscriptionCompleted { text: String },
+    /// The session's text is final. `confidence` is the mean word
+    /// probability and `segments` carry per-segment and per-word timing and
+    /// probability, for engines that report them. `confidence_available` is
+    /// false for engines that never do (Parakeet), whose results are never held.
+    TranscriptionCompleted {
+        text: String,
+        confidence: Option<f32>,
+        #[serde(rename = "confidenceAvailable")]
+        confidence_available: bool,
+        segments: Vec<TranscriptSegment>,
+    },
+    /// The recording was silent; transcription was skipped.
+    NoSpeechDetected,
+    /// Best transcript so far while recording; `is_final` once the session's text is settled.
+    PartialTranscription {
+        text: String,
+        #[serde(rename = "isFinal")]
+        is_final: bool,
+    },
+    /// The recording device was disconnected. `fallback_device` names the default
+    /// device the session continued on; when `None` the session is being stopped.
+    DeviceLost {
+        #[serde(rename = "fallbackDevice")]
+        fallback_device: Option<String>,
+    },
+    /// The input is clipping or too quiet to transcribe well. Sent at most once
+    /// per kind per recording.
+    LevelWarning { kind: LevelWarningKind },
+    /// The recording will be stopped at the maximum duration in `remaining_secs`.
+    RecordingLimitWarning {
+        #[serde(rename = "remainingSecs")]
+        remaining_secs: u32,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,4 +105,28 @@ pub struct TranscriptionResult {
     pub session_id: String,
     pub text: String,
     pub duration_ms: u64,
+    /// The recording contained no speech, so nothing was transcribed.
+    #[serde(default)]
+    pub no_speech: bool,
+    /// Language the speech was transcribed as, when the engine reports it.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Mean word probability (0.0–1.0), when the engine reports it.
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// Confidence was below the user's threshold: confirm the text before
+    /// injecting it.
+    #[serde(default)]
+    pub held: bool,
+}
+
+/// Outcome of a microphone test, returned as its recording starts playing back.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicTestResult {
+    pub device_name: String,
+    pub duration_ms: u64,
+    pub input_levels: InputLevelMetrics,
+    /// Why the recording could not be played back, if it couldn't.
+    pub playback_error: Option<String>,
 }
@@ -0,0 +1,108 @@
+//! Playback of recorded audio through the default output device.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::Stream;
+
+/// Plays a mono recording once through the default output device. Playback
+/// stops when this is dropped.
+pub struct AudioPlayer {
+    _stream: Stream,
+}
+
+impl AudioPlayer {
+    /// Start playing `samples`, recorded at `sample_rate`.
+    pub fn play(samples: &[f32], sample_rate: u32) -> Result<Self, String> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| "No output device available.".to_string())?;
+        let config = choose_output_config(&device, sample_rate)?;
+        let channels = config.channels() as usize;
+        let output_rate = config.sample_rate();
+
+        let samples = resample_linear(samples, sample_rate, output_rate);
+        let mut position = 0;
+        let stream = device
+            .build_output_stream(
+                &config.into(),
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    for frame in data.chunks_mut(channels) {
+                        frame.fill(samples.get(position).copied().unwrap_or(0.0));
+                        position = (position + 1).min(samples.len());
+                    }
+                },
+                |err| log::error!("Audio playback error: {}", err),
+                None,
+            )
+            .map_err(|e| format!("Failed to build output stream: {}", e))?;
+        stream
+            .play()
+            .map_err(|e| format!("Failed to start audio playback: {}", e))?;
+
+        Ok(Self { _stream: stream })
+    }
+}
+
+/// Pick a float output configuration, at `sample_rate` when the device
+/// supports it so the recording plays back unresampled.
+fn choose_output_config(
+    device: &cpal::Device,
+    sample_rate: u32,
+) -> Result<cpal::SupportedStreamConfig, String> {
+    let ranges: Vec<_> = device
+        .supported_output_configs()
+        .map_err(|e| format!("Failed to query output configs: {}", e))?
+        .filter(|range| range.sample_format() == cpal::SampleFormat::F32)
+        .collect();
+    if let Some(config) = ranges
+        .iter()
+        .filter_map(|range| range.try_with_sample_rate(sample_rate))
+        .min_by_key(|config| config.channels())
+    {
+        return Ok(config);
+    }
+
+    let default = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to get output config: {}", e))?;
+    ranges
+        .iter()
+        .find_map(|range| range.try_with_sample_rate(default.sample_rate()))
+        .ok_or_else(|| "Output device does not support float samples.".to_string())
+}
+
+/// Resample by linear interpolation. Plenty for listening back to a mic test.
+fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / step).floor() as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let frac = (position - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample_linear_keeps_duration_and_shape() {
+        let ramp: Vec<f32> = (0..16000).map(|i| i as f32 / 16000.0).collect();
+        let resampled = resample_linear(&ramp, 16000, 48000);
+        assert_eq!(resampled.len(), 48000);
+        assert!((resampled[24000] - 0.5).abs() < 1e-4);
+        assert!((resampled[1] - 1.0 / 48000.0).abs() < 1e-6);
+
+        assert_eq!(resample_linear(&ramp, 16000, 8000).len(), 8000);
+        assert_eq!(resample_linear(&ramp, 16000, 16000), ramp);
+    }
+}
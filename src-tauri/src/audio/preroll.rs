@@ -0,0 +1,121 @@
+/// Fixed-size ring of the most recent mono samples.
+///
+/// Kept filled by the warm microphone so a new recording can start with the
+/// audio from just before the hotkey was pressed.
+pub struct PreRollBuffer {
+    samples: Vec<f32>,
+    write_pos: usize,
+    len: usize,
+}
+
+impl PreRollBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: vec![0.0; capacity],
+            write_pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Ring size needed to hold `duration_ms` of audio at `sample_rate`.
+    pub fn capacity_for(sample_rate: u32, duration_ms: u32) -> usize {
+        sample_rate as usize * duration_ms as usize / 1000
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append samples, overwriting the oldest once full.
+    pub fn push(&mut self, data: &[f32]) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        // Only the newest `capacity` samples can survive
+        let data = &data[data.len().saturating_sub(capacity)..];
+
+        let first = data.len().min(capacity - self.write_pos);
+        self.samples[self.write_pos..self.write_pos + first].copy_from_slice(&data[..first]);
+        let rest = data.len() - first;
+        self.samples[..rest].copy_from_slice(&data[first..]);
+
+        self.write_pos = (self.write_pos + data.len()) % capacity;
+        self.len = (self.len + data.len()).min(capacity);
+    }
+
+    /// Buffered samples, oldest first.
+    pub fn to_vec(&self) -> Vec<f32> {
+        let capacity = self.capacity();
+        let start = (self.write_pos + capacity - self.len) % capacity.max(1);
+        let mut out = Vec::with_capacity(self.len);
+        if start + self.len <= capacity {
+            out.extend_from_slice(&self.samples[start..start + self.len]);
+        } else {
+            out.extend_from_slice(&self.samples[start..]);
+            out.extend_from_slice(&self.samples[..self.write_pos]);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacity_for_duration() {
+        assert_eq!(PreRollBuffer::capacity_for(48000, 500), 24000);
+        assert_eq!(PreRollBuffer::capacity_for(16000, 300), 4800);
+    }
+
+    #[test]
+    fn test_partial_fill_returns_in_order() {
+        let mut ring = PreRollBuffer::new(8);
+        ring.push(&[1.0, 2.0, 3.0]);
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.to_vec(), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_wraparound_keeps_newest() {
+        let mut ring = PreRollBuffer::new(4);
+        ring.push(&[1.0, 2.0, 3.0]);
+        ring.push(&[4.0, 5.0, 6.0]);
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.to_vec(), vec![3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_push_larger_than_capacity() {
+        let mut ring = PreRollBuffer::new(3);
+        ring.push(&[1.0]);
+        ring.push(&[2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(ring.to_vec(), vec![4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_many_small_pushes() {
+        let mut ring = PreRollBuffer::new(5);
+        for i in 0..23 {
+            ring.push(&[i as f32]);
+        }
+        assert_eq!(ring.to_vec(), vec![18.0, 19.0, 20.0, 21.0, 22.0]);
+    }
+
+    #[test]
+    fn test_zero_capacity_is_noop() {
+        let mut ring = PreRollBuffer::new(0);
+        ring.push(&[1.0, 2.0]);
+        assert!(ring.is_empty());
+        assert!(ring.to_vec().is_empty());
+    }
+}
@@ -1,70 +1,299 @@
-use rubato::{FftFixedInOut, Resampler};
+use std::path::PathBuf;
+
+use rubato::{
+    FastFixedIn, FftFixedInOut, PolynomialDegree, SincFixedIn, SincInterpolationParameters,
+    SincInterpolationType, VecResampler, WindowFunction,
+};
+
+use super::buffer::CaptureBuffer;
+use crate::preferences::ResamplerQuality;
 
 const WHISPER_SAMPLE_RATE: u32 = 16000;
+/// Input frames per resampler call.
+const CHUNK_SIZE: usize = 1024;
 
 /// Resample audio from the source sample rate to 16kHz mono f32 for Whisper.
 /// If the source is already 16kHz, returns the input unchanged.
 pub fn resample_to_16khz(audio: &[f32], source_rate: u32) -> Result<Vec<f32>, String> {
+    resample_to_16khz_with(audio, source_rate, ResamplerQuality::default())
+}
+
+/// [`resample_to_16khz`] with a chosen resampler.
+pub fn resample_to_16khz_with(
+    audio: &[f32],
+    source_rate: u32,
+    quality: ResamplerQuality,
+) -> Result<Vec<f32>, String> {
     if source_rate == WHISPER_SAMPLE_RATE {
         return Ok(audio.to_vec());
     }
 
-    if audio.is_empty() {
-        return Ok(Vec::new());
+    let mut output = Vec::with_capacity(resampled_len(audio.len(), source_rate));
+    let mut resampler = StreamingResampler::with_quality(source_rate, quality)?;
+    resampler.push(audio, &mut output)?;
+    resampler.finish(&mut output)?;
+    Ok(output)
+}
+
+/// Number of 16kHz samples that `source_len` samples at `source_rate` resample to.
+pub fn resampled_len(source_len: usize, source_rate: u32) -> usize {
+    let rate = source_rate.max(1) as u64;
+    ((source_len as u64 * WHISPER_SAMPLE_RATE as u64 + rate / 2) / rate) as usize
+}
+
+/// Incremental resampler to 16kHz, for audio that arrives (or is read back)
+/// in chunks. Produces the same output as [`resample_to_16khz_with`] on the
+/// concatenated input.
+///
+/// The resampler's own delay is trimmed from the start, so output sample `n`
+/// lines up with input time `n / 16000`, and the output is exactly
+/// [`resampled_len`] samples long.
+pub struct StreamingResampler {
+    resampler: Option<Box<dyn VecResampler<f32>>>,
+    source_rate: u32,
+    pending: Vec<f32>,
+    /// Leading output frames still to discard.
+    delay: usize,
+    input_len: usize,
+    output_len: usize,
+}
+
+impl StreamingResampler {
+    pub fn new(source_rate: u32) -> Result<Self, String> {
+        Self::with_quality(source_rate, ResamplerQuality::default())
     }
 
-    let chunk_size = 1024;
-    let mut resampler = FftFixedInOut::<f32>::new(
-        source_rate as usize,
-        WHISPER_SAMPLE_RATE as usize,
-        chunk_size,
-        1, // mono
-    )
-    .map_err(|e| format!("Failed to create resampler: {}", e))?;
+    pub fn with_quality(source_rate: u32, quality: ResamplerQuality) -> Result<Self, String> {
+        let resampler = if source_rate == WHISPER_SAMPLE_RATE {
+            None
+        } else {
+            Some(build_resampler(source_rate, quality)?)
+        };
+        Ok(Self {
+            delay: resampler.as_ref().map_or(0, |r| r.output_delay()),
+            resampler,
+            source_rate,
+            pending: Vec::new(),
+            input_len: 0,
+            output_len: 0,
+        })
+    }
 
-    let mut output = Vec::new();
-    let frames_needed = resampler.input_frames_next();
+    /// Resample as much of `audio` as fills whole resampler chunks, appending
+    /// to `output`. The remainder is kept for the next call.
+    pub fn push(&mut self, audio: &[f32], output: &mut Vec<f32>) -> Result<(), String> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            output.extend_from_slice(audio);
+            return Ok(());
+        };
+        self.input_len += audio.len();
 
-    // Process in chunks
-    let mut offset = 0;
-    while offset + frames_needed <= audio.len() {
-        let chunk = &audio[offset..offset + frames_needed];
-        let input = vec![chunk.to_vec()];
-        let result = resampler
-            .process(&input, None)
-            .map_err(|e| format!("Resampling failed: {}", e))?;
-        if let Some(channel) = result.first() {
-            output.extend_from_slice(channel);
+        let mut audio = audio;
+        // Complete a chunk started by the previous call
+        if !self.pending.is_empty() {
+            let frames_needed = resampler.input_frames_next();
+            let take = (frames_needed - self.pending.len()).min(audio.len());
+            self.pending.extend_from_slice(&audio[..take]);
+            audio = &audio[take..];
+            if self.pending.len() < frames_needed {
+                return Ok(());
+            }
+            let chunk = std::mem::take(&mut self.pending);
+            self.output_len += process_chunk(resampler.as_mut(), chunk, &mut self.delay, output)?;
         }
-        offset += frames_needed;
+
+        loop {
+            let frames_needed = resampler.input_frames_next();
+            if audio.len() < frames_needed {
+                break;
+            }
+            let (chunk, rest) = audio.split_at(frames_needed);
+            audio = rest;
+            self.output_len +=
+                process_chunk(resampler.as_mut(), chunk.to_vec(), &mut self.delay, output)?;
+        }
+        self.pending.extend_from_slice(audio);
+        Ok(())
     }
 
-    // Handle remaining samples by zero-padding
-    if offset < audio.len() {
-        let remaining = &audio[offset..];
-        let mut padded = remaining.to_vec();
-        padded.resize(frames_needed, 0.0);
-        let input = vec![padded];
-        let result = resampler
-            .process(&input, None)
-            .map_err(|e| format!("Resampling failed on final chunk: {}", e))?;
-        if let Some(channel) = result.first() {
-            // Only take proportional output for the actual remaining samples
-            let expected_output = (remaining.len() as f64
-                * WHISPER_SAMPLE_RATE as f64
-                / source_rate as f64) as usize;
-            let take = expected_output.min(channel.len());
-            output.extend_from_slice(&channel[..take]);
+    /// Flush the remaining samples by feeding silence until the delayed
+    /// output has caught up with the input.
+    pub fn finish(self, output: &mut Vec<f32>) -> Result<(), String> {
+        let Some(mut resampler) = self.resampler else {
+            return Ok(());
+        };
+        let expected = resampled_len(self.input_len, self.source_rate);
+        let mut pending = self.pending;
+        let mut delay = self.delay;
+        let mut produced = self.output_len;
+
+        while produced < expected {
+            let mut chunk = std::mem::take(&mut pending);
+            chunk.resize(resampler.input_frames_next(), 0.0);
+            produced += process_chunk(resampler.as_mut(), chunk, &mut delay, output)
+                .map_err(|e| format!("{} (final chunk)", e))?;
         }
+        output.truncate(output.len() - (produced - expected));
+        Ok(())
     }
+}
 
-    Ok(output)
+fn build_resampler(
+    source_rate: u32,
+    quality: ResamplerQuality,
+) -> Result<Box<dyn VecResampler<f32>>, String> {
+    let ratio = WHISPER_SAMPLE_RATE as f64 / source_rate as f64;
+    let error = |e: rubato::ResamplerConstructionError| format!("Failed to create resampler: {}", e);
+    Ok(match quality {
+        ResamplerQuality::Fast => Box::new(
+            FastFixedIn::<f32>::new(ratio, 1.0, PolynomialDegree::Linear, CHUNK_SIZE, 1)
+                .map_err(error)?,
+        ),
+        ResamplerQuality::Balanced => Box::new(
+            FftFixedInOut::<f32>::new(
+                source_rate as usize,
+                WHISPER_SAMPLE_RATE as usize,
+                CHUNK_SIZE,
+                1, // mono
+            )
+            .map_err(error)?,
+        ),
+        ResamplerQuality::High => {
+            let parameters = SincInterpolationParameters {
+                sinc_len: 256,
+                f_cutoff: 0.95,
+                oversampling_factor: 256,
+                interpolation: SincInterpolationType::Cubic,
+                window: WindowFunction::BlackmanHarris2,
+            };
+            Box::new(SincFixedIn::<f32>::new(ratio, 1.0, parameters, CHUNK_SIZE, 1).map_err(error)?)
+        }
+    })
+}
+
+/// Resample one chunk, dropping whatever is left of the initial delay.
+/// Returns the number of frames appended to `output`.
+fn process_chunk(
+    resampler: &mut dyn VecResampler<f32>,
+    chunk: Vec<f32>,
+    delay: &mut usize,
+    output: &mut Vec<f32>,
+) -> Result<usize, String> {
+    let result = resampler
+        .process(&[chunk], None)
+        .map_err(|e| format!("Resampling failed: {}", e))?;
+    let Some(channel) = result.first() else {
+        return Ok(0);
+    };
+    let skip = (*delay).min(channel.len());
+    *delay -= skip;
+    output.extend_from_slice(&channel[skip..]);
+    Ok(channel.len() - skip)
+}
+
+/// A 16kHz copy of a recording, resampled while the session is running so
+/// that stopping only has to flush the last chunk.
+///
+/// Created before the capture starts and bound to its sample rate by
+/// [`ResampledCapture::start`]. The first error is kept and reported by
+/// [`ResampledCapture::finish`]; audio pushed after it is ignored.
+pub struct ResampledCapture {
+    quality: ResamplerQuality,
+    resampler: Option<StreamingResampler>,
+    output: CaptureBuffer,
+    scratch: Vec<f32>,
+    error: Option<String>,
+}
+
+impl ResampledCapture {
+    pub fn new(quality: ResamplerQuality, spill_dir: Option<PathBuf>) -> Self {
+        Self {
+            quality,
+            resampler: None,
+            output: CaptureBuffer::new(WHISPER_SAMPLE_RATE, spill_dir),
+            scratch: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Begin resampling audio captured at `source_rate`.
+    pub fn start(&mut self, source_rate: u32) {
+        match StreamingResampler::with_quality(source_rate, self.quality) {
+            Ok(resampler) => self.resampler = Some(resampler),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn push(&mut self, audio: &[f32]) {
+        if self.error.is_some() {
+            return;
+        }
+        let Some(resampler) = self.resampler.as_mut() else {
+            self.error = Some("Resampling was not started.".to_string());
+            return;
+        };
+        self.scratch.clear();
+        match resampler.push(audio, &mut self.scratch) {
+            Ok(()) => self.output.push(&self.scratch),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Flush the resampler and return the whole recording at 16kHz.
+    pub fn finish(self) -> Result<CaptureBuffer, String> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let resampler = self
+            .resampler
+            .ok_or_else(|| "Resampling was not started.".to_string())?;
+        let mut output = self.output;
+        let mut tail = Vec::new();
+        resampler.finish(&mut tail)?;
+        output.push(&tail);
+        Ok(output)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const QUALITIES: [ResamplerQuality; 3] = [
+        ResamplerQuality::Fast,
+        ResamplerQuality::Balanced,
+        ResamplerQuality::High,
+    ];
+
+    fn tones(frequencies: &[f32], rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / rate as f32;
+                frequencies
+                    .iter()
+                    .map(|f| 0.3 * (2.0 * std::f32::consts::PI * f * t).sin())
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Amplitude of `frequency` in 16kHz audio, by correlating with a sine
+    /// and cosine over the middle of the signal (away from edge effects).
+    fn magnitude(audio: &[f32], frequency: f32) -> f32 {
+        let middle = &audio[audio.len() / 4..audio.len() * 3 / 4];
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, &sample) in middle.iter().enumerate() {
+            let phase = 2.0 * std::f64::consts::PI * frequency as f64 * i as f64 / 16000.0;
+            re += sample as f64 * phase.cos();
+            im += sample as f64 * phase.sin();
+        }
+        (2.0 * (re * re + im * im).sqrt() / middle.len() as f64) as f32
+    }
+
+    fn db(ratio: f32) -> f32 {
+        20.0 * ratio.log10()
+    }
+
     #[test]
     fn test_resample_passthrough_16khz() {
         let audio: Vec<f32> = vec![0.1, 0.2, 0.3];
@@ -74,8 +303,10 @@ mod tests {
 
     #[test]
     fn test_resample_empty() {
-        let result = resample_to_16khz(&[], 44100).unwrap();
-        assert!(result.is_empty());
+        for quality in QUALITIES {
+            let result = resample_to_16khz_with(&[], 44100, quality).unwrap();
+            assert!(result.is_empty());
+        }
     }
 
     #[test]
@@ -120,4 +351,97 @@ mod tests {
             result.len()
         );
     }
+
+    #[test]
+    fn test_qualities_match_fft_length_and_spectrum() {
+        for rate in [44100, 48000] {
+            let len = rate as usize * 2 + 333;
+            let audio = tones(&[440.0, 3000.0], rate, len);
+            let reference = resample_to_16khz_with(&audio, rate, ResamplerQuality::Balanced).unwrap();
+            assert_eq!(reference.len(), resampled_len(len, rate));
+
+            for quality in [ResamplerQuality::Fast, ResamplerQuality::High] {
+                let output = resample_to_16khz_with(&audio, rate, quality).unwrap();
+                assert_eq!(output.len(), reference.len(), "{:?} at {}Hz", quality, rate);
+                for frequency in [440.0, 3000.0] {
+                    let difference = db(magnitude(&output, frequency) / magnitude(&reference, frequency));
+                    assert!(
+                        difference.abs() < 1.0,
+                        "{:?} at {}Hz: {}Hz off by {:.2}dB",
+                        quality,
+                        rate,
+                        frequency,
+                        difference
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_is_aligned_with_input() {
+        // A step halfway through should stay halfway through
+        for quality in QUALITIES {
+            let mut audio = vec![0.0; 48000];
+            audio[24000..].fill(0.5);
+            let output = resample_to_16khz_with(&audio, 48000, quality).unwrap();
+            assert!(output[7950].abs() < 0.05, "{:?}: {}", quality, output[7950]);
+            assert!((output[8050] - 0.5).abs() < 0.05, "{:?}: {}", quality, output[8050]);
+        }
+    }
+
+    #[test]
+    fn test_filtered_qualities_reject_aliases() {
+        // 10kHz is above the new Nyquist and would alias down to 6kHz
+        let audio = tones(&[10000.0], 48000, 48000);
+        for quality in [ResamplerQuality::Balanced, ResamplerQuality::High] {
+            let output = resample_to_16khz_with(&audio, 48000, quality).unwrap();
+            let alias = db(magnitude(&output, 6000.0) / 0.3);
+            assert!(alias < -60.0, "{:?}: alias at {:.1}dB", quality, alias);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let source_rate = 48000;
+        let audio: Vec<f32> = (0..source_rate + 777)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / source_rate as f32).sin())
+            .collect();
+        for quality in QUALITIES {
+            let expected = resample_to_16khz_with(&audio, source_rate as u32, quality).unwrap();
+
+            let mut resampler = StreamingResampler::with_quality(source_rate as u32, quality).unwrap();
+            let mut output = Vec::new();
+            for chunk in audio.chunks(1000) {
+                resampler.push(chunk, &mut output).unwrap();
+            }
+            resampler.finish(&mut output).unwrap();
+
+            assert_eq!(output, expected, "{:?}", quality);
+        }
+    }
+
+    #[test]
+    fn test_streaming_passthrough_16khz() {
+        let mut resampler = StreamingResampler::new(16000).unwrap();
+        let mut output = Vec::new();
+        resampler.push(&[0.1, 0.2], &mut output).unwrap();
+        resampler.push(&[0.3], &mut output).unwrap();
+        resampler.finish(&mut output).unwrap();
+        assert_eq!(output, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_resampled_capture_matches_one_shot() {
+        let audio = tones(&[440.0], 44100, 44100 + 500);
+        let expected = resample_to_16khz_with(&audio, 44100, ResamplerQuality::High).unwrap();
+
+        let mut capture = ResampledCapture::new(ResamplerQuality::High, None);
+        capture.start(44100);
+        for block in audio.chunks(441) {
+            capture.push(block);
+        }
+        let mut output = capture.finish().unwrap();
+        assert_eq!(output.to_vec().unwrap(), expected);
+    }
 }
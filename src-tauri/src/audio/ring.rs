@@ -0,0 +1,277 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use rtrb::{Consumer, Producer, RingBuffer};
+
+/// How often the consumer thread drains the ring.
+const CONSUMER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Ring capacity in seconds of interleaved input audio.
+const RING_CAPACITY_SECS: usize = 1;
+
+/// Create a ring sized for the given stream format. The consumer averages all
+/// channels, or keeps only `channel` when one is given and exists.
+pub fn frame_ring(
+    sample_rate: u32,
+    channels: usize,
+    channel: Option<usize>,
+) -> (FrameProducer, FrameConsumer) {
+    let channels = channels.max(1);
+    if let Some(missing) = channel.filter(|&c| c >= channels) {
+        log::warn!(
+            "Input channel {} not available on a {}-channel stream; mixing all channels",
+            missing + 1,
+            channels
+        );
+    }
+    let channel = channel.filter(|&c| c < channels);
+    let capacity = (sample_rate as usize * channels * RING_CAPACITY_SECS).max(channels);
+    let (producer, consumer) = RingBuffer::new(capacity);
+    let dropped = Arc::new(AtomicUsize::new(0));
+    (
+        FrameProducer {
+            producer,
+            channels,
+            dropped: Arc::clone(&dropped),
+        },
+        FrameConsumer {
+            consumer,
+            channels,
+            channel,
+            mono: Vec::with_capacity(capacity / channels),
+            dropped,
+        },
+    )
+}
+
+/// Realtime side of the capture ring, owned by the cpal input callback.
+///
+/// Writing never locks or allocates. If the consumer falls behind and the
+/// ring fills, the excess frames are dropped and counted.
+pub struct FrameProducer {
+    producer: Producer<f32>,
+    channels: usize,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl FrameProducer {
+    /// Copy interleaved samples into the ring. Only whole frames are written
+    /// so the consumer never loses track of channel order.
+    pub fn write(&mut self, data: &[f32]) {
+        let space = self.producer.slots() / self.channels * self.channels;
+        let n = data.len().min(space);
+        if n < data.len() {
+            self.dropped.fetch_add(data.len() - n, Ordering::Relaxed);
+        }
+        if let Ok(chunk) = self.producer.write_chunk_uninit(n) {
+            chunk.fill_from_iter(data.iter().copied());
+        }
+    }
+}
+
+/// Non-realtime side of the capture ring: drains interleaved frames and
+/// downmixes them to mono.
+pub struct FrameConsumer {
+    consumer: Consumer<f32>,
+    channels: usize,
+    channel: Option<usize>,
+    mono: Vec<f32>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl FrameConsumer {
+    /// Downmix (or pick the selected channel of) every complete frame currently
+    /// in the ring and hand the mono samples to `sink`. Returns the number of
+    /// frames consumed.
+    pub fn drain(&mut self, sink: &mut dyn FnMut(&[f32])) -> usize {
+        let frames = self.consumer.slots() / self.channels;
+        if frames == 0 {
+            return 0;
+        }
+        let Ok(chunk) = self.consumer.read_chunk(frames * self.channels) else {
+            return 0;
+        };
+
+        self.mono.clear();
+        let (first, second) = chunk.as_slices();
+        if self.channels == 1 {
+            self.mono.extend_from_slice(first);
+            self.mono.extend_from_slice(second);
+        } else if let Some(channel) = self.channel {
+            let samples = first.iter().chain(second);
+            self.mono
+                .extend(samples.skip(channel).step_by(self.channels).copied());
+        } else {
+            // The wrap point can fall between the two slices, so sum across both
+            let mut sum = 0.0;
+            for (i, sample) in first.iter().chain(second).enumerate() {
+                sum += sample;
+                if (i + 1) % self.channels == 0 {
+                    self.mono.push(sum / self.channels as f32);
+                    sum = 0.0;
+                }
+            }
+        }
+        chunk.commit_all();
+
+        sink(&self.mono);
+        frames
+    }
+
+    /// Samples dropped because the ring was full.
+    pub fn dropped_samples(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FrameConsumer {
+    fn drop(&mut self) {
+        let dropped = self.dropped_samples();
+        if dropped > 0 {
+            log::warn!("Audio consumer fell behind; dropped {} samples", dropped);
+        }
+    }
+}
+
+/// Thread that periodically runs a drain function, typically one that calls
+/// [`FrameConsumer::drain`]. Dropping it runs a final drain and joins the thread.
+pub struct ConsumerThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConsumerThread {
+    pub fn spawn<F>(mut drain: F) -> Self
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || loop {
+            // Read the flag before draining so the last frames are never skipped
+            let stopping = stop_clone.load(Ordering::Acquire);
+            drain();
+            if stopping {
+                break;
+            }
+            std::thread::park_timeout(CONSUMER_POLL_INTERVAL);
+        });
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Drain whatever is left in the ring and wait for the thread to exit.
+    /// The input stream should be stopped first so no new frames arrive.
+    pub fn finish(self) {
+        drop(self);
+    }
+}
+
+impl Drop for ConsumerThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain_all(frames: &mut FrameConsumer) -> Vec<f32> {
+        let mut out = Vec::new();
+        frames.drain(&mut |mono| out.extend_from_slice(mono));
+        out
+    }
+
+    #[test]
+    fn test_mono_passes_through() {
+        let (mut producer, mut consumer) = frame_ring(100, 1, None);
+        producer.write(&[0.1, 0.2, 0.3]);
+        assert_eq!(drain_all(&mut consumer), vec![0.1, 0.2, 0.3]);
+        assert!(drain_all(&mut consumer).is_empty());
+    }
+
+    #[test]
+    fn test_stereo_is_downmixed() {
+        let (mut producer, mut consumer) = frame_ring(100, 2, None);
+        producer.write(&[1.0, 0.0, 0.5, 0.5, -1.0, 1.0]);
+        assert_eq!(drain_all(&mut consumer), vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_selected_channel_is_kept() {
+        let (mut producer, mut consumer) = frame_ring(100, 3, Some(1));
+        producer.write(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        assert_eq!(drain_all(&mut consumer), vec![0.2, 0.5]);
+    }
+
+    #[test]
+    fn test_missing_channel_falls_back_to_mix() {
+        let (mut producer, mut consumer) = frame_ring(100, 2, Some(4));
+        producer.write(&[1.0, 0.0]);
+        assert_eq!(drain_all(&mut consumer), vec![0.5]);
+    }
+
+    #[test]
+    fn test_frames_across_wrap_point() {
+        // 3 channels in a ring of 9 samples; repeated writes force wrap-around
+        let (mut producer, mut consumer) = frame_ring(3, 3, None);
+        let mut expected = Vec::new();
+        let mut actual = Vec::new();
+        for i in 0..20 {
+            let v = i as f32;
+            producer.write(&[v, v + 1.0, v + 2.0]);
+            producer.write(&[v, v, v]);
+            expected.push(v + 1.0);
+            expected.push(v);
+            actual.extend(drain_all(&mut consumer));
+        }
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_overflow_drops_and_counts() {
+        let (mut producer, mut consumer) = frame_ring(4, 1, None);
+        producer.write(&[1.0; 10]);
+        assert_eq!(consumer.dropped_samples(), 6);
+        assert_eq!(drain_all(&mut consumer).len(), 4);
+    }
+
+    #[test]
+    fn test_overflow_keeps_frames_aligned() {
+        // Ring holds 6 samples; after the first frame only 2 more stereo frames fit
+        let (mut producer, mut consumer) = frame_ring(3, 2, None);
+        producer.write(&[0.0, 0.0]);
+        producer.write(&[1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(consumer.dropped_samples(), 2);
+        assert_eq!(drain_all(&mut consumer), vec![0.0, 1.0, 1.0]);
+
+        producer.write(&[0.2, 0.4]);
+        assert_eq!(drain_all(&mut consumer), vec![0.3]);
+    }
+
+    #[test]
+    fn test_consumer_thread_drains_before_exit() {
+        let (mut producer, mut consumer) = frame_ring(16000, 2, None);
+        let total = Arc::new(AtomicUsize::new(0));
+        let total_clone = Arc::clone(&total);
+        let thread = ConsumerThread::spawn(move || {
+            consumer.drain(&mut |mono| {
+                total_clone.fetch_add(mono.len(), Ordering::Relaxed);
+            });
+        });
+
+        for _ in 0..10 {
+            producer.write(&[0.25; 320]);
+        }
+        thread.finish();
+        assert_eq!(total.load(Ordering::Relaxed), 1600);
+    }
+}
@@ -0,0 +1,366 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::buffer::CaptureBuffer;
+use super::capture::{
+    lock_metrics, take_buffer, AmplitudeCallback, AudioCapture, CaptureOptions, CaptureTap,
+    SessionProcessor,
+};
+use super::decode::decode_audio_file;
+use super::level::{InputLevelMetrics, LevelMonitor};
+use crate::preferences::AudioSourceSetting;
+
+/// Environment variable that overrides the audio source preference, e.g.
+/// `synthetic` or `file:/path/to/recording.wav`.
+pub const AUDIO_SOURCE_ENV: &str = "TALKYTEXTY_AUDIO_SOURCE";
+
+/// Sample rate of the synthetic source.
+const SYNTHETIC_SAMPLE_RATE: u32 = 16000;
+/// Synthetic signal: tone bursts separated by silence, like short phrases.
+const SYNTHETIC_BURST_MS: usize = 1000;
+const SYNTHETIC_GAP_MS: usize = 500;
+const SYNTHETIC_FREQUENCY_HZ: f32 = 220.0;
+const SYNTHETIC_AMPLITUDE: f32 = 0.3;
+
+/// Length of each block handed to the session by playback sources.
+const PLAYBACK_BLOCK_MS: usize = 10;
+
+/// A running recording session, whatever the audio comes from.
+///
+/// [`AudioCapture`] records from an input device; [`FileSource`] and
+/// [`SyntheticSource`] stand in for a microphone in tests and when replaying
+/// recordings.
+pub trait AudioSource: Send {
+    /// Get a view of the capture buffer for reading audio while recording.
+    fn tap(&self) -> CaptureTap;
+
+    /// Id of the device this session is recording from.
+    fn device_id(&self) -> &str;
+
+    /// Name of the device this session is recording from.
+    fn device_name(&self) -> &str;
+
+    /// Peak, clipping and noise floor of the audio recorded so far.
+    fn input_levels(&self) -> InputLevelMetrics;
+
+    /// Treat the device as disconnected, as if the audio backend had reported it.
+    fn mark_device_lost(&self) {}
+
+    /// Continue the session on the system default input device after the
+    /// current one was lost. Returns the name of the new device.
+    fn fail_over_to_default(&mut self) -> Result<String, String> {
+        Err(format!("'{}' can't switch devices.", self.device_name()))
+    }
+
+    /// Stop capturing and return the collected audio buffer and sample rate.
+    fn stop(self: Box<Self>) -> (CaptureBuffer, u32);
+}
+
+/// The audio source to record from: the environment override when set and
+/// valid, otherwise the preference.
+pub fn effective_source(preference: &AudioSourceSetting) -> AudioSourceSetting {
+    match std::env::var(AUDIO_SOURCE_ENV) {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            log::warn!("Ignoring {}: {}", AUDIO_SOURCE_ENV, e);
+            preference.clone()
+        }),
+        Err(_) => preference.clone(),
+    }
+}
+
+/// Start a recording session from `setting`.
+pub fn start_source(
+    setting: &AudioSourceSetting,
+    amplitude_callback: AmplitudeCallback,
+    options: CaptureOptions,
+) -> Result<Box<dyn AudioSource>, String> {
+    Ok(match setting {
+        AudioSourceSetting::Microphone => Box::new(AudioCapture::start(amplitude_callback, options)?),
+        AudioSourceSetting::File { path } => {
+            Box::new(FileSource::start(Path::new(path), amplitude_callback, options)?)
+        }
+        AudioSourceSetting::Synthetic => Box::new(SyntheticSource::start(amplitude_callback, options)),
+    })
+}
+
+/// Feeds audio from a generator into a session at real-time pace, so silence
+/// detection, the duration limit and live transcription behave as they do
+/// with a microphone.
+struct Playback {
+    buffer: Arc<Mutex<CaptureBuffer>>,
+    sample_rate: u32,
+    levels: Arc<Mutex<LevelMonitor>>,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Playback {
+    /// `fill` writes the next block of mono samples into the slice it is given.
+    fn start<F>(
+        sample_rate: u32,
+        mut fill: F,
+        amplitude_callback: AmplitudeCallback,
+        options: CaptureOptions,
+    ) -> Self
+    where
+        F: FnMut(&mut [f32]) + Send + 'static,
+    {
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new(sample_rate, options.spill_dir)));
+        let mut processor = SessionProcessor::new(
+            Arc::clone(&buffer),
+            sample_rate,
+            amplitude_callback,
+            options.spectrum,
+            options.on_level_warning,
+            options.auto_stop,
+            options.limit,
+        )
+        .resampling_into(options.resampled);
+        let levels = processor.levels();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stop_clone = Arc::clone(&stop);
+        let thread = std::thread::spawn(move || {
+            let started = Instant::now();
+            let mut block = vec![0.0; (sample_rate as usize * PLAYBACK_BLOCK_MS / 1000).max(1)];
+            let mut delivered = 0usize;
+            while !stop_clone.load(Ordering::Acquire) {
+                fill(&mut block);
+                processor.push(&block);
+                delivered += block.len();
+
+                let due = Duration::from_secs_f64(delivered as f64 / sample_rate as f64);
+                if let Some(wait) = due.checked_sub(started.elapsed()) {
+                    std::thread::park_timeout(wait);
+                }
+            }
+        });
+
+        Self {
+            buffer,
+            sample_rate,
+            levels,
+            stop,
+            thread,
+        }
+    }
+
+    fn tap(&self) -> CaptureTap {
+        CaptureTap::new(Arc::clone(&self.buffer), self.sample_rate)
+    }
+
+    fn input_levels(&self) -> InputLevelMetrics {
+        lock_metrics(&self.levels)
+    }
+
+    fn stop(self) -> (CaptureBuffer, u32) {
+        self.stop.store(true, Ordering::Release);
+        self.thread.thread().unpark();
+        let _ = self.thread.join();
+        (take_buffer(self.buffer, self.sample_rate), self.sample_rate)
+    }
+}
+
+/// Replays an audio file in place of the microphone, followed by silence
+/// until the session is stopped.
+pub struct FileSource {
+    playback: Playback,
+    id: String,
+    name: String,
+}
+
+impl FileSource {
+    /// Decode `path` and start playing it into a new session.
+    pub fn start(
+        path: &Path,
+        amplitude_callback: AmplitudeCallback,
+        options: CaptureOptions,
+    ) -> Result<Self, String> {
+        let decoded = decode_audio_file(path, &mut |_| {})?;
+        let samples = decoded.samples;
+        let mut position = 0;
+        let playback = Playback::start(
+            decoded.sample_rate,
+            move |block| {
+                let n = samples.len().saturating_sub(position).min(block.len());
+                block[..n].copy_from_slice(&samples[position..position + n]);
+                block[n..].fill(0.0);
+                position += n;
+            },
+            amplitude_callback,
+            options,
+        );
+
+        Ok(Self {
+            playback,
+            id: format!("file:{}", path.display()),
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string()),
+        })
+    }
+}
+
+impl AudioSource for FileSource {
+    fn tap(&self) -> CaptureTap {
+        self.playback.tap()
+    }
+
+    fn device_id(&self) -> &str {
+        &self.id
+    }
+
+    fn device_name(&self) -> &str {
+        &self.name
+    }
+
+    fn input_levels(&self) -> InputLevelMetrics {
+        self.playback.input_levels()
+    }
+
+    fn stop(self: Box<Self>) -> (CaptureBuffer, u32) {
+        self.playback.stop()
+    }
+}
+
+/// Generates a test signal in place of the microphone; see [`synthetic_sample`].
+pub struct SyntheticSource {
+    playback: Playback,
+}
+
+impl SyntheticSource {
+    pub fn start(amplitude_callback: AmplitudeCallback, options: CaptureOptions) -> Self {
+        let mut index = 0;
+        let playback = Playback::start(
+            SYNTHETIC_SAMPLE_RATE,
+            move |block| {
+                for sample in block.iter_mut() {
+                    *sample = synthetic_sample(index, SYNTHETIC_SAMPLE_RATE);
+                    index += 1;
+                }
+            },
+            amplitude_callback,
+            options,
+        );
+        Self { playback }
+    }
+}
+
+impl AudioSource for SyntheticSource {
+    fn tap(&self) -> CaptureTap {
+        self.playback.tap()
+    }
+
+    fn device_id(&self) -> &str {
+        "synthetic"
+    }
+
+    fn device_name(&self) -> &str {
+        "Synthetic signal"
+    }
+
+    fn input_levels(&self) -> InputLevelMetrics {
+        self.playback.input_levels()
+    }
+
+    fn stop(self: Box<Self>) -> (CaptureBuffer, u32) {
+        self.playback.stop()
+    }
+}
+
+/// Sample `index` of the synthetic signal: a tone burst, then a gap of silence,
+/// repeating. Starts with a burst.
+pub fn synthetic_sample(index: usize, sample_rate: u32) -> f32 {
+    let rate = sample_rate as usize;
+    let burst = SYNTHETIC_BURST_MS * rate / 1000;
+    let period = burst + SYNTHETIC_GAP_MS * rate / 1000;
+    if period == 0 || index % period >= burst {
+        return 0.0;
+    }
+    let t = index as f32 / sample_rate as f32;
+    SYNTHETIC_AMPLITUDE * (2.0 * std::f32::consts::PI * SYNTHETIC_FREQUENCY_HZ * t).sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_signal_alternates_bursts_and_silence() {
+        let rate = 16000;
+        let burst: Vec<f32> = (0..16000).map(|i| synthetic_sample(i, rate)).collect();
+        let gap: Vec<f32> = (16000..24000).map(|i| synthetic_sample(i, rate)).collect();
+        let peak = burst.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - SYNTHETIC_AMPLITUDE).abs() < 0.01, "peak {}", peak);
+        assert!(gap.iter().all(|&s| s == 0.0));
+        assert!(synthetic_sample(24000 + 100, rate) != 0.0);
+    }
+
+    #[test]
+    fn test_synthetic_source_records_in_real_time() {
+        let source: Box<dyn AudioSource> =
+            Box::new(SyntheticSource::start(Box::new(|_| {}), CaptureOptions::default()));
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!source.tap().samples_since(0).is_empty());
+        let levels = source.input_levels();
+        assert!((levels.peak_dbfs - -10.5).abs() < 0.5, "{:?}", levels);
+        assert_eq!(levels.clipping_ratio, 0.0);
+        let (mut buffer, rate) = source.stop();
+
+        assert_eq!(rate, SYNTHETIC_SAMPLE_RATE);
+        // Paced to the clock: roughly 200ms of audio, not as much as the CPU allows
+        let secs = buffer.len() as f32 / rate as f32;
+        assert!((0.1..1.0).contains(&secs), "recorded {}s", secs);
+        let samples = buffer.to_vec().unwrap();
+        assert_eq!(samples[160], synthetic_sample(160, rate));
+    }
+
+    #[test]
+    fn test_file_source_replays_then_pads_with_silence() {
+        let dir = std::env::temp_dir().join("ttc_test_audio_source");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("replay.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..800 {
+            writer.write_sample(i16::MAX / 2).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let source: Box<dyn AudioSource> = Box::new(
+            FileSource::start(&path, Box::new(|_| {}), CaptureOptions::default()).unwrap(),
+        );
+        assert_eq!(source.device_name(), "replay.wav");
+        std::thread::sleep(Duration::from_millis(250));
+        let (mut buffer, rate) = source.stop();
+
+        assert_eq!(rate, 8000);
+        let samples = buffer.to_vec().unwrap();
+        assert!(samples.len() > 800, "only {} samples", samples.len());
+        assert!(samples[..800].iter().all(|&s| (s - 0.5).abs() < 0.01));
+        assert!(samples[800..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_parse_audio_source_setting() {
+        assert_eq!("synthetic".parse(), Ok(AudioSourceSetting::Synthetic));
+        assert_eq!(" Mic ".parse(), Ok(AudioSourceSetting::Microphone));
+        assert_eq!(
+            "file:/tmp/a b.wav".parse(),
+            Ok(AudioSourceSetting::File {
+                path: "/tmp/a b.wav".to_string()
+            })
+        );
+        assert!("file:".parse::<AudioSourceSetting>().is_err());
+        assert!("speaker".parse::<AudioSourceSetting>().is_err());
+    }
+}
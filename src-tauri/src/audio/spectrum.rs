@@ -0,0 +1,240 @@
+//! Frequency-band levels for the overlay visualizer.
+
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use serde::Serialize;
+
+/// Number of log-spaced bands reported per update.
+pub const SPECTRUM_BANDS: usize = 32;
+/// Frequency range covered by the bands; speech has little energy outside it.
+const MIN_FREQUENCY_HZ: f32 = 80.0;
+const MAX_FREQUENCY_HZ: f32 = 8000.0;
+/// Band levels are mapped from this many dB below full scale up to 0 dB onto 0..1.
+const DYNAMIC_RANGE_DB: f32 = 70.0;
+/// How long a peak stays put before it starts to fall.
+const PEAK_HOLD_SECS: f32 = 0.5;
+/// How fast a released peak falls, in level units per second.
+const PEAK_DECAY_PER_SEC: f32 = 1.0;
+
+/// Band levels for one visualization update, each in 0..1 from low to high frequency.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectrumFrame {
+    pub bands: Vec<f32>,
+    /// Recent maximum of each band, held briefly and then decaying.
+    pub peaks: Vec<f32>,
+}
+
+/// Computes [`SpectrumFrame`]s from a stream of mono samples.
+///
+/// Each analysis looks at the most recent FFT window of audio, which may
+/// overlap the previous one when updates come more often than the window length.
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    /// Sum of the window, to scale a full-scale sine to a magnitude of 1.
+    window_gain: f32,
+    history: Vec<f32>,
+    input: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    /// FFT bin range `[start, end)` of each band.
+    band_bins: Vec<(usize, usize)>,
+    peaks: Vec<f32>,
+    /// Analyses left before each peak starts to decay.
+    hold: Vec<usize>,
+    hold_analyses: usize,
+    /// How far a released peak falls per analysis.
+    decay: f32,
+}
+
+impl SpectrumAnalyzer {
+    /// `interval` is the number of samples pushed between calls to [`SpectrumAnalyzer::analyze`].
+    pub fn new(sample_rate: u32, interval: usize) -> Self {
+        // At least 50ms of audio, so the lowest bands still get their own bins
+        let fft_len = (sample_rate as usize / 20).max(interval).max(2).next_power_of_two();
+        let window: Vec<f32> = (0..fft_len)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / fft_len as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        let window_gain = window.iter().sum::<f32>();
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(fft_len);
+        let input = fft.make_input_vec();
+        let spectrum = fft.make_output_vec();
+
+        Self {
+            band_bins: band_bins(sample_rate, fft_len, SPECTRUM_BANDS),
+            fft,
+            window,
+            window_gain,
+            history: vec![0.0; fft_len],
+            input,
+            spectrum,
+            peaks: vec![0.0; SPECTRUM_BANDS],
+            hold: vec![0; SPECTRUM_BANDS],
+            hold_analyses: (PEAK_HOLD_SECS * sample_rate as f32 / interval.max(1) as f32).round() as usize,
+            decay: PEAK_DECAY_PER_SEC * interval as f32 / sample_rate as f32,
+        }
+    }
+
+    /// Add samples to the analysis window.
+    pub fn push(&mut self, samples: &[f32]) {
+        let len = self.history.len();
+        if samples.len() >= len {
+            self.history.copy_from_slice(&samples[samples.len() - len..]);
+        } else {
+            self.history.rotate_left(samples.len());
+            self.history[len - samples.len()..].copy_from_slice(samples);
+        }
+    }
+
+    /// Band levels of the most recent window, with peaks advanced by one interval.
+    pub fn analyze(&mut self) -> SpectrumFrame {
+        for ((input, sample), w) in self.input.iter_mut().zip(&self.history).zip(&self.window) {
+            *input = sample * w;
+        }
+        if self.fft.process(&mut self.input, &mut self.spectrum).is_err() {
+            self.spectrum.fill(Complex::default());
+        }
+
+        let scale = 2.0 / self.window_gain;
+        let bands: Vec<f32> = self
+            .band_bins
+            .iter()
+            .map(|&(start, end)| {
+                let power: f32 = self.spectrum[start..end].iter().map(|c| c.norm_sqr()).sum();
+                level(power.sqrt() * scale)
+            })
+            .collect();
+
+        for ((peak, hold), &band) in self.peaks.iter_mut().zip(&mut self.hold).zip(&bands) {
+            if band >= *peak {
+                *peak = band;
+                *hold = self.hold_analyses;
+            } else if *hold > 0 {
+                *hold -= 1;
+            } else {
+                *peak = (*peak - self.decay).max(band);
+            }
+        }
+
+        SpectrumFrame {
+            bands,
+            peaks: self.peaks.clone(),
+        }
+    }
+}
+
+/// Map a linear magnitude onto 0..1 across [`DYNAMIC_RANGE_DB`].
+fn level(magnitude: f32) -> f32 {
+    if magnitude <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * magnitude.log10();
+    ((db + DYNAMIC_RANGE_DB) / DYNAMIC_RANGE_DB).clamp(0.0, 1.0)
+}
+
+/// Split the FFT bins into `count` log-spaced bands. Bands narrower than a
+/// bin take the bin their centre falls in, so every band has at least one.
+fn band_bins(sample_rate: u32, fft_len: usize, count: usize) -> Vec<(usize, usize)> {
+    let nyquist = sample_rate as f32 / 2.0;
+    let bin_hz = sample_rate as f32 / fft_len as f32;
+    let last_bin = fft_len / 2;
+    let max = MAX_FREQUENCY_HZ.min(nyquist);
+    let min = MIN_FREQUENCY_HZ.min(max / 2.0);
+    let ratio = (max / min).powf(1.0 / count as f32);
+
+    (0..count)
+        .map(|i| {
+            let low = min * ratio.powi(i as i32);
+            let high = low * ratio;
+            let start = ((low / bin_hz).ceil() as usize).min(last_bin);
+            let end = ((high / bin_hz).ceil() as usize).min(last_bin + 1);
+            if end > start {
+                (start, end)
+            } else {
+                let centre = (((low * high).sqrt() / bin_hz).round() as usize).min(last_bin);
+                (centre, centre + 1)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frequency: f32, amplitude: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * frequency * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    fn loudest(bands: &[f32]) -> usize {
+        (0..bands.len())
+            .max_by(|&a, &b| bands[a].total_cmp(&bands[b]))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bands_are_log_spaced() {
+        let bins = band_bins(16000, 1024, SPECTRUM_BANDS);
+        assert_eq!(bins.len(), SPECTRUM_BANDS);
+        assert!(bins.iter().all(|&(start, end)| end > start && end <= 513));
+        // Higher bands span more bins
+        let width = |(start, end): (usize, usize)| end - start;
+        assert!(width(bins[SPECTRUM_BANDS - 1]) > 4 * width(bins[SPECTRUM_BANDS / 2]));
+    }
+
+    #[test]
+    fn test_tone_lands_in_matching_band() {
+        let rate = 16000;
+        let mut low = SpectrumAnalyzer::new(rate, 800);
+        low.push(&tone(200.0, 0.5, rate, 2048));
+        let mut high = SpectrumAnalyzer::new(rate, 800);
+        high.push(&tone(3000.0, 0.5, rate, 2048));
+
+        let (low, high) = (low.analyze().bands, high.analyze().bands);
+        assert!(loudest(&low) < SPECTRUM_BANDS / 3, "200Hz in band {}", loudest(&low));
+        assert!(loudest(&high) > SPECTRUM_BANDS * 2 / 3, "3kHz in band {}", loudest(&high));
+        // -6 dBFS maps to about 0.9
+        let peak = low[loudest(&low)];
+        assert!((0.8..=1.0).contains(&peak), "level {}", peak);
+    }
+
+    #[test]
+    fn test_silence_is_zero() {
+        let mut analyzer = SpectrumAnalyzer::new(48000, 2400);
+        analyzer.push(&[0.0; 2400]);
+        let frame = analyzer.analyze();
+        assert!(frame.bands.iter().chain(&frame.peaks).all(|&b| b == 0.0));
+    }
+
+    #[test]
+    fn test_peaks_hold_then_decay() {
+        let rate = 16000;
+        let interval = 800; // 50ms
+        let mut analyzer = SpectrumAnalyzer::new(rate, interval);
+        analyzer.push(&tone(1000.0, 0.5, rate, 1024));
+        let loud = analyzer.analyze();
+        let band = loudest(&loud.bands);
+        assert_eq!(loud.peaks[band], loud.bands[band]);
+
+        // Held for 500ms after the tone stops
+        analyzer.push(&[0.0; 1024]);
+        for _ in 0..10 {
+            let frame = analyzer.analyze();
+            assert_eq!(frame.bands[band], 0.0);
+            assert_eq!(frame.peaks[band], loud.peaks[band]);
+        }
+        // Then falls steadily back to the band level
+        let falling = analyzer.analyze().peaks[band];
+        assert!((loud.peaks[band] - falling - 0.05).abs() < 1e-4, "{}", falling);
+        for _ in 0..20 {
+            analyzer.analyze();
+        }
+        assert_eq!(analyzer.analyze().peaks[band], 0.0);
+    }
+}
@@ -0,0 +1,341 @@
+use std::ops::Range;
+
+use super::capture::calculate_rms;
+
+/// Analysis window for trimming silence from the ends of a recording.
+const TRIM_WINDOW_MS: usize = 20;
+/// Windows quieter than this never count as speech, however quiet the room.
+const TRIM_MIN_THRESHOLD_RMS: f32 = 0.01;
+/// Speech must stand this far above the recording's noise floor.
+const TRIM_NOISE_FLOOR_RATIO: f32 = 2.5;
+/// Upper bound on the adaptive threshold, so a recording that is speech from
+/// end to end (and so has no real noise floor) isn't judged silent.
+const TRIM_MAX_THRESHOLD_RMS: f32 = 0.05;
+/// Shortest burst of sound treated as speech rather than a click or bump.
+const TRIM_MIN_SPEECH_MS: usize = 100;
+/// Audio kept on either side of the detected speech so soft word onsets and
+/// endings aren't clipped.
+const TRIM_MARGIN_MS: usize = 250;
+
+/// Tuning for the energy-based silence detector used by hands-free recording.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceDetectorConfig {
+    /// RMS level below which a window is considered silent.
+    pub threshold_rms: f32,
+    /// How long the input must stay silent after speech before the session ends.
+    pub trailing_silence_ms: u32,
+    /// Minimum amount of speech required before trailing silence can end the session.
+    pub min_speech_ms: u32,
+}
+
+/// Energy-based voice activity detector.
+///
+/// Fed consecutive mono analysis windows; reports once that the speaker has
+/// talked for at least `min_speech_ms` and then gone quiet for
+/// `trailing_silence_ms`. Silence before any speech never ends the session.
+pub struct SilenceDetector {
+    config: SilenceDetectorConfig,
+    sample_rate: u32,
+    speech_ms: f64,
+    silence_ms: f64,
+    triggered: bool,
+}
+
+impl SilenceDetector {
+    pub fn new(config: SilenceDetectorConfig, sample_rate: u32) -> Self {
+        Self {
+            config,
+            sample_rate,
+            speech_ms: 0.0,
+            silence_ms: 0.0,
+            triggered: false,
+        }
+    }
+
+    /// Process one window of mono samples.
+    /// Returns `true` exactly once, on the window where the session should end.
+    pub fn process(&mut self, samples: &[f32]) -> bool {
+        if self.triggered || samples.is_empty() || self.sample_rate == 0 {
+            return false;
+        }
+
+        let window_ms = samples.len() as f64 * 1000.0 / self.sample_rate as f64;
+        if calculate_rms(samples) >= self.config.threshold_rms {
+            self.speech_ms += window_ms;
+            self.silence_ms = 0.0;
+        } else {
+            self.silence_ms += window_ms;
+        }
+
+        if self.has_speech() && self.silence_ms >= self.config.trailing_silence_ms as f64
+        {
+            self.triggered = true;
+            return true;
+        }
+        false
+    }
+
+    /// Whether enough speech has been heard for trailing silence to count.
+    /// Some speech is always needed, so silence alone never ends a session.
+    pub fn has_speech(&self) -> bool {
+        self.speech_ms > 0.0 && self.speech_ms >= self.config.min_speech_ms as f64
+    }
+}
+
+/// Find the part of a recording that contains speech, with a small margin on
+/// either side. Returns `None` when nothing in the recording sounds like speech.
+///
+/// The threshold adapts to the recording's noise floor (its quietest windows),
+/// so a noisy room doesn't count as speech from start to finish.
+pub fn find_speech_bounds(samples: &[f32], sample_rate: u32) -> Option<Range<usize>> {
+    let window = trim_window(sample_rate);
+    let runs = speech_runs(samples, window);
+    let first = runs.first()?.start;
+    let last = runs.last()?.end;
+    Some(with_margin(first * window..last * window, samples.len(), sample_rate))
+}
+
+/// Find each stretch of speech in a recording, split wherever it goes quiet
+/// for at least `min_gap_ms`. Regions carry the same margin and adaptive
+/// threshold as [`find_speech_bounds`].
+pub fn find_speech_regions(samples: &[f32], sample_rate: u32, min_gap_ms: usize) -> Vec<Range<usize>> {
+    let window = trim_window(sample_rate);
+    let min_gap = (min_gap_ms / TRIM_WINDOW_MS).max(1);
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for run in speech_runs(samples, window) {
+        match regions.last_mut() {
+            Some(last) if run.start - last.end < min_gap => last.end = run.end,
+            _ => regions.push(run),
+        }
+    }
+    regions
+        .into_iter()
+        .map(|run| with_margin(run.start * window..run.end * window, samples.len(), sample_rate))
+        .collect()
+}
+
+fn trim_window(sample_rate: u32) -> usize {
+    (sample_rate as usize * TRIM_WINDOW_MS / 1000).max(1)
+}
+
+/// Runs of loud windows long enough to be speech, in window indices.
+fn speech_runs(samples: &[f32], window: usize) -> Vec<Range<usize>> {
+    let levels: Vec<f32> = samples.chunks(window).map(calculate_rms).collect();
+    if levels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = levels.clone();
+    sorted.sort_by(f32::total_cmp);
+    let noise_floor = sorted[sorted.len() / 10];
+    let threshold = (noise_floor * TRIM_NOISE_FLOOR_RATIO)
+        .clamp(TRIM_MIN_THRESHOLD_RMS, TRIM_MAX_THRESHOLD_RMS);
+
+    // A window starts (or ends) speech only as part of a long enough loud run
+    let min_run = (TRIM_MIN_SPEECH_MS / TRIM_WINDOW_MS).max(1);
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    for (i, &level) in levels.iter().chain(std::iter::once(&0.0)).enumerate() {
+        match (level >= threshold, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if i - start >= min_run {
+                    runs.push(start..i);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// Widen `range` by [`TRIM_MARGIN_MS`] on both sides, within the recording.
+fn with_margin(range: Range<usize>, len: usize, sample_rate: u32) -> Range<usize> {
+    let margin = sample_rate as usize * TRIM_MARGIN_MS / 1000;
+    range.start.saturating_sub(margin)..(range.end + margin).min(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+    // 50ms windows, matching the capture amplitude window
+    const WINDOW: usize = 800;
+
+    fn config() -> SilenceDetectorConfig {
+        SilenceDetectorConfig {
+            threshold_rms: 0.02,
+            trailing_silence_ms: 500,
+            min_speech_ms: 200,
+        }
+    }
+
+    fn tone() -> Vec<f32> {
+        (0..WINDOW)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence() -> Vec<f32> {
+        vec![0.001; WINDOW]
+    }
+
+    #[test]
+    fn test_silence_before_speech_never_triggers() {
+        let mut detector = SilenceDetector::new(config(), RATE);
+        for _ in 0..100 {
+            assert!(!detector.process(&silence()));
+        }
+        assert!(!detector.has_speech());
+    }
+
+    #[test]
+    fn test_zero_min_speech_still_waits_for_speech() {
+        let mut detector = SilenceDetector::new(
+            SilenceDetectorConfig {
+                min_speech_ms: 0,
+                ..config()
+            },
+            RATE,
+        );
+        for _ in 0..100 {
+            assert!(!detector.process(&silence()));
+        }
+        detector.process(&tone());
+        let fired = (0..10).filter(|_| detector.process(&silence())).count();
+        assert_eq!(fired, 1);
+    }
+
+    #[test]
+    fn test_triggers_after_trailing_silence() {
+        let mut detector = SilenceDetector::new(config(), RATE);
+        for _ in 0..10 {
+            assert!(!detector.process(&tone()));
+        }
+        // 500ms of trailing silence = 10 windows; the 10th should trigger
+        for _ in 0..9 {
+            assert!(!detector.process(&silence()));
+        }
+        assert!(detector.process(&silence()));
+    }
+
+    #[test]
+    fn test_triggers_only_once() {
+        let mut detector = SilenceDetector::new(config(), RATE);
+        for _ in 0..10 {
+            detector.process(&tone());
+        }
+        let fired = (0..40).filter(|_| detector.process(&silence())).count();
+        assert_eq!(fired, 1);
+    }
+
+    #[test]
+    fn test_short_blip_does_not_count_as_speech() {
+        let mut detector = SilenceDetector::new(config(), RATE);
+        // 100ms of speech is below the 200ms minimum
+        detector.process(&tone());
+        detector.process(&tone());
+        for _ in 0..40 {
+            assert!(!detector.process(&silence()));
+        }
+    }
+
+    #[test]
+    fn test_speech_resets_trailing_silence() {
+        let mut detector = SilenceDetector::new(config(), RATE);
+        for _ in 0..10 {
+            detector.process(&tone());
+        }
+        for _ in 0..8 {
+            assert!(!detector.process(&silence()));
+        }
+        // Speaker resumes mid-pause
+        assert!(!detector.process(&tone()));
+        for _ in 0..9 {
+            assert!(!detector.process(&silence()));
+        }
+        assert!(detector.process(&silence()));
+    }
+
+    fn long_tone(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_speech_bounds_trim_both_ends_with_margin() {
+        // 2s silence, 1s speech, 3s silence
+        let mut audio = vec![0.001; 2 * RATE as usize];
+        audio.extend(long_tone(RATE as usize));
+        audio.extend(vec![0.001; 3 * RATE as usize]);
+
+        let bounds = find_speech_bounds(&audio, RATE).unwrap();
+        let margin = RATE as usize / 4;
+        let tolerance = RATE as usize / 50;
+        assert!(bounds.start.abs_diff(2 * RATE as usize - margin) <= tolerance, "start {}", bounds.start);
+        assert!(bounds.end.abs_diff(3 * RATE as usize + margin) <= tolerance, "end {}", bounds.end);
+    }
+
+    #[test]
+    fn test_speech_bounds_none_for_silence() {
+        assert!(find_speech_bounds(&vec![0.001; RATE as usize * 3], RATE).is_none());
+        assert!(find_speech_bounds(&[], RATE).is_none());
+    }
+
+    #[test]
+    fn test_speech_bounds_ignore_click() {
+        // A 40ms bump is too short to be speech
+        let mut audio = vec![0.001; RATE as usize];
+        audio.extend(long_tone(RATE as usize * 40 / 1000));
+        audio.extend(vec![0.001; RATE as usize]);
+        assert!(find_speech_bounds(&audio, RATE).is_none());
+    }
+
+    #[test]
+    fn test_speech_bounds_rise_above_noise_floor() {
+        // Constant hum louder than the fixed minimum, with speech on top
+        let hum: Vec<f32> = (0..RATE as usize * 4)
+            .map(|i| 0.02 * (2.0 * std::f32::consts::PI * 100.0 * i as f32 / RATE as f32).sin())
+            .collect();
+        let mut audio = hum.clone();
+        for (i, s) in long_tone(RATE as usize).into_iter().enumerate() {
+            audio[RATE as usize * 2 + i] += s;
+        }
+
+        let bounds = find_speech_bounds(&audio, RATE).unwrap();
+        assert!(bounds.start > RATE as usize);
+        assert!(bounds.end < RATE as usize * 4 - RATE as usize / 2);
+    }
+
+    #[test]
+    fn test_speech_bounds_clamped_to_recording() {
+        let audio = long_tone(RATE as usize / 2);
+        assert_eq!(find_speech_bounds(&audio, RATE), Some(0..audio.len()));
+    }
+
+    #[test]
+    fn test_speech_regions_split_at_long_pauses() {
+        // Speech, a short pause, speech, a long pause, speech
+        let mut audio = long_tone(RATE as usize);
+        audio.extend(vec![0.001; RATE as usize / 5]);
+        audio.extend(long_tone(RATE as usize));
+        audio.extend(vec![0.001; RATE as usize * 2]);
+        audio.extend(long_tone(RATE as usize));
+
+        let regions = find_speech_regions(&audio, RATE, 800);
+        assert_eq!(regions.len(), 2, "{:?}", regions);
+        let tolerance = RATE as usize / 50;
+        assert!(regions[0].end.abs_diff(RATE as usize * 11 / 5 + RATE as usize / 4) <= tolerance);
+        assert!(regions[1].start.abs_diff(RATE as usize * 21 / 5 - RATE as usize / 4) <= tolerance);
+        assert_eq!(regions[1].end, audio.len());
+    }
+
+    #[test]
+    fn test_speech_regions_empty_for_silence() {
+        assert!(find_speech_regions(&vec![0.001; RATE as usize * 3], RATE, 800).is_empty());
+        assert!(find_speech_regions(&[], RATE, 800).is_empty());
+    }
+}
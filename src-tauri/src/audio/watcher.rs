@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use super::capture::{list_input_devices, AudioDeviceInfo};
+
+/// How often the input device list is re-enumerated.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Watch the input device list on a background thread and call `on_change`
+/// with the new list whenever a device is added, removed or the default changes.
+///
+/// cpal has no hotplug notifications, so this polls.
+pub fn watch_input_devices<F>(on_change: F)
+where
+    F: Fn(Vec<AudioDeviceInfo>) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut known = list_input_devices().unwrap_or_default();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Ok(current) = list_input_devices() else {
+                continue;
+            };
+            if device_list_changed(&known, &current) {
+                on_change(current.clone());
+                known = current;
+            }
+        }
+    });
+}
+
+/// Whether two device lists differ, ignoring order.
+pub fn device_list_changed(old: &[AudioDeviceInfo], new: &[AudioDeviceInfo]) -> bool {
+    old.len() != new.len() || !old.iter().all(|device| new.contains(device))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, is_default: bool) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            id: format!("test:{}", name),
+            name: name.to_string(),
+            is_default,
+            channels: 2,
+            is_loopback: false,
+        }
+    }
+
+    #[test]
+    fn test_same_devices_in_different_order_unchanged() {
+        let old = vec![device("Built-in", true), device("USB Mic", false)];
+        let new = vec![device("USB Mic", false), device("Built-in", true)];
+        assert!(!device_list_changed(&old, &new));
+    }
+
+    #[test]
+    fn test_device_added_or_removed() {
+        let old = vec![device("Built-in", true)];
+        let new = vec![device("Built-in", true), device("Headset", false)];
+        assert!(device_list_changed(&old, &new));
+        assert!(device_list_changed(&new, &old));
+    }
+
+    #[test]
+    fn test_default_device_change_detected() {
+        let old = vec![device("Built-in", true), device("Headset", false)];
+        let new = vec![device("Built-in", false), device("Headset", true)];
+        assert!(device_list_changed(&old, &new));
+    }
+}
@@ -1,25 +1,44 @@
 use tauri::{ipc::Channel, Emitter, Manager, State};
 
-use crate::audio::capture::{AudioCapture, AudioDeviceInfo};
-use crate::audio::resample::resample_to_16khz;
-use crate::audio::{AudioEvent, TranscriptionResult};
+use std::sync::{Arc, Mutex};
+
+use crate::audio::buffer::{get_spill_dir, CaptureBuffer};
+use crate::audio::capture::{
+    AmplitudeFrame, AudioDeviceInfo, AutoStop, CaptureOptions, DeviceLostCallback, DurationLimit,
+    WarmMicrophone,
+};
+use crate::audio::dsp::ProcessingChain;
+use crate::audio::level::InputLevelMetrics;
+use crate::audio::loopback::LoopbackCapture;
+use crate::audio::playback::AudioPlayer;
+use crate::audio::source::{effective_source, start_source, AudioSource};
+use crate::audio::resample::{resampled_len, ResampledCapture, StreamingResampler};
+use crate::audio::vad::{find_speech_bounds, SilenceDetectorConfig};
+use crate::audio::{AudioEvent, MicTestResult, TranscriptionResult};
+use crate::preferences::{AudioSourceSetting, ResamplerQuality, UserPreferences, VisualizationMode};
+use crate::transcription::engine::{dominant_language, samples_to_ms, TranscribeOptions, Transcript, CANCELLED};
+use crate::transcription::file::{transcribe_segments, FileTranscriptionProgress, FileTranscriptionStage};
+use crate::transcription::meeting::{combine_segments, combine_transcripts, transcribe_track, MeetingTrack};
+use crate::transcription::streaming::{LiveTranscription, StreamingProgress};
+use crate::transcription::worker::{JobKind, NO_MODEL};
 use crate::AppState;
 
 use super::CommandError;
 
+/// How long before the maximum recording length the user is warned.
+const RECORDING_LIMIT_WARNING_SECS: u32 = 30;
+
+/// Microphone tests stop recording after this long.
+const MIC_TEST_MAX_SECS: u32 = 5;
+
 /// Validate that recording can be started (model loaded, not already recording).
 pub fn validate_can_start_recording(state: &AppState) -> Result<(), CommandError> {
-    let engine = state
-        .engine
-        .read()
-        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
-    if engine.is_none() {
+    if state.transcriber.engine_info().is_none() {
         return Err(CommandError::new(
             "NoModelSelected",
             "No active transcription model loaded. Please select and download a model first.",
         ));
     }
-    drop(engine);
 
     let recording = state
         .recording_active
@@ -31,6 +50,47 @@ pub fn validate_can_start_recording(state: &AppState) -> Result<(), CommandError
             "A recording session is already active.",
         ));
     }
+    drop(recording);
+
+    let mic_test = state
+        .mic_test
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    if mic_test.is_some() {
+        return Err(CommandError::new(
+            "MicTestActive",
+            "Finish the microphone test before recording.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate that a microphone test can be started (not recording or already
+/// testing). Unlike recording, no model needs to be loaded.
+pub fn validate_can_start_mic_test(state: &AppState) -> Result<(), CommandError> {
+    let recording = state
+        .recording_active
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    if *recording {
+        return Err(CommandError::new(
+            "AlreadyRecording",
+            "A recording session is already active.",
+        ));
+    }
+    drop(recording);
+
+    let mic_test = state
+        .mic_test
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    if mic_test.is_some() {
+        return Err(CommandError::new(
+            "MicTestActive",
+            "A microphone test is already running.",
+        ));
+    }
 
     Ok(())
 }
@@ -50,17 +110,248 @@ pub fn validate_can_stop_recording(state: &AppState) -> Result<(), CommandError>
     Ok(())
 }
 
+/// Engine options for the user's current preferences. `translate` asks for
+/// English output, which the loaded model has to support. Jobs check the
+/// options again against the model loaded when they start.
+pub fn transcribe_options(state: &AppState, translate: bool) -> Result<TranscribeOptions, CommandError> {
+    let prefs = state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    let vocabulary = state
+        .vocabulary
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .clone();
+    let options = TranscribeOptions {
+        language: prefs.spoken_language.clone(),
+        translate,
+        vocabulary,
+        whisper_decoding: prefs.whisper_decoding.clone(),
+        ..Default::default()
+    };
+    match state.transcriber.engine_info() {
+        Some(engine) => options
+            .for_model(&engine.model)
+            .map_err(|e| CommandError::new("TranslationUnsupported", e)),
+        None => Ok(options),
+    }
+}
+
+/// Apply the user's post-processing preferences to raw engine output.
+pub fn postprocess_transcript(state: &AppState, text: &str) -> Result<String, CommandError> {
+    // Engines that can't be primed with the vocabulary get their spelling fixed here
+    let snap = state
+        .transcriber
+        .engine_info()
+        .is_some_and(|engine| !engine.supports_vocabulary);
+    let text = if snap {
+        let vocabulary = state
+            .vocabulary
+            .read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        crate::transcription::vocabulary::snap_to_vocabulary(text, &vocabulary)
+    } else {
+        text.to_string()
+    };
+
+    let prefs = state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    if prefs.remove_filler_words {
+        Ok(crate::transcription::postprocess::remove_filler_words(&text))
+    } else {
+        Ok(text)
+    }
+}
+
+/// Start a background worker that emits partial transcripts while recording.
+fn start_live_transcription(
+    app: &tauri::AppHandle,
+    on_event: &Channel<AudioEvent>,
+    capture: &dyn AudioSource,
+    resampler_quality: ResamplerQuality,
+    options: TranscribeOptions,
+) -> LiveTranscription {
+    // Segments get the same cleanup the tail gets on stop
+    let chain = app
+        .state::<AppState>()
+        .preferences
+        .read()
+        .map(|prefs| prefs.audio_processing.chain())
+        .unwrap_or_default();
+    let engine_app = app.clone();
+    let transcribe = Box::new(move |audio: &[f32]| {
+        let audio = audio.to_vec();
+        engine_app
+            .state::<AppState>()
+            .transcriber
+            .submit(JobKind::LivePreview, &options, move |engine, options| {
+                engine.transcribe_with(&audio, options)
+            })
+            .wait()
+    });
+
+    let partial_app = app.clone();
+    let partial_channel = on_event.clone();
+    let on_partial = Box::new(move |text: String| {
+        let state = partial_app.state::<AppState>();
+        let text = postprocess_transcript(&state, &text).unwrap_or(text);
+        let event = AudioEvent::PartialTranscription {
+            text,
+            is_final: false,
+        };
+        let _ = partial_channel.send(event.clone());
+        let _ = partial_app.emit("partial-transcription", event);
+    });
+
+    LiveTranscription::start(capture.tap(), resampler_quality, chain, transcribe, on_partial)
+}
+
+/// Open, reopen or close the warm microphone to match preferences.
+/// Deferred while recording so an attached session isn't cut off.
+pub fn sync_warm_microphone(app: &tauri::AppHandle, prefs: &UserPreferences) {
+    let state = app.state::<AppState>();
+    if state.recording_active.read().map(|r| *r).unwrap_or(false) {
+        return;
+    }
+    let Ok(mut warm) = state.warm_microphone.lock() else {
+        return;
+    };
+
+    let settings = &prefs.warm_microphone;
+    let device_id = prefs.selected_audio_device_id.as_deref();
+    let channel = prefs.input_channel_for(device_id);
+    // Other sources don't record from a device, so there's nothing to keep open
+    let uses_microphone = effective_source(&prefs.audio_source) == AudioSourceSetting::Microphone;
+    if !settings.enabled || !uses_microphone {
+        if warm.take().is_some() {
+            eprintln!("[warm_microphone] Disarmed");
+        }
+    } else if !warm
+        .as_ref()
+        .is_some_and(|w| !w.is_lost() && w.matches(device_id, settings.pre_roll_ms, channel))
+    {
+        // Close the old stream before reopening the same device
+        *warm = None;
+        match WarmMicrophone::start(device_id, settings.pre_roll_ms, channel) {
+            Ok(mic) => {
+                eprintln!("[warm_microphone] Armed with {}ms pre-roll", settings.pre_roll_ms);
+                *warm = Some(mic);
+            }
+            Err(e) => eprintln!("[warm_microphone] Failed to arm: {}", e),
+        }
+    }
+
+    let armed = warm.is_some();
+    drop(warm);
+    let _ = app.emit("mic-armed-changed", armed);
+    crate::update_tray_recording_state(app, false);
+}
+
+/// React to the recording device being disconnected: continue on the default
+/// device when failover is enabled, otherwise end the session through the same
+/// path as the stop hotkey so the audio captured so far is still transcribed.
+fn handle_device_lost(app: &tauri::AppHandle, on_event: &Channel<AudioEvent>) {
+    let state = app.state::<AppState>();
+    let failover = state
+        .preferences
+        .read()
+        .map(|prefs| prefs.device_failover)
+        .unwrap_or(false);
+    eprintln!("[device_lost] Recording device disconnected (failover: {})", failover);
+
+    let fallback_device = if failover {
+        let mut active = match state.active_capture.lock() {
+            Ok(active) => active,
+            Err(_) => return,
+        };
+        match active.as_mut().map(|capture| capture.fail_over_to_default()) {
+            Some(Ok(name)) => Some(name),
+            Some(Err(e)) => {
+                eprintln!("[device_lost] Failover failed: {}", e);
+                None
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+
+    let event = AudioEvent::DeviceLost {
+        fallback_device: fallback_device.clone(),
+    };
+    let _ = on_event.send(event.clone());
+    let _ = app.emit("device-lost", event);
+
+    if fallback_device.is_none() {
+        let _ = app.emit("hotkey-stop-recording", ());
+    }
+}
+
+/// Called by the device watcher when input devices are added or removed.
+pub fn handle_devices_changed(app: &tauri::AppHandle, devices: Vec<AudioDeviceInfo>) {
+    let state = app.state::<AppState>();
+
+    // Some backends stop delivering audio without reporting an error, so a
+    // recording device that vanished from the list also counts as lost
+    if let Ok(active) = state.active_capture.lock() {
+        if let Some(capture) = active.as_ref() {
+            if !devices.iter().any(|d| d.id == capture.device_id()) {
+                capture.mark_device_lost();
+            }
+        }
+    }
+
+    let _ = app.emit("devices-changed", &devices);
+    migrate_device_preferences(app, &devices);
+
+    // Reopen a warm microphone whose device went away or came back
+    let prefs = state.preferences.read().map(|p| p.clone());
+    if let Ok(prefs) = prefs {
+        sync_warm_microphone(app, &prefs);
+    }
+}
+
+/// Re-key device preferences saved by name now that the device is connected.
+fn migrate_device_preferences(app: &tauri::AppHandle, devices: &[AudioDeviceInfo]) {
+    let state = app.state::<AppState>();
+    let Ok(mut prefs) = state.preferences.write() else {
+        return;
+    };
+    if prefs.migrate_device_ids(devices) {
+        eprintln!("[migrate_device_preferences] Device preferences now use device ids");
+        if let Err(e) = crate::preferences::storage::save_preferences(&state.app_data_dir, &prefs) {
+            eprintln!("[migrate_device_preferences] Failed to save preferences: {}", e);
+        }
+    }
+}
+
+/// Refresh the tray and apply warm microphone changes deferred during a session.
+fn rearm_after_session(app: &tauri::AppHandle, state: &AppState) -> Result<(), CommandError> {
+    let prefs = state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .clone();
+    sync_warm_microphone(app, &prefs);
+    Ok(())
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn start_recording(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
     on_event: Channel<AudioEvent>,
+    translate: Option<bool>,
 ) -> Result<String, CommandError> {
     eprintln!("[start_recording] Command invoked");
     if let Err(e) = validate_can_start_recording(&state) {
         eprintln!("[start_recording] Validation failed: {} - {}", e.code, e.message);
         return Err(e);
     }
+    let session_options = transcribe_options(&state, translate.unwrap_or(false))?;
     eprintln!("[start_recording] Validation passed");
 
     let session_id = uuid::Uuid::new_v4().to_string();
@@ -68,34 +359,161 @@ pub async fn start_recording(
     // Create amplitude callback that sends events to the frontend
     let event_channel = on_event.clone();
     let app_clone = app.clone();
-    let amplitude_callback = Box::new(move |amplitudes: Vec<f32>, rms: f32| {
-        let _ = event_channel.send(AudioEvent::AmplitudeUpdate {
-            amplitudes: amplitudes.clone(),
-            rms,
-        });
+    let amplitude_callback = Box::new(move |frame: AmplitudeFrame| {
+        let (bands, peaks) = match frame.spectrum {
+            Some(spectrum) => (Some(spectrum.bands), Some(spectrum.peaks)),
+            None => (None, None),
+        };
+        let event = AudioEvent::AmplitudeUpdate {
+            amplitudes: frame.amplitudes,
+            rms: frame.rms,
+            bands,
+            peaks,
+        };
+        let _ = event_channel.send(event.clone());
         // Also emit to all windows so the overlay can show amplitude visualization
-        let _ = app_clone.emit(
-            "amplitude-update",
-            AudioEvent::AmplitudeUpdate { amplitudes, rms },
-        );
+        let _ = app_clone.emit("amplitude-update", event);
     });
 
-    // Read selected device from preferences
-    let device_name = {
+    // Read selected device and channel, hands-free, live transcription, visualizer and resampler settings from preferences
+    let (device_id, channel, hands_free, live_transcription, max_recording_minutes, source, spectrum, resampler_quality) = {
         let prefs = state.preferences.read()
             .map_err(|e| CommandError::new("LockError", e.to_string()))?;
-        prefs.selected_audio_device.clone()
+        let hands_free = matches!(prefs.recording_mode, crate::preferences::RecordingMode::HandsFree)
+            .then(|| prefs.hands_free.clone());
+        let channel = prefs.input_channel_for(prefs.selected_audio_device_id.as_deref());
+        (
+            prefs.selected_audio_device_id.clone(),
+            channel,
+            hands_free,
+            prefs.live_transcription,
+            prefs.max_recording_minutes,
+            effective_source(&prefs.audio_source),
+            prefs.overlay_visualization_mode() == VisualizationMode::Spectrum,
+            prefs.resampler_quality,
+        )
     };
 
-    // Start audio capture
-    eprintln!("[start_recording] Starting audio capture...");
-    let capture = AudioCapture::start(amplitude_callback, device_name.as_deref())
+    // In hands-free mode, trailing silence triggers the same stop path as the hotkey
+    let auto_stop = hands_free.map(|settings| {
+        let app_clone = app.clone();
+        AutoStop {
+            config: SilenceDetectorConfig {
+                threshold_rms: settings.silence_threshold,
+                trailing_silence_ms: settings.trailing_silence_ms,
+                min_speech_ms: settings.min_speech_ms,
+            },
+            on_silence: Box::new(move || {
+                eprintln!("[start_recording] Trailing silence detected, stopping");
+                let _ = app_clone.emit("hotkey-stop-recording", ());
+            }),
+        }
+    });
+
+    // Device loss is reported on the audio backend's thread; handle it elsewhere
+    let lost_app = app.clone();
+    let lost_channel = on_event.clone();
+    let on_device_lost: DeviceLostCallback = Arc::new(move || {
+        let app = lost_app.clone();
+        let channel = lost_channel.clone();
+        std::thread::spawn(move || handle_device_lost(&app, &channel));
+    });
+
+    // Reaching the maximum length stops the session like the hotkey would
+    let warn_app = app.clone();
+    let warn_channel = on_event.clone();
+    let limit_app = app.clone();
+    let limit = DurationLimit {
+        max_secs: max_recording_minutes * 60,
+        warning_secs: RECORDING_LIMIT_WARNING_SECS,
+        on_warning: Box::new(move |remaining_secs| {
+            let event = AudioEvent::RecordingLimitWarning { remaining_secs };
+            let _ = warn_channel.send(event.clone());
+            let _ = warn_app.emit("recording-limit-warning", event);
+        }),
+        on_limit: Box::new(move || {
+            eprintln!("[start_recording] Maximum recording length reached, stopping");
+            let _ = limit_app.emit("hotkey-stop-recording", ());
+        }),
+    };
+
+    // Tell the user about muted or overdriven input while they can still fix it
+    let level_app = app.clone();
+    let level_channel = on_event.clone();
+    let on_level_warning = Box::new(move |kind| {
+        eprintln!("[start_recording] Input level warning: {:?}", kind);
+        let event = AudioEvent::LevelWarning { kind };
+        let _ = level_channel.send(event.clone());
+        let _ = level_app.emit("level-warning", event);
+    });
+
+    // Resample while recording so stopping only has to flush the tail
+    let spill_dir = get_spill_dir(&state.app_data_dir);
+
+    // Calls are recorded as two tracks: the microphone and the system audio
+    let system_audio = {
+        let prefs = state.preferences.read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        (prefs.system_audio.enabled && source == AudioSourceSetting::Microphone)
+            .then(|| prefs.system_audio.clone())
+    };
+    let loopback = system_audio
+        .map(|settings| LoopbackCapture::start(&settings, resampler_quality, Some(spill_dir.clone())))
+        .transpose()
         .map_err(|e| {
-            eprintln!("[start_recording] Audio capture failed: {}", e);
-            CommandError::new("MicrophoneUnavailable", e)
+            eprintln!("[start_recording] System audio capture failed: {}", e);
+            CommandError::new("SystemAudioUnavailable", e)
         })?;
+    if let Some(loopback) = &loopback {
+        eprintln!("[start_recording] Recording system audio from {}", loopback.device_name());
+    }
+    let resampled = Arc::new(Mutex::new(ResampledCapture::new(
+        resampler_quality,
+        Some(spill_dir.clone()),
+    )));
+
+    let options = CaptureOptions {
+        device_id,
+        channel,
+        auto_stop,
+        on_device_lost: Some(on_device_lost),
+        limit: Some(limit),
+        spill_dir: Some(spill_dir),
+        spectrum,
+        on_level_warning: Some(on_level_warning),
+        resampled: Some(Arc::clone(&resampled)),
+    };
+
+    // Start audio capture, attaching to the warm microphone when it is armed
+    eprintln!("[start_recording] Starting audio capture from {:?}...", source);
+    let capture = {
+        let warm = state
+            .warm_microphone
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        match warm.as_ref() {
+            Some(warm) if !warm.is_lost() && source == AudioSourceSetting::Microphone => warm
+                .attach(amplitude_callback, options)
+                .map(|capture| Box::new(capture) as Box<dyn AudioSource>),
+            _ => start_source(&source, amplitude_callback, options),
+        }
+    };
+    let capture = capture.map_err(|e| {
+        eprintln!("[start_recording] Audio capture failed: {}", e);
+        CommandError::new("MicrophoneUnavailable", e)
+    })?;
     eprintln!("[start_recording] Audio capture started");
 
+    // Partial results would only cover the microphone side of a call
+    if live_transcription && loopback.is_none() {
+        let live = start_live_transcription(&app, &on_event, capture.as_ref(), resampler_quality, session_options.clone());
+        let mut active = state
+            .live_transcription
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        *active = Some(live);
+    }
+
     // Store capture handle in state
     {
         let mut active = state
@@ -104,6 +522,27 @@ pub async fn start_recording(
             .map_err(|e| CommandError::new("LockError", e.to_string()))?;
         *active = Some(capture);
     }
+    {
+        let mut session_resampler = state
+            .session_resampler
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        *session_resampler = Some(resampled);
+    }
+    {
+        let mut system_capture = state
+            .system_capture
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        *system_capture = loopback;
+    }
+    {
+        let mut options = state
+            .session_options
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        *options = Some(session_options);
+    }
 
     // Mark recording as active
     {
@@ -160,7 +599,7 @@ pub async fn stop_recording(
     validate_can_stop_recording(&state)?;
 
     // Stop capture and get audio buffer
-    let (audio_buffer, sample_rate) = {
+    let (audio_buffer, sample_rate, input_levels) = {
         let mut active = state
             .active_capture
             .lock()
@@ -168,7 +607,54 @@ pub async fn stop_recording(
         let capture = active
             .take()
             .ok_or_else(|| CommandError::new("NotRecording", "No active capture found."))?;
-        capture.stop()
+        let input_levels = capture.input_levels();
+        let (buffer, sample_rate) = capture.stop();
+        (buffer, sample_rate, input_levels)
+    };
+    eprintln!("[stop_recording] Input levels: {:?}", input_levels);
+    let session_resampler = state
+        .session_resampler
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .take();
+    let loopback = state
+        .system_capture
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .take();
+    let options = match state
+        .session_options
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .take()
+    {
+        Some(options) => options,
+        None => transcribe_options(&state, false)?,
+    };
+    let live = state
+        .live_transcription
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .take();
+    // A preview job still running would hold up finishing the live transcription
+    if live.is_some() {
+        state.transcriber.cancel_kind(JobKind::LivePreview);
+    }
+    let (quality, chain) = {
+        let prefs = state
+            .preferences
+            .read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        (prefs.resampler_quality, prefs.audio_processing.chain())
+    };
+    let stopped = StoppedRecording {
+        audio_buffer,
+        sample_rate,
+        session_resampler,
+        loopback,
+        live,
+        quality,
+        chain,
     };
 
     // Mark recording as stopped and capture duration
@@ -188,61 +674,99 @@ pub async fn stop_recording(
 
     let _ = on_event.send(AudioEvent::RecordingStopped);
     let _ = app.emit("recording-stopped", ());
-    crate::update_tray_recording_state(&app, false);
+    rearm_after_session(&app, &state)?;
 
-    // Unregister Escape shortcut
-    {
-        use tauri_plugin_global_shortcut::GlobalShortcutExt;
-        let _ = app.global_shortcut().unregister("Escape");
-    }
+    // Escape cancels the transcription until this returns
+    let _escape = EscapeShortcut(app.clone());
 
     let _ = on_event.send(AudioEvent::TranscriptionStarted);
 
     let start_time = std::time::Instant::now();
 
-    // Resample to 16kHz for transcription engine
-    let audio_16khz = resample_to_16khz(&audio_buffer, sample_rate)
-        .map_err(|e| CommandError::new("TranscriptionFailed", format!("Resampling failed: {}", e)))?;
+    // Joining the live and system audio workers, resampling and processing
+    // all block, so they run off the async runtime
+    let RecordedAudio {
+        audio_16khz,
+        committed_len,
+        live_progress,
+        system_16khz,
+    } = tauri::async_runtime::spawn_blocking(move || stopped.into_16khz())
+        .await
+        .map_err(|e| CommandError::new("TranscriptionFailed", e.to_string()))?
+        .map_err(|e| CommandError::new("TranscriptionFailed", e))?;
 
     // Generate session ID before saving audio so we can use it as filename
     let session_id = uuid::Uuid::new_v4().to_string();
 
-    // Save audio as WAV for history playback
-    let audio_file_name = crate::history::save_audio_wav(
-        &state.app_data_dir, &session_id, &audio_16khz, 16000,
-    ).ok();
+    if let Some(system_16khz) = system_16khz {
+        let call = CallRecording {
+            session_id,
+            microphone: audio_16khz,
+            system: system_16khz,
+            recording_duration_ms,
+            input_levels,
+            start_time,
+            options,
+        };
+        return finish_call_recording(&app, &state, &on_event, call).await;
+    }
 
-    // Transcribe
-    let text = {
-        let engine = state
-            .engine
-            .read()
-            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
-        let engine = engine
-            .as_ref()
-            .ok_or_else(|| CommandError::new("NoModelSelected", "No model loaded."))?;
-        engine
-            .transcribe(&audio_16khz)
-            .map_err(|e| CommandError::new("TranscriptionFailed", e))?
+    // Cut the silence before and after speech: Whisper hallucinates on it and
+    // it bloats the saved audio. Committed live segments are already
+    // transcribed, so the cut never reaches into them.
+    let speech = match live_progress.speech_range(find_speech_bounds(&audio_16khz, 16000)) {
+        Some(speech) => speech,
+        None => {
+            eprintln!("[stop_recording] No speech detected, skipping transcription");
+            return Ok(report_no_speech(&app, &on_event, session_id, start_time));
+        }
     };
+    let trimmed_start_ms = samples_to_ms(speech.start);
+    let trimmed_end_ms = samples_to_ms(audio_16khz.len() - speech.end);
+    let tail_16khz = &audio_16khz[speech.start.max(committed_len)..speech.end];
+    let tail_offset_ms = samples_to_ms(speech.start.max(committed_len));
 
-    // Post-process: remove filler words if enabled
-    let text = {
-        let prefs = state
-            .preferences
-            .read()
-            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
-        if prefs.remove_filler_words {
-            crate::transcription::postprocess::remove_filler_words(&text)
-        } else {
-            text
-        }
+    // Save audio for history playback
+    let audio_file_name = save_history_audio(&state, session_id.clone(), audio_16khz[speech].to_vec()).await;
+
+    // Transcribe the tail
+    let tail = if tail_16khz.is_empty() {
+        Transcript::default()
+    } else {
+        let tail_16khz = tail_16khz.to_vec();
+        state
+            .transcriber
+            .submit(JobKind::Dictation, &options, move |engine, options| {
+                engine.transcribe_with(&tail_16khz, options)
+            })
+            .join()
+            .await
+            .map_err(|e| transcription_error(&app, e))?
     };
+    // Time the transcript from the start of the saved (trimmed) audio
+    let transcript = live_progress
+        .transcript_with(tail_offset_ms, tail)
+        .shifted(-(trimmed_start_ms as i64));
+    let language = transcript.language.clone();
+    let confidence = transcript.confidence();
+    let held = hold_for_confirmation(&state, confidence)?;
+
+    // Post-process: remove filler words if enabled
+    let text = postprocess_transcript(&state, &transcript.text)?;
 
     let duration_ms = start_time.elapsed().as_millis() as u64;
 
+    let final_event = AudioEvent::PartialTranscription {
+        text: text.clone(),
+        is_final: true,
+    };
+    let _ = on_event.send(final_event.clone());
+    let _ = app.emit("partial-transcription", final_event);
     let _ = on_event.send(AudioEvent::TranscriptionCompleted {
         text: text.clone(),
+        confidence,
+        confidence_available: confidence_available(&state),
+        segments: transcript.segments.clone(),
     });
     let _ = app.emit("transcription-completed", ());
 
@@ -259,6 +783,17 @@ pub async fn stop_recording(
             transcription_duration_ms: duration_ms,
             audio_device: prefs.selected_audio_device.clone(),
             audio_file_name: audio_file_name.clone(),
+            source_file_name: None,
+            trimmed_start_ms,
+            trimmed_end_ms,
+            input_levels: Some(input_levels),
+            system_audio_file_name: None,
+            language: language.clone(),
+            translated: options.translate,
+            transcript_file_name: crate::history::save_transcript(&state.app_data_dir, &session_id, &transcript)
+                .ok()
+                .flatten(),
+            confidence,
         };
         drop(prefs);
         let _ = crate::history::add_entry(&state.app_data_dir, &entry);
@@ -274,6 +809,358 @@ pub async fn stop_recording(
         session_id,
         text,
         duration_ms,
+        no_speech: false,
+        language,
+        confidence,
+        held,
+    })
+}
+
+/// A recording whose capture has stopped, before it's converted to the
+/// 16kHz audio that gets transcribed.
+struct StoppedRecording {
+    audio_buffer: CaptureBuffer,
+    sample_rate: u32,
+    session_resampler: Option<Arc<Mutex<ResampledCapture>>>,
+    loopback: Option<LoopbackCapture>,
+    live: Option<LiveTranscription>,
+    quality: ResamplerQuality,
+    chain: ProcessingChain,
+}
+
+/// A stopped recording at 16kHz.
+struct RecordedAudio {
+    audio_16khz: Vec<f32>,
+    /// Length of the start of `audio_16khz` the live worker already transcribed.
+    committed_len: usize,
+    live_progress: StreamingProgress,
+    /// System audio of a call recording.
+    system_16khz: Option<Vec<f32>>,
+}
+
+impl StoppedRecording {
+    /// Wait for the live and system audio workers, then resample and clean
+    /// up the microphone audio they haven't handled. Blocks until done.
+    fn into_16khz(self) -> Result<RecordedAudio, String> {
+        let Self {
+            mut audio_buffer,
+            sample_rate,
+            session_resampler,
+            loopback,
+            live,
+            quality,
+            chain,
+        } = self;
+
+        // A failed system track still leaves the microphone recording to transcribe
+        let system_16khz = loopback.and_then(|loopback| {
+            loopback
+                .finish()
+                .map_err(|e| eprintln!("[stop_recording] System audio track lost: {}", e))
+                .ok()
+        });
+
+        // Collect whatever the live worker already decoded
+        let mut live_progress = live.map(LiveTranscription::finish).unwrap_or_default();
+
+        // The session was resampled as it was captured, so only the tail after
+        // the segments the live worker committed (which are already resampled)
+        // is read back, chunk by chunk from the (possibly spilled) buffer
+        let mut audio_16khz = std::mem::take(&mut live_progress.committed_audio);
+        let committed_len = audio_16khz.len();
+        let resampled = session_resampler
+            .and_then(|resampled| Arc::try_unwrap(resampled).ok())
+            .and_then(|resampled| resampled.into_inner().ok())
+            .map(ResampledCapture::finish);
+        match resampled {
+            Some(Ok(mut resampled)) => resampled.stream_from(
+                resampled_len(live_progress.committed_samples, sample_rate),
+                &mut |chunk| {
+                    audio_16khz.extend_from_slice(chunk);
+                    Ok(())
+                },
+            ),
+            // Resample the capture buffer after all
+            resampled => {
+                if let Some(Err(e)) = resampled {
+                    eprintln!("[stop_recording] Incremental resampling failed: {}", e);
+                }
+                let mut resampler = StreamingResampler::with_quality(sample_rate, quality)?;
+                audio_buffer
+                    .stream_from(live_progress.committed_samples, &mut |chunk| {
+                        resampler.push(chunk, &mut audio_16khz)
+                    })
+                    .and_then(|_| resampler.finish(&mut audio_16khz))
+            }
+        }
+        .map_err(|e| format!("Resampling failed: {}", e))?;
+        // Removes the spill file, if the recording was long enough to need one
+        drop(audio_buffer);
+
+        // Clean up the recording; the processed audio is what gets transcribed
+        // and saved. Committed segments were processed one by one as they were
+        // transcribed, so only the tail is left
+        if !chain.is_empty() {
+            chain.process(&mut audio_16khz[committed_len..], 16000);
+        }
+
+        Ok(RecordedAudio {
+            audio_16khz,
+            committed_len,
+            live_progress,
+            system_16khz,
+        })
+    }
+}
+
+/// Save a 16kHz recording for history playback, encoding it off the async
+/// runtime. Returns the file name, or `None` if it couldn't be saved.
+async fn save_history_audio(state: &AppState, id: String, samples: Vec<f32>) -> Option<String> {
+    let app_data_dir = state.app_data_dir.clone();
+    let format = state
+        .preferences
+        .read()
+        .map(|prefs| prefs.history_audio_format)
+        .unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::history::save_audio(&app_data_dir, &id, &samples, 16000, format).ok()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Reports `stage` of transcribing `file_name` to the frontend, in whole
+/// percent steps.
+fn file_progress(app: tauri::AppHandle, file_name: String, stage: FileTranscriptionStage) -> impl FnMut(f32) {
+    let mut last_percent = None;
+    move |fraction| {
+        let percent = (fraction * 100.0).floor();
+        if last_percent == Some(percent) {
+            return;
+        }
+        last_percent = Some(percent);
+        let _ = app.emit(
+            "file-transcription-progress",
+            FileTranscriptionProgress {
+                file_name: file_name.clone(),
+                stage,
+                percent,
+            },
+        );
+    }
+}
+
+/// Unregisters Escape when dropped, unless a new recording has claimed it.
+struct EscapeShortcut(tauri::AppHandle);
+
+impl Drop for EscapeShortcut {
+    fn drop(&mut self) {
+        use tauri_plugin_global_shortcut::GlobalShortcutExt;
+        let state = self.0.state::<AppState>();
+        if !state.recording_active.read().map(|r| *r).unwrap_or(false) {
+            let _ = self.0.global_shortcut().unregister("Escape");
+        }
+    }
+}
+
+/// Map the error of a failed transcription job to a command error.
+pub fn job_error(e: String) -> CommandError {
+    match e.as_str() {
+        CANCELLED => CommandError::new("TranscriptionCancelled", e),
+        NO_MODEL => CommandError::new("NoModelSelected", e),
+        _ => CommandError::new("TranscriptionFailed", e),
+    }
+}
+
+/// Like [`job_error`], for the dictation being waited on. A cancelled one
+/// also dismisses the overlay, since no result is coming.
+fn transcription_error(app: &tauri::AppHandle, e: String) -> CommandError {
+    if e == CANCELLED {
+        eprintln!("[stop_recording] Transcription cancelled");
+        let _ = app.emit("transcription-cancelled", ());
+        if let Some(overlay) = app.get_webview_window("recording-overlay") {
+            let _ = overlay.hide();
+        }
+    }
+    job_error(e)
+}
+
+/// Tell the frontend a session ended without speech and hide the overlay.
+fn report_no_speech(
+    app: &tauri::AppHandle,
+    on_event: &Channel<AudioEvent>,
+    session_id: String,
+    start_time: std::time::Instant,
+) -> TranscriptionResult {
+    let _ = on_event.send(AudioEvent::NoSpeechDetected);
+    let _ = app.emit("transcription-completed", ());
+    if let Some(overlay) = app.get_webview_window("recording-overlay") {
+        let _ = overlay.hide();
+    }
+    TranscriptionResult {
+        session_id,
+        text: String::new(),
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        no_speech: true,
+        language: None,
+        confidence: None,
+        held: false,
+    }
+}
+
+/// Whether the loaded engine's transcripts carry a confidence at all.
+fn confidence_available(state: &AppState) -> bool {
+    state
+        .transcriber
+        .engine_info()
+        .is_some_and(|info| info.reports_confidence)
+}
+
+/// Whether a transcript with `confidence` waits for the user to confirm it
+/// instead of being injected.
+fn hold_for_confirmation(state: &AppState, confidence: Option<f32>) -> Result<bool, CommandError> {
+    Ok(state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .low_confidence
+        .should_hold(confidence))
+}
+
+/// Both tracks of a stopped call recording, at 16kHz.
+struct CallRecording {
+    session_id: String,
+    microphone: Vec<f32>,
+    system: Vec<f32>,
+    recording_duration_ms: u64,
+    input_levels: InputLevelMetrics,
+    start_time: std::time::Instant,
+    options: TranscribeOptions,
+}
+
+/// Transcribe a call recording as a conversation, one labelled line per
+/// turn, and save both tracks to history so they can be re-transcribed.
+async fn finish_call_recording(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    on_event: &Channel<AudioEvent>,
+    call: CallRecording,
+) -> Result<TranscriptionResult, CommandError> {
+    let CallRecording {
+        session_id,
+        microphone,
+        system,
+        recording_duration_ms,
+        input_levels,
+        start_time,
+        options,
+    } = call;
+
+    // The tracks come back from the worker to be saved
+    let (utterances, microphone, system) = state
+        .transcriber
+        .submit(JobKind::Dictation, &options, move |engine, options| {
+            // Each track is half of the job's progress
+            let track_options = |from, to| TranscribeOptions {
+                job: options.job.step(from, to),
+                ..options.clone()
+            };
+            let mut utterances =
+                transcribe_track(engine, &microphone, MeetingTrack::Microphone, &track_options(0.0, 0.5), &mut |_| {})?;
+            utterances.extend(transcribe_track(
+                engine,
+                &system,
+                MeetingTrack::System,
+                &track_options(0.5, 1.0),
+                &mut |_| {},
+            )?);
+            Ok((utterances, microphone, system))
+        })
+        .join()
+        .await
+        .map_err(|e| transcription_error(app, e))?;
+    if utterances.is_empty() {
+        eprintln!("[stop_recording] No speech detected on either track, skipping transcription");
+        return Ok(report_no_speech(app, on_event, session_id, start_time));
+    }
+
+    // Post-process each turn on its own so filler removal can't merge lines
+    let utterances = utterances
+        .into_iter()
+        .map(|mut utterance| {
+            utterance.text = postprocess_transcript(state, &utterance.text)?;
+            Ok(utterance)
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?;
+    let transcript = Transcript {
+        language: dominant_language(utterances.iter().map(|u| (u.text.as_str(), u.language.as_deref()))),
+        segments: combine_segments(&utterances),
+        text: combine_transcripts(utterances),
+    };
+    let language = transcript.language.clone();
+    let text = transcript.text.clone();
+    let confidence = transcript.confidence();
+    let held = hold_for_confirmation(state, confidence)?;
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+
+    let final_event = AudioEvent::PartialTranscription {
+        text: text.clone(),
+        is_final: true,
+    };
+    let _ = on_event.send(final_event.clone());
+    let _ = app.emit("partial-transcription", final_event);
+    let _ = on_event.send(AudioEvent::TranscriptionCompleted {
+        text: text.clone(),
+        confidence,
+        confidence_available: confidence_available(state),
+        segments: transcript.segments.clone(),
+    });
+    let _ = app.emit("transcription-completed", ());
+
+    // Save both tracks untrimmed, so they stay aligned with each other
+    let audio_file_name = save_history_audio(state, session_id.clone(), microphone).await;
+    let system_audio_file_name = save_history_audio(state, format!("{}-system", session_id), system).await;
+    {
+        let prefs = state.preferences.read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        let entry = crate::history::HistoryEntry {
+            id: session_id.clone(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            text: text.clone(),
+            model_id: prefs.active_model_id.clone().unwrap_or_default(),
+            recording_duration_ms,
+            transcription_duration_ms: duration_ms,
+            audio_device: prefs.selected_audio_device.clone(),
+            audio_file_name,
+            source_file_name: None,
+            trimmed_start_ms: 0,
+            trimmed_end_ms: 0,
+            input_levels: Some(input_levels),
+            system_audio_file_name,
+            language: language.clone(),
+            translated: options.translate,
+            transcript_file_name: crate::history::save_transcript(&state.app_data_dir, &session_id, &transcript)
+                .ok()
+                .flatten(),
+            confidence,
+        };
+        drop(prefs);
+        let _ = crate::history::add_entry(&state.app_data_dir, &entry);
+    }
+
+    if let Some(overlay) = app.get_webview_window("recording-overlay") {
+        let _ = overlay.hide();
+    }
+
+    Ok(TranscriptionResult {
+        session_id,
+        text,
+        duration_ms,
+        no_speech: false,
+        language,
+        confidence,
+        held,
     })
 }
 
@@ -293,6 +1180,39 @@ pub async fn cancel_recording(
         let capture = active.take();
         drop(capture);
     }
+    {
+        let mut session_resampler = state
+            .session_resampler
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        session_resampler.take();
+    }
+    {
+        let mut system_capture = state
+            .system_capture
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        system_capture.take();
+    }
+    {
+        let mut session_options = state
+            .session_options
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        session_options.take();
+    }
+
+    // Abandon any live transcription in flight
+    {
+        let mut live = state
+            .live_transcription
+            .lock()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        if let Some(live) = live.take() {
+            live.cancel();
+        }
+    }
+    state.transcriber.cancel_kind(JobKind::LivePreview);
 
     // Mark recording as stopped
     {
@@ -304,7 +1224,7 @@ pub async fn cancel_recording(
     }
 
     let _ = app.emit("recording-cancelled", ());
-    crate::update_tray_recording_state(&app, false);
+    rearm_after_session(&app, &state)?;
 
     // Unregister Escape shortcut
     use tauri_plugin_global_shortcut::GlobalShortcutExt;
@@ -318,8 +1238,244 @@ pub async fn cancel_recording(
     Ok(())
 }
 
+/// Transcribe an existing audio file with the active model and save it to history.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn transcribe_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<TranscriptionResult, CommandError> {
+    eprintln!("[transcribe_file] Transcribing {}", path);
+    let path = std::path::PathBuf::from(path);
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !path.is_file() {
+        return Err(CommandError::new(
+            "FileNotFound",
+            format!("No audio file at {}", path.display()),
+        ));
+    }
+
+    let resampler_quality = state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .resampler_quality;
+    let options = transcribe_options(&state, false)?;
+    if state.transcriber.engine_info().is_none() {
+        return Err(CommandError::new(
+            "NoModelSelected",
+            "No active transcription model loaded. Please select and download a model first.",
+        ));
+    }
+    let start_time = std::time::Instant::now();
+
+    // Decode before queueing, so dictations don't wait behind a long file
+    // that isn't even being transcribed yet
+    let mut on_decode_progress = file_progress(app.clone(), file_name.clone(), FileTranscriptionStage::Decoding);
+    let decoded = tauri::async_runtime::spawn_blocking(move || {
+        crate::transcription::file::decode_file(&path, resampler_quality, &mut on_decode_progress)
+    })
+    .await
+    .map_err(|e| CommandError::new("TranscriptionFailed", e.to_string()))?
+    .map_err(|e| {
+        eprintln!("[transcribe_file] Failed to decode: {}", e);
+        CommandError::new("TranscriptionFailed", e)
+    })?;
+
+    // The audio comes back from the worker to be saved
+    let mut on_progress = file_progress(app.clone(), file_name.clone(), FileTranscriptionStage::Transcribing);
+    let audio_16khz = decoded.audio_16khz;
+    let (transcript, audio_16khz) = state
+        .transcriber
+        .submit(JobKind::File, &options, move |engine, options| {
+            on_progress(0.0);
+            let transcript = transcribe_segments(engine, &audio_16khz, options, &mut on_progress)?;
+            Ok((transcript, audio_16khz))
+        })
+        .join()
+        .await
+        .map_err(|e| {
+            eprintln!("[transcribe_file] Failed: {}", e);
+            job_error(e)
+        })?;
+
+    let text = postprocess_transcript(&state, &transcript.text)?;
+    let confidence = transcript.confidence();
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    let _ = app.emit(
+        "file-transcription-progress",
+        FileTranscriptionProgress {
+            file_name: file_name.clone(),
+            stage: FileTranscriptionStage::Transcribing,
+            percent: 100.0,
+        },
+    );
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let audio_file_name = save_history_audio(&state, session_id.clone(), audio_16khz).await;
+    {
+        let prefs = state.preferences.read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        let entry = crate::history::HistoryEntry {
+            id: session_id.clone(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            text: text.clone(),
+            model_id: prefs.active_model_id.clone().unwrap_or_default(),
+            recording_duration_ms: decoded.audio_duration_ms,
+            transcription_duration_ms: duration_ms,
+            audio_device: None,
+            audio_file_name,
+            source_file_name: Some(file_name),
+            trimmed_start_ms: 0,
+            trimmed_end_ms: 0,
+            input_levels: None,
+            system_audio_file_name: None,
+            language: transcript.language.clone(),
+            translated: false,
+            transcript_file_name: crate::history::save_transcript(
+                &state.app_data_dir, &session_id, &transcript,
+            ).ok().flatten(),
+            confidence,
+        };
+        drop(prefs);
+        let _ = crate::history::add_entry(&state.app_data_dir, &entry);
+    }
+
+    eprintln!("[transcribe_file] Done in {}ms", duration_ms);
+    Ok(TranscriptionResult {
+        session_id,
+        text,
+        duration_ms,
+        no_speech: false,
+        language: transcript.language,
+        confidence,
+        held: false,
+    })
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>, CommandError> {
     crate::audio::capture::list_input_devices()
         .map_err(|e| CommandError::new("DeviceEnumeration", e))
 }
+
+/// Record a few seconds from the selected input without creating a recording
+/// session, streaming levels to `on_event`. Doesn't need a transcription model.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn start_mic_test(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    on_event: Channel<AudioEvent>,
+) -> Result<(), CommandError> {
+    validate_can_start_mic_test(&state)?;
+
+    // Silence the previous test's playback so it isn't recorded
+    if let Ok(mut playback) = state.mic_test_playback.lock() {
+        playback.take();
+    }
+
+    let (device_id, channel, source, spectrum) = {
+        let prefs = state
+            .preferences
+            .read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        (
+            prefs.selected_audio_device_id.clone(),
+            prefs.input_channel_for(prefs.selected_audio_device_id.as_deref()),
+            effective_source(&prefs.audio_source),
+            prefs.overlay_visualization_mode() == VisualizationMode::Spectrum,
+        )
+    };
+
+    let amplitude_channel = on_event.clone();
+    let amplitude_callback = Box::new(move |frame: AmplitudeFrame| {
+        let (bands, peaks) = match frame.spectrum {
+            Some(spectrum) => (Some(spectrum.bands), Some(spectrum.peaks)),
+            None => (None, None),
+        };
+        let _ = amplitude_channel.send(AudioEvent::AmplitudeUpdate {
+            amplitudes: frame.amplitudes,
+            rms: frame.rms,
+            bands,
+            peaks,
+        });
+    });
+    let level_channel = on_event.clone();
+    let limit_app = app.clone();
+    let options = CaptureOptions {
+        device_id,
+        channel,
+        on_level_warning: Some(Box::new(move |kind| {
+            let _ = level_channel.send(AudioEvent::LevelWarning { kind });
+        })),
+        limit: Some(DurationLimit {
+            max_secs: MIC_TEST_MAX_SECS,
+            warning_secs: 0,
+            on_warning: Box::new(|_| {}),
+            // The UI calls stop_mic_test, which plays the recording back
+            on_limit: Box::new(move || {
+                let _ = limit_app.emit("mic-test-limit-reached", ());
+            }),
+        }),
+        spectrum,
+        ..Default::default()
+    };
+
+    eprintln!("[start_mic_test] Starting audio capture from {:?}...", source);
+    let capture = start_source(&source, amplitude_callback, options)
+        .map_err(|e| CommandError::new("MicrophoneUnavailable", e))?;
+    let mut mic_test = state
+        .mic_test
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+    *mic_test = Some(capture);
+    Ok(())
+}
+
+/// Stop the microphone test and play its recording through the default output.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn stop_mic_test(state: State<'_, AppState>) -> Result<MicTestResult, CommandError> {
+    let capture = state
+        .mic_test
+        .lock()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .take()
+        .ok_or_else(|| CommandError::new("NoMicTest", "No microphone test is running."))?;
+
+    let device_name = capture.device_name().to_string();
+    let input_levels = capture.input_levels();
+    let (mut buffer, sample_rate) = capture.stop();
+    let audio = buffer
+        .to_vec()
+        .map_err(|e| CommandError::new("AudioProcessing", e))?;
+    let duration_ms = audio.len() as u64 * 1000 / sample_rate.max(1) as u64;
+    eprintln!(
+        "[stop_mic_test] Recorded {}ms from '{}': {:?}",
+        duration_ms, device_name, input_levels
+    );
+
+    let playback_error = match AudioPlayer::play(&audio, sample_rate) {
+        Ok(player) => {
+            let mut playback = state
+                .mic_test_playback
+                .lock()
+                .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+            *playback = Some(player);
+            None
+        }
+        Err(e) => {
+            eprintln!("[stop_mic_test] Playback failed: {}", e);
+            Some(e)
+        }
+    };
+
+    Ok(MicTestResult {
+        device_name,
+        duration_ms,
+        input_levels,
+        playback_error,
+    })
+}
@@ -1,8 +1,13 @@
 use tauri::State;
 
-use crate::history::HistoryEntry;
+use crate::history::{HistoryAudio, HistoryEntry};
+use crate::transcription::engine::Transcript;
+use crate::transcription::file::{decode_file, transcribe_segments};
+use crate::transcription::meeting::MeetingTrack;
+use crate::transcription::worker::JobKind;
 use crate::AppState;
 
+use super::audio_commands::{job_error, postprocess_transcript, transcribe_options};
 use super::CommandError;
 
 #[tauri::command(rename_all = "camelCase")]
@@ -30,7 +35,59 @@ pub fn clear_history(state: State<'_, AppState>) -> Result<(), CommandError> {
 pub fn get_history_audio(
     state: State<'_, AppState>,
     file_name: String,
-) -> Result<Vec<u8>, CommandError> {
+) -> Result<HistoryAudio, CommandError> {
     crate::history::load_audio_bytes(&state.app_data_dir, &file_name)
         .map_err(|e| CommandError::new("AudioLoadError", e))
 }
+
+/// Timed transcript of a history entry, for seeking its recording.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_history_transcript(
+    state: State<'_, AppState>,
+    file_name: String,
+) -> Result<Transcript, CommandError> {
+    crate::history::load_transcript(&state.app_data_dir, &file_name)
+        .map_err(|e| CommandError::new("TranscriptLoadError", e))
+}
+
+/// Transcribe one track of a call recording in history on its own.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn retranscribe_history_track(
+    state: State<'_, AppState>,
+    id: String,
+    track: MeetingTrack,
+) -> Result<String, CommandError> {
+    let entries = crate::history::load_history(&state.app_data_dir)
+        .map_err(|e| CommandError::new("HistoryLoadError", e))?;
+    let entry = entries
+        .iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| CommandError::new("HistoryEntryNotFound", format!("No history entry {}", id)))?;
+    let file_name = match track {
+        MeetingTrack::Microphone => entry.audio_file_name.as_deref(),
+        MeetingTrack::System => entry.system_audio_file_name.as_deref(),
+    }
+    .ok_or_else(|| CommandError::new("AudioLoadError", "No recording saved for this track."))?;
+    let path = crate::history::get_recordings_dir(&state.app_data_dir).join(file_name);
+
+    let resampler_quality = state
+        .preferences
+        .read()
+        .map_err(|e| CommandError::new("LockError", e.to_string()))?
+        .resampler_quality;
+    let options = transcribe_options(&state, entry.translated)?;
+    // Decode before queueing, so dictations don't wait behind it
+    let decoded = tauri::async_runtime::spawn_blocking(move || decode_file(&path, resampler_quality, &mut |_| {}))
+        .await
+        .map_err(|e| CommandError::new("AudioLoadError", e.to_string()))?
+        .map_err(|e| CommandError::new("AudioLoadError", e))?;
+    let transcript = state
+        .transcriber
+        .submit(JobKind::File, &options, move |engine, options| {
+            transcribe_segments(engine, &decoded.audio_16khz, options, &mut |_| {})
+        })
+        .join()
+        .await
+        .map_err(job_error)?;
+    postprocess_transcript(&state, &transcript.text)
+}
@@ -1,7 +1,8 @@
 use tauri::State;
 
-use crate::transcription::engine::{ParakeetEngine, TranscriptionEngine, WhisperEngine};
+use crate::transcription::engine::{ParakeetEngine, TranscriptionEngine, WhisperEngine, CANCELLED};
 use crate::transcription::models;
+use crate::transcription::worker::ModelSwitch;
 use crate::transcription::{DownloadStatus, ModelVariant, TranscriptionModel};
 use crate::AppState;
 
@@ -53,10 +54,13 @@ pub async fn list_models(
     Ok(models::get_builtin_models(&state.app_data_dir))
 }
 
+/// Load a model and make it the active one. The switch waits for
+/// transcriptions already queued unless `preempt` is set, which cancels them.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn set_active_model(
     state: State<'_, AppState>,
     model_id: String,
+    preempt: Option<bool>,
 ) -> Result<(), CommandError> {
     eprintln!("[set_active_model] Activating model: {}", model_id);
 
@@ -70,6 +74,20 @@ pub async fn set_active_model(
         .find(|m| m.id == model_id)
         .ok_or_else(|| CommandError::new("ModelNotFound", format!("No model with id: {}", model_id)))?;
 
+    // The spoken language preference has to carry over to the new model
+    {
+        let prefs = state
+            .preferences
+            .read()
+            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
+        crate::preferences::validate_spoken_language(&prefs.spoken_language, model).map_err(|e| {
+            CommandError::new(
+                "UnsupportedLanguage",
+                format!("{} Change the spoken language before switching to this model.", e),
+            )
+        })?;
+    }
+
     let local_path = match &model.download_status {
         DownloadStatus::Downloaded { local_path } => {
             eprintln!("[set_active_model] Model path: {}", local_path);
@@ -84,24 +102,30 @@ pub async fn set_active_model(
         }
     };
 
-    // Load engine based on model family
+    // Load engine based on model family; it replaces the current one on the
+    // transcription worker once jobs ahead of it are done
     eprintln!("[set_active_model] Loading {} engine...", model.model_family);
-    let engine = load_engine_for_model(model, &local_path)
+    let switch = if preempt.unwrap_or(false) {
+        ModelSwitch::Preempt
+    } else {
+        ModelSwitch::Queue
+    };
+    let model = model.clone();
+    state
+        .transcriber
+        .load(model.clone(), move || load_engine_for_model(&model, &local_path), switch)
+        .join()
+        .await
         .map_err(|e| {
             eprintln!("[set_active_model] Load failed: {}", e);
-            CommandError::new("LoadFailed", e)
+            if e == CANCELLED {
+                CommandError::new("LoadCancelled", "Another model was activated first.")
+            } else {
+                CommandError::new("LoadFailed", e)
+            }
         })?;
     eprintln!("[set_active_model] Engine loaded successfully");
 
-    // Update engine in state
-    {
-        let mut engine_lock = state
-            .engine
-            .write()
-            .map_err(|e| CommandError::new("LockError", e.to_string()))?;
-        *engine_lock = Some(engine);
-    }
-
     // Update preferences and save to disk
     {
         let mut prefs = state
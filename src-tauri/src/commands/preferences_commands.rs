@@ -2,7 +2,7 @@ use tauri::State;
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 
 use crate::preferences::storage;
-use crate::preferences::UserPreferences;
+use crate::preferences::{UserPreferences, WhisperDecodeOptions};
 use crate::AppState;
 
 use super::CommandError;
@@ -41,13 +41,38 @@ pub async fn update_preferences(
         ));
     }
 
-    // Read old hotkeys to diff
-    let old_hotkeys = {
+    // Validate hands-free, warm microphone, input channel, duration, confidence and decoding settings
+    crate::preferences::validate_hands_free_settings(&preferences.hands_free)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_warm_microphone_settings(&preferences.warm_microphone)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_input_channels(&preferences.input_channels)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_max_recording_minutes(preferences.max_recording_minutes)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_audio_source(&preferences.audio_source)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_low_confidence_settings(&preferences.low_confidence)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+    crate::preferences::validate_whisper_decoding(&preferences.whisper_decoding)
+        .map_err(|e| CommandError::new("InvalidPreferences", e))?;
+
+    // The spoken language has to be one the active model transcribes
+    if let Some(model_id) = &preferences.active_model_id {
+        let models = crate::transcription::models::get_builtin_models(&state.app_data_dir);
+        if let Some(model) = models.iter().find(|m| &m.id == model_id) {
+            crate::preferences::validate_spoken_language(&preferences.spoken_language, model)
+                .map_err(|e| CommandError::new("UnsupportedLanguage", e))?;
+        }
+    }
+
+    // Read old hotkeys and history format to diff
+    let (old_hotkeys, old_history_audio_format) = {
         let prefs = state
             .preferences
             .read()
             .map_err(|e| CommandError::new("LockError", format!("Failed to read preferences: {}", e)))?;
-        prefs.hotkeys.clone()
+        (prefs.hotkeys.clone(), prefs.history_audio_format)
     };
 
     // Persist to disk
@@ -76,6 +101,22 @@ pub async fn update_preferences(
         }
     }
 
+    // Convert existing WAV recordings once a compressed format is picked
+    if preferences.history_audio_format != old_history_audio_format
+        && preferences.history_audio_format != crate::preferences::HistoryAudioFormat::Wav
+    {
+        match crate::history::request_wav_migration(&state.app_data_dir) {
+            Ok(()) => crate::history::spawn_wav_migration(
+                state.app_data_dir.clone(),
+                preferences.history_audio_format,
+            ),
+            Err(e) => log::warn!("Failed to start history conversion: {}", e),
+        }
+    }
+
+    // Open, reopen or close the warm microphone to match the new settings
+    crate::commands::audio_commands::sync_warm_microphone(&app, &preferences);
+
     // Update in-memory state
     let mut prefs = state
         .preferences
@@ -85,3 +126,69 @@ pub async fn update_preferences(
 
     Ok(())
 }
+
+/// Persist the vocabulary and make it the one new transcriptions use.
+fn store_vocabulary(state: &AppState, terms: Vec<String>) -> Result<Vec<String>, CommandError> {
+    let terms = crate::preferences::normalize_vocabulary(terms)
+        .map_err(|e| CommandError::new("InvalidVocabulary", e))?;
+    storage::save_vocabulary(&state.app_data_dir, &terms)
+        .map_err(|e| CommandError::new("PersistFailed", e))?;
+    let mut vocabulary = state
+        .vocabulary
+        .write()
+        .map_err(|e| CommandError::new("LockError", format!("Failed to write vocabulary: {}", e)))?;
+    *vocabulary = terms.clone();
+    Ok(terms)
+}
+
+fn current_vocabulary(state: &AppState) -> Result<Vec<String>, CommandError> {
+    let vocabulary = state
+        .vocabulary
+        .read()
+        .map_err(|e| CommandError::new("LockError", format!("Failed to read vocabulary: {}", e)))?;
+    Ok(vocabulary.clone())
+}
+
+#[tauri::command]
+pub async fn get_vocabulary(state: State<'_, AppState>) -> Result<Vec<String>, CommandError> {
+    current_vocabulary(&state)
+}
+
+/// Replace the whole vocabulary. Returns it as stored.
+#[tauri::command]
+pub async fn set_vocabulary(
+    state: State<'_, AppState>,
+    terms: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    store_vocabulary(&state, terms)
+}
+
+#[tauri::command]
+pub async fn add_vocabulary_term(
+    state: State<'_, AppState>,
+    term: String,
+) -> Result<Vec<String>, CommandError> {
+    let mut terms = current_vocabulary(&state)?;
+    terms.push(term);
+    store_vocabulary(&state, terms)
+}
+
+#[tauri::command]
+pub async fn remove_vocabulary_term(
+    state: State<'_, AppState>,
+    term: String,
+) -> Result<Vec<String>, CommandError> {
+    let terms = current_vocabulary(&state)?
+        .into_iter()
+        .filter(|t| !t.eq_ignore_ascii_case(term.trim()))
+        .collect();
+    store_vocabulary(&state, terms)
+}
+
+/// Decoding options Whisper uses for the loaded model under the current setting.
+#[tauri::command]
+pub async fn get_whisper_decode_options(
+    state: State<'_, AppState>,
+) -> Result<WhisperDecodeOptions, CommandError> {
+    Ok(super::audio_commands::transcribe_options(&state, false)?.whisper)
+}
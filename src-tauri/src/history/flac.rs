@@ -0,0 +1,382 @@
+//! Minimal FLAC encoder for mono 16-bit history recordings.
+//!
+//! Each block uses whichever of the constant or fixed-predictor subframes is
+//! smallest, with partitioned Rice coding of the residual. That gets most of
+//! the size benefit of a full encoder for speech at a fraction of the code.
+
+/// Samples per frame; the FLAC reference encoder's default.
+const BLOCK_SIZE: usize = 4096;
+/// Smallest block size the format allows, except for a stream's last frame.
+const MIN_BLOCK_SIZE: usize = 16;
+const BITS_PER_SAMPLE: u32 = 16;
+/// Highest fixed predictor order the format defines.
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+/// Rice parameters are 4 bits; 15 is reserved as an escape code.
+const MAX_RICE_PARAMETER: u32 = 14;
+
+/// Encode mono audio as a 16-bit FLAC file.
+pub fn encode_flac(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let pcm: Vec<i32> = samples
+        .iter()
+        .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i32)
+        .collect();
+
+    // Short recordings still get split in two; some demuxers (symphonia among
+    // them) need to see a second frame before they will read the first
+    let block_size = BLOCK_SIZE.min(pcm.len().div_ceil(2).max(MIN_BLOCK_SIZE));
+
+    let mut out = BitWriter::default();
+    out.write_bytes(b"fLaC");
+    write_stream_info(&mut out, sample_rate, block_size, pcm.len() as u64);
+    for (index, block) in pcm.chunks(block_size).enumerate() {
+        write_frame(&mut out, index as u64, block);
+    }
+    out.into_bytes()
+}
+
+fn write_stream_info(out: &mut BitWriter, sample_rate: u32, block_size: usize, total_samples: u64) {
+    // Last metadata block, type 0 (STREAMINFO), 34 bytes long
+    out.write(1, 1);
+    out.write(0, 7);
+    out.write(34, 24);
+
+    out.write(block_size as u64, 16);
+    out.write(block_size as u64, 16);
+    // Frame size bounds unknown
+    out.write(0, 24);
+    out.write(0, 24);
+    out.write(sample_rate as u64, 20);
+    out.write(0, 3); // channels - 1
+    out.write((BITS_PER_SAMPLE - 1) as u64, 5);
+    out.write(total_samples, 36);
+    // MD5 of the audio; all zero means not computed
+    out.write_bytes(&[0; 16]);
+}
+
+fn write_frame(out: &mut BitWriter, frame_number: u64, block: &[i32]) {
+    let start = out.bytes.len();
+
+    out.write(0b11_1111_1111_1110, 14); // sync code
+    out.write(0, 1);
+    out.write(0, 1); // fixed block size
+    out.write(0b0111, 4); // block size as 16 bits after the header
+    out.write(0, 4); // sample rate from STREAMINFO
+    out.write(0, 4); // mono
+    out.write(0b100, 3); // 16 bits per sample
+    out.write(0, 1);
+    write_utf8_number(out, frame_number);
+    out.write(block.len() as u64 - 1, 16);
+    let crc = crc8(&out.bytes[start..]);
+    out.write(crc as u64, 8);
+
+    write_subframe(out, block);
+
+    out.align();
+    let crc = crc16(&out.bytes[start..]);
+    out.write(crc as u64, 16);
+}
+
+/// Frame numbers use the same variable-length coding as UTF-8.
+fn write_utf8_number(out: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        out.write(value, 8);
+        return;
+    }
+    let bits = 64 - value.leading_zeros();
+    // Each continuation byte holds 6 bits; the lead byte holds the rest
+    let mut extra = 1;
+    while bits > 6 * extra + (6 - extra) {
+        extra += 1;
+    }
+    let lead_marker = (0xFF00u64 >> (extra + 1)) & 0xFF;
+    out.write(lead_marker | (value >> (6 * extra)), 8);
+    for i in (0..extra).rev() {
+        out.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+fn write_subframe(out: &mut BitWriter, block: &[i32]) {
+    if block.iter().all(|&s| s == block[0]) {
+        out.write(0, 1);
+        out.write(0b000000, 6); // CONSTANT
+        out.write(0, 1);
+        out.write_signed(block[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let max_order = MAX_FIXED_ORDER.min(block.len() - 1);
+    let (order, residual, coding) = (0..=max_order)
+        .map(|order| {
+            let residual = fixed_residual(block, order);
+            let coding = plan_rice_coding(&residual, block.len(), order);
+            (order, residual, coding)
+        })
+        .min_by_key(|(order, _, coding)| *order as u64 * BITS_PER_SAMPLE as u64 + coding.bits)
+        .expect("at least one predictor order");
+
+    out.write(0, 1);
+    out.write(0b001000 | order as u64, 6); // FIXED
+    out.write(0, 1);
+    for &sample in &block[..order] {
+        out.write_signed(sample, BITS_PER_SAMPLE);
+    }
+
+    out.write(0, 2); // 4-bit Rice parameters
+    out.write(coding.partition_order as u64, 4);
+    let partition_len = block.len() >> coding.partition_order;
+    let mut offset = 0;
+    for (index, &parameter) in coding.parameters.iter().enumerate() {
+        let len = if index == 0 { partition_len - order } else { partition_len };
+        out.write(parameter as u64, 4);
+        for &r in &residual[offset..offset + len] {
+            out.write_rice(zigzag(r), parameter);
+        }
+        offset += len;
+    }
+}
+
+/// Prediction error of the fixed polynomial predictor of `order`, for every
+/// sample after the warm-up samples.
+fn fixed_residual(block: &[i32], order: usize) -> Vec<i32> {
+    (order..block.len())
+        .map(|i| {
+            let s = |back: usize| block[i - back];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+struct RiceCoding {
+    partition_order: u32,
+    parameters: Vec<u32>,
+    /// Size of the coded residual, including partition headers.
+    bits: u64,
+}
+
+/// Pick the partition order and per-partition Rice parameters that code
+/// `residual` in the fewest bits.
+fn plan_rice_coding(residual: &[i32], block_len: usize, order: usize) -> RiceCoding {
+    let values: Vec<u32> = residual.iter().map(|&r| zigzag(r)).collect();
+    let mut best: Option<RiceCoding> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        // Partitions must divide the block evenly and the first must outlast the warm-up
+        if !block_len.is_multiple_of(partitions) || block_len / partitions <= order {
+            break;
+        }
+        let partition_len = block_len / partitions;
+        let mut parameters = Vec::with_capacity(partitions);
+        let mut bits = 6u64;
+        let mut offset = 0;
+        for index in 0..partitions {
+            let len = if index == 0 { partition_len - order } else { partition_len };
+            let (parameter, cost) = best_rice_parameter(&values[offset..offset + len]);
+            parameters.push(parameter);
+            bits += 4 + cost;
+            offset += len;
+        }
+        if best.as_ref().is_none_or(|b| bits < b.bits) {
+            best = Some(RiceCoding {
+                partition_order,
+                parameters,
+                bits,
+            });
+        }
+    }
+    best.expect("partition order 0 always fits")
+}
+
+/// Rice parameter for `values` and the bits it takes to code them with it.
+fn best_rice_parameter(values: &[u32]) -> (u32, u64) {
+    let cost = |k: u32| -> u64 {
+        values
+            .iter()
+            .map(|&v| (v >> k) as u64 + 1 + k as u64)
+            .sum()
+    };
+    // The optimum sits near log2 of the mean; check its neighbours
+    let mean = values.iter().map(|&v| v as u64).sum::<u64>() / values.len().max(1) as u64;
+    let estimate = (64 - mean.leading_zeros()).min(MAX_RICE_PARAMETER);
+    (estimate.saturating_sub(1)..=(estimate + 1).min(MAX_RICE_PARAMETER))
+        .map(|k| (k, cost(k)))
+        .min_by_key(|&(_, bits)| bits)
+        .expect("non-empty parameter range")
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// CRC-8 with polynomial x^8 + x^2 + x + 1, as used for frame headers.
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// CRC-16 with polynomial x^16 + x^15 + x^2 + 1, as used for whole frames.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// MSB-first bit writer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits not yet flushed to `bytes`, right-aligned.
+    pending: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    /// Write the low `bits` bits of `value`; at most 36 bits per call.
+    fn write(&mut self, value: u64, bits: u32) {
+        debug_assert!(bits <= 36);
+        if bits == 0 {
+            return;
+        }
+        self.pending = (self.pending << bits) | (value & ((1u64 << bits) - 1));
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes.push((self.pending >> self.pending_bits) as u8);
+        }
+        self.pending &= (1u64 << self.pending_bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u32 as u64, bits);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write(byte as u64, 8);
+        }
+    }
+
+    /// Unary-coded quotient followed by the low `parameter` bits.
+    fn write_rice(&mut self, value: u32, parameter: u32) {
+        let mut quotient = value >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        self.write(value as u64, parameter);
+    }
+
+    /// Pad with zero bits to the next byte boundary.
+    fn align(&mut self) {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8], name: &str) -> crate::audio::decode::DecodedAudio {
+        let dir = std::env::temp_dir().join("ttc_test_flac");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        crate::audio::decode::decode_audio_file(&path, &mut |_| {}).unwrap()
+    }
+
+    fn quantize(samples: &[f32]) -> Vec<f32> {
+        samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16 as f32 / 32768.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip_is_lossless() {
+        // Tone, silence and noise across several blocks, with a short final block
+        let mut samples: Vec<f32> = (0..10000)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / 16000.0).sin())
+            .collect();
+        samples.extend(std::iter::repeat_n(0.0, 5000));
+        let mut seed = 12345u32;
+        samples.extend((0..3000).map(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as f32 / 65536.0 - 0.5
+        }));
+
+        let bytes = encode_flac(&samples, 16000);
+        let decoded = decode(&bytes, "roundtrip.flac");
+
+        assert_eq!(decoded.sample_rate, 16000);
+        assert_eq!(decoded.samples, quantize(&samples));
+    }
+
+    #[test]
+    fn test_speech_like_audio_compresses() {
+        let samples: Vec<f32> = (0..16000 * 5)
+            .map(|i| {
+                let t = i as f32 / 16000.0;
+                0.3 * (2.0 * std::f32::consts::PI * 180.0 * t).sin()
+                    * (2.0 * std::f32::consts::PI * 3.0 * t).sin().abs()
+            })
+            .collect();
+        let bytes = encode_flac(&samples, 16000);
+        // 16-bit WAV would take two bytes per sample
+        assert!(bytes.len() < samples.len(), "{} bytes", bytes.len());
+    }
+
+    #[test]
+    fn test_utf8_frame_numbers() {
+        let encode = |n: u64| {
+            let mut out = BitWriter::default();
+            write_utf8_number(&mut out, n);
+            out.into_bytes()
+        };
+        assert_eq!(encode(0x41), vec![0x41]);
+        assert_eq!(encode(0x80), vec![0xC2, 0x80]);
+        assert_eq!(encode(0x7FF), vec![0xDF, 0xBF]);
+        assert_eq!(encode(0x800), vec![0xE0, 0xA0, 0x80]);
+        assert_eq!(encode(0x10000), vec![0xF0, 0x90, 0x80, 0x80]);
+    }
+
+    #[test]
+    fn test_short_recordings() {
+        for len in [17, 100, 4097] {
+            let samples: Vec<f32> = (0..len).map(|i| (i % 100) as f32 / 100.0).collect();
+            let decoded = decode(&encode_flac(&samples, 16000), &format!("short{}.flac", len));
+            assert_eq!(decoded.samples, quantize(&samples));
+        }
+    }
+
+    #[test]
+    fn test_short_final_block() {
+        for extra in [1, 3, 17, 4096] {
+            let samples: Vec<f32> = (0..BLOCK_SIZE + extra).map(|i| (i % 100) as f32 / 100.0).collect();
+            let decoded = decode(&encode_flac(&samples, 16000), &format!("tail{}.flac", extra));
+            assert_eq!(decoded.samples, quantize(&samples));
+        }
+    }
+}
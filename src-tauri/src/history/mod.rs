@@ -1,8 +1,16 @@
+pub mod flac;
+pub mod opus;
+
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::audio::level::InputLevelMetrics;
+use crate::preferences::HistoryAudioFormat;
+use crate::transcription::engine::Transcript;
+
 const MAX_ENTRIES: usize = 5000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +25,36 @@ pub struct HistoryEntry {
     pub audio_device: Option<String>,
     #[serde(default)]
     pub audio_file_name: Option<String>,
+    /// Name of the imported file, for entries created by `transcribe_file`.
+    #[serde(default)]
+    pub source_file_name: Option<String>,
+    /// Silence cut from the start of the recording before transcription.
+    #[serde(default)]
+    pub trimmed_start_ms: u64,
+    /// Silence cut from the end of the recording before transcription.
+    #[serde(default)]
+    pub trimmed_end_ms: u64,
+    /// Input levels while recording; `None` for imported files.
+    #[serde(default)]
+    pub input_levels: Option<InputLevelMetrics>,
+    /// System audio track of a call recording; `audio_file_name` holds the
+    /// microphone track. Both cover the whole session, untrimmed.
+    #[serde(default)]
+    pub system_audio_file_name: Option<String>,
+    /// Language the speech was transcribed as, when the engine reports it.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The speech was translated into English rather than transcribed as spoken.
+    #[serde(default)]
+    pub translated: bool,
+    /// Timed transcript saved next to the recording, timed from the start of
+    /// the saved audio. Its segments hold the engine output, before
+    /// post-processing.
+    #[serde(default)]
+    pub transcript_file_name: Option<String>,
+    /// Mean word probability of the transcript, when the engine reports it.
+    #[serde(default)]
+    pub confidence: Option<f32>,
 }
 
 pub fn get_history_path(app_data_dir: &Path) -> PathBuf {
@@ -27,19 +65,32 @@ pub fn get_recordings_dir(app_data_dir: &Path) -> PathBuf {
     app_data_dir.join("recordings")
 }
 
-pub fn save_audio_wav(
+/// Save a recording for history playback in `format`. Returns the file name
+/// within the recordings directory.
+pub fn save_audio(
     app_data_dir: &Path,
     id: &str,
     samples: &[f32],
     sample_rate: u32,
+    format: HistoryAudioFormat,
 ) -> Result<String, String> {
     let dir = get_recordings_dir(app_data_dir);
     fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
 
-    let file_name = format!("{}.wav", id);
-    let path = dir.join(&file_name);
+    let (extension, bytes) = match format {
+        HistoryAudioFormat::Wav => ("wav", encode_wav(samples, sample_rate)?),
+        HistoryAudioFormat::Flac => ("flac", flac::encode_flac(samples, sample_rate)),
+        HistoryAudioFormat::Opus => ("opus", opus::encode_ogg_opus(samples, sample_rate)?),
+    };
+    let file_name = format!("{}.{}", id, extension);
+    fs::write(dir.join(&file_name), bytes)
+        .map_err(|e| format!("Failed to write audio file: {}", e))?;
+
+    Ok(file_name)
+}
 
+fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
     let spec = hound::WavSpec {
         channels: 1,
         sample_rate,
@@ -47,7 +98,8 @@ pub fn save_audio_wav(
         sample_format: hound::SampleFormat::Int,
     };
 
-    let mut writer = hound::WavWriter::create(&path, spec)
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut bytes, spec)
         .map_err(|e| format!("Failed to create WAV file: {}", e))?;
 
     for &sample in samples {
@@ -62,12 +114,165 @@ pub fn save_audio_wav(
         .finalize()
         .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
 
-    Ok(file_name)
+    Ok(bytes.into_inner())
 }
 
-pub fn load_audio_bytes(app_data_dir: &Path, file_name: &str) -> Result<Vec<u8>, String> {
+/// Save the timed transcript of an entry in the recordings directory.
+/// Returns the file name, or `None` when the transcript has no timing.
+pub fn save_transcript(app_data_dir: &Path, id: &str, transcript: &Transcript) -> Result<Option<String>, String> {
+    if transcript.segments.is_empty() {
+        return Ok(None);
+    }
+    let dir = get_recordings_dir(app_data_dir);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+    let contents = serde_json::to_string(transcript)
+        .map_err(|e| format!("Failed to serialize transcript: {}", e))?;
+    let file_name = format!("{}.transcript.json", id);
+    fs::write(dir.join(&file_name), contents)
+        .map_err(|e| format!("Failed to write transcript file: {}", e))?;
+
+    Ok(Some(file_name))
+}
+
+pub fn load_transcript(app_data_dir: &Path, file_name: &str) -> Result<Transcript, String> {
     let path = get_recordings_dir(app_data_dir).join(file_name);
-    fs::read(&path).map_err(|e| format!("Failed to read audio file: {}", e))
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read transcript file: {}", e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse transcript: {}", e))
+}
+
+/// A history recording and the MIME type to play it back with.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryAudio {
+    pub bytes: Vec<u8>,
+    pub mime_type: String,
+}
+
+/// MIME type of a saved recording, from its file extension.
+pub fn audio_mime_type(file_name: &str) -> &'static str {
+    match Path::new(file_name).extension().and_then(|e| e.to_str()) {
+        Some("flac") => "audio/flac",
+        Some("opus") => "audio/ogg; codecs=opus",
+        _ => "audio/wav",
+    }
+}
+
+pub fn load_audio_bytes(app_data_dir: &Path, file_name: &str) -> Result<HistoryAudio, String> {
+    let path = get_recordings_dir(app_data_dir).join(file_name);
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read audio file: {}", e))?;
+    Ok(HistoryAudio {
+        bytes,
+        mime_type: audio_mime_type(file_name).to_string(),
+    })
+}
+
+/// Marks WAV recordings as waiting to be re-encoded; removed once they are.
+const WAV_MIGRATION_MARKER: &str = ".wav-migration-pending";
+
+/// Ask for WAV recordings to be re-encoded by the next `migrate_wav_recordings`.
+/// The request survives a restart until a migration runs to completion.
+pub fn request_wav_migration(app_data_dir: &Path) -> Result<(), String> {
+    let dir = get_recordings_dir(app_data_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    fs::write(dir.join(WAV_MIGRATION_MARKER), b"")
+        .map_err(|e| format!("Failed to record pending migration: {}", e))
+}
+
+pub fn wav_migration_pending(app_data_dir: &Path) -> bool {
+    get_recordings_dir(app_data_dir).join(WAV_MIGRATION_MARKER).exists()
+}
+
+/// Run a requested migration to `format` on a background thread; converting a
+/// long history may take a while.
+pub fn spawn_wav_migration(app_data_dir: PathBuf, format: HistoryAudioFormat) {
+    if format == HistoryAudioFormat::Wav || !wav_migration_pending(&app_data_dir) {
+        return;
+    }
+    std::thread::spawn(move || match migrate_wav_recordings(&app_data_dir, format) {
+        Ok(0) => {}
+        Ok(count) => log::info!("Converted {} history recordings to {:?}", count, format),
+        Err(e) => log::warn!("Failed to convert history recordings: {}", e),
+    });
+}
+
+/// Re-encode WAV recordings in `format`, if a migration was requested.
+///
+/// History is updated before the WAV files are removed, so an interrupted
+/// migration leaves every entry pointing at a playable file. Entries that
+/// fail to convert keep their WAV. Returns how many recordings were converted.
+pub fn migrate_wav_recordings(app_data_dir: &Path, format: HistoryAudioFormat) -> Result<usize, String> {
+    if format == HistoryAudioFormat::Wav || !wav_migration_pending(app_data_dir) {
+        return Ok(0);
+    }
+    let converted = convert_wav_recordings(app_data_dir, format)?;
+    let _ = fs::remove_file(get_recordings_dir(app_data_dir).join(WAV_MIGRATION_MARKER));
+    Ok(converted)
+}
+
+fn convert_wav_recordings(app_data_dir: &Path, format: HistoryAudioFormat) -> Result<usize, String> {
+    let dir = get_recordings_dir(app_data_dir);
+    let entries = load_history(app_data_dir)?;
+
+    // New file name by WAV file name, for the microphone and system audio tracks
+    let mut converted = HashMap::new();
+    let tracks = entries
+        .iter()
+        .flat_map(|entry| [&entry.audio_file_name, &entry.system_audio_file_name])
+        .flatten();
+    for wav_name in tracks {
+        if audio_mime_type(wav_name) != "audio/wav" {
+            continue;
+        }
+        let Some(id) = Path::new(wav_name).file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        match read_wav(&dir.join(wav_name))
+            .and_then(|(samples, rate)| save_audio(app_data_dir, id, &samples, rate, format))
+        {
+            Ok(new_name) => {
+                converted.insert(wav_name.clone(), new_name);
+            }
+            Err(e) => log::warn!("Failed to convert recording {}: {}", wav_name, e),
+        }
+    }
+    if converted.is_empty() {
+        return Ok(0);
+    }
+
+    // Reload so entries added while converting aren't lost
+    let mut entries = load_history(app_data_dir)?;
+    for entry in &mut entries {
+        for track in [&mut entry.audio_file_name, &mut entry.system_audio_file_name] {
+            if let Some(new_name) = track.as_ref().and_then(|name| converted.get(name)) {
+                *track = Some(new_name.clone());
+            }
+        }
+    }
+    save_history(app_data_dir, &entries)?;
+
+    for wav_name in converted.keys() {
+        delete_audio_file(app_data_dir, wav_name);
+    }
+    Ok(converted.len())
+}
+
+fn read_wav(path: &Path) -> Result<(Vec<f32>, u32), String> {
+    let mut reader =
+        hound::WavReader::open(path).map_err(|e| format!("Failed to open WAV file: {}", e))?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return Err("Unexpected WAV format.".to_string());
+    }
+    let samples = reader
+        .samples::<i16>()
+        .map(|s| s.map(|s| s as f32 / i16::MAX as f32))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read WAV file: {}", e))?;
+    Ok((samples, spec.sample_rate))
 }
 
 pub fn delete_audio_file(app_data_dir: &Path, file_name: &str) {
@@ -119,9 +324,14 @@ pub fn add_entry(app_data_dir: &Path, entry: &HistoryEntry) -> Result<(), String
 
 pub fn delete_entry(app_data_dir: &Path, id: &str) -> Result<(), String> {
     let entries = load_history(app_data_dir)?;
-    // Delete audio file if present
+    // Delete audio and transcript files if present
     if let Some(entry) = entries.iter().find(|e| e.id == id) {
-        if let Some(ref file_name) = entry.audio_file_name {
+        for file_name in entry
+            .audio_file_name
+            .iter()
+            .chain(&entry.system_audio_file_name)
+            .chain(&entry.transcript_file_name)
+        {
             delete_audio_file(app_data_dir, file_name);
         }
     }
@@ -137,3 +347,129 @@ pub fn clear_history(app_data_dir: &Path) -> Result<(), String> {
     }
     save_history(app_data_dir, &[])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, audio_file_name: Option<&str>) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            text: "hello".to_string(),
+            model_id: "test".to_string(),
+            recording_duration_ms: 1000,
+            transcription_duration_ms: 100,
+            audio_device: None,
+            audio_file_name: audio_file_name.map(str::to_string),
+            source_file_name: None,
+            trimmed_start_ms: 0,
+            trimmed_end_ms: 0,
+            input_levels: None,
+            system_audio_file_name: None,
+            language: None,
+            translated: false,
+            transcript_file_name: None,
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn test_migrate_wav_recordings_to_flac() {
+        let dir = std::env::temp_dir().join("ttc_test_history_migration");
+        let _ = fs::remove_dir_all(&dir);
+        let samples: Vec<f32> = (0..8000).map(|i| ((i % 50) as f32 - 25.0) / 100.0).collect();
+        let wav = save_audio(&dir, "a", &samples, 16000, HistoryAudioFormat::Wav).unwrap();
+        let call_mic = save_audio(&dir, "call", &samples, 16000, HistoryAudioFormat::Wav).unwrap();
+        let call_system = save_audio(&dir, "call-system", &samples, 16000, HistoryAudioFormat::Wav).unwrap();
+        let mut call = entry("call", Some(&call_mic));
+        call.system_audio_file_name = Some(call_system.clone());
+        save_history(&dir, &[entry("a", Some(&wav)), entry("b", None), call]).unwrap();
+
+        // Nothing happens until a compressed format is picked
+        assert_eq!(migrate_wav_recordings(&dir, HistoryAudioFormat::Flac).unwrap(), 0);
+        request_wav_migration(&dir).unwrap();
+        assert_eq!(migrate_wav_recordings(&dir, HistoryAudioFormat::Flac).unwrap(), 3);
+        assert!(!wav_migration_pending(&dir));
+
+        let entries = load_history(&dir).unwrap();
+        assert_eq!(entries[0].audio_file_name.as_deref(), Some("a.flac"));
+        assert_eq!(entries[1].audio_file_name, None);
+        assert_eq!(entries[2].audio_file_name.as_deref(), Some("call.flac"));
+        assert_eq!(entries[2].system_audio_file_name.as_deref(), Some("call-system.flac"));
+        for old in [&wav, &call_mic, &call_system] {
+            assert!(!get_recordings_dir(&dir).join(old).exists());
+        }
+        let audio = load_audio_bytes(&dir, "a.flac").unwrap();
+        assert_eq!(audio.mime_type, "audio/flac");
+        assert_eq!(&audio.bytes[..4], b"fLaC");
+
+        // Nothing left to convert the second time
+        request_wav_migration(&dir).unwrap();
+        assert_eq!(migrate_wav_recordings(&dir, HistoryAudioFormat::Flac).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_delete_entry_removes_both_tracks() {
+        let dir = std::env::temp_dir().join("ttc_test_history_two_tracks");
+        let _ = fs::remove_dir_all(&dir);
+        let samples = vec![0.1; 1600];
+        let mic = save_audio(&dir, "call", &samples, 16000, HistoryAudioFormat::Flac).unwrap();
+        let system = save_audio(&dir, "call-system", &samples, 16000, HistoryAudioFormat::Flac).unwrap();
+        let mut call = entry("call", Some(&mic));
+        call.system_audio_file_name = Some(system.clone());
+        save_history(&dir, &[call]).unwrap();
+
+        delete_entry(&dir, "call").unwrap();
+
+        assert!(load_history(&dir).unwrap().is_empty());
+        assert!(!get_recordings_dir(&dir).join(&mic).exists());
+        assert!(!get_recordings_dir(&dir).join(&system).exists());
+    }
+
+    #[test]
+    fn test_transcript_roundtrip_and_delete() {
+        use crate::transcription::engine::{TimedWord, TranscriptSegment};
+
+        let dir = std::env::temp_dir().join("ttc_test_history_transcript");
+        let _ = fs::remove_dir_all(&dir);
+        let transcript = Transcript {
+            text: "Hello world.".to_string(),
+            language: Some("en".to_string()),
+            segments: vec![TranscriptSegment {
+                text: "Hello world.".to_string(),
+                start_ms: 120,
+                end_ms: 980,
+                words: vec![
+                    TimedWord {
+                        text: "Hello".to_string(),
+                        start_ms: 120,
+                        end_ms: 480,
+                        probability: Some(0.9),
+                    },
+                    TimedWord {
+                        text: "world.".to_string(),
+                        start_ms: 520,
+                        end_ms: 980,
+                        probability: None,
+                    },
+                ],
+            }],
+        };
+        let file_name = save_transcript(&dir, "a", &transcript).unwrap().unwrap();
+        assert_eq!(load_transcript(&dir, &file_name).unwrap(), transcript);
+
+        // Untimed transcripts aren't saved
+        let untimed = Transcript {
+            text: "Hi".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(save_transcript(&dir, "b", &untimed).unwrap(), None);
+
+        let mut a = entry("a", None);
+        a.transcript_file_name = Some(file_name.clone());
+        save_history(&dir, &[a]).unwrap();
+        delete_entry(&dir, "a").unwrap();
+        assert!(!get_recordings_dir(&dir).join(&file_name).exists());
+    }
+}
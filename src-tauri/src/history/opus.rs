@@ -0,0 +1,203 @@
+//! Ogg Opus encoding and decoding for history recordings.
+
+use std::io::Cursor;
+
+use audiopus::coder::{Decoder, Encoder};
+use audiopus::packet::Packet;
+use audiopus::{Application, Bitrate, Channels, MutSignals, SampleRate};
+use ogg::reading::{OggReadError, PacketReader};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+/// Plenty for intelligible mono speech, about a twentieth of 16-bit WAV at 16kHz.
+const BITRATE: i32 = 24_000;
+const FRAME_MS: usize = 20;
+/// Granule positions in Ogg Opus always count 48kHz samples.
+const GRANULE_RATE: u64 = 48_000;
+/// Largest packet libopus produces, per the Opus spec.
+const MAX_PACKET_BYTES: usize = 1275;
+/// Longest audio a single Opus packet can hold.
+const MAX_PACKET_MS: usize = 120;
+const STREAM_SERIAL: u32 = 1;
+
+/// The Opus sample rate matching `sample_rate`, if Opus supports it natively.
+fn opus_rate(sample_rate: u32) -> Option<SampleRate> {
+    match sample_rate {
+        8000 => Some(SampleRate::Hz8000),
+        12000 => Some(SampleRate::Hz12000),
+        16000 => Some(SampleRate::Hz16000),
+        24000 => Some(SampleRate::Hz24000),
+        48000 => Some(SampleRate::Hz48000),
+        _ => None,
+    }
+}
+
+/// Encode mono audio as an Ogg Opus file. `sample_rate` must be one Opus
+/// supports natively: 8, 12, 16, 24 or 48 kHz.
+pub fn encode_ogg_opus(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let opus_rate =
+        opus_rate(sample_rate).ok_or_else(|| format!("Opus does not support {} Hz audio.", sample_rate))?;
+    let opus_err = |e: audiopus::Error| format!("Opus encoding failed: {}", e);
+    let mut encoder = Encoder::new(opus_rate, Channels::Mono, Application::Voip).map_err(opus_err)?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(BITRATE))
+        .map_err(opus_err)?;
+
+    let scale = GRANULE_RATE / sample_rate as u64;
+    let lookahead = encoder.lookahead().map_err(opus_err)? as usize;
+    let pre_skip = lookahead as u64 * scale;
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let write_err = |e: std::io::Error| format!("Failed to write Ogg page: {}", e);
+    writer
+        .write_packet(
+            opus_head(pre_skip as u16, sample_rate),
+            STREAM_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+    writer
+        .write_packet(opus_tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_err)?;
+
+    // Feed the lookahead's worth of extra silence so the last samples come out
+    let frame_len = sample_rate as usize * FRAME_MS / 1000;
+    let padded_len = (samples.len() + lookahead).div_ceil(frame_len).max(1) * frame_len;
+    let mut frame = vec![0.0f32; frame_len];
+    let mut packet = vec![0u8; MAX_PACKET_BYTES];
+    for start in (0..padded_len).step_by(frame_len) {
+        let end = (start + frame_len).min(samples.len());
+        let available = end.saturating_sub(start);
+        frame[..available].copy_from_slice(&samples[start..start + available]);
+        frame[available..].fill(0.0);
+
+        let len = encoder.encode_float(&frame, &mut packet).map_err(opus_err)?;
+        let is_last = start + frame_len >= padded_len;
+        // The final granule position marks where the real audio ends
+        let decoded = ((start + frame_len).min(samples.len() + lookahead)) as u64;
+        writer
+            .write_packet(
+                packet[..len].to_vec().into_boxed_slice(),
+                STREAM_SERIAL,
+                if is_last {
+                    PacketWriteEndInfo::EndStream
+                } else {
+                    PacketWriteEndInfo::NormalPacket
+                },
+                decoded * scale,
+            )
+            .map_err(write_err)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// Decode a mono Ogg Opus file, as written by [`encode_ogg_opus`]. Returns
+/// the samples at the rate the header says the audio was recorded at, or at
+/// 48kHz when Opus can't decode to that rate.
+pub fn decode_ogg_opus(bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    let read_err = |e: OggReadError| format!("Failed to read Ogg page: {}", e);
+    let mut reader = PacketReader::new(Cursor::new(bytes));
+    let head = reader
+        .read_packet()
+        .map_err(read_err)?
+        .ok_or_else(|| "Ogg file is empty.".to_string())?;
+    let head = &head.data;
+    if head.len() < 19 || &head[..8] != b"OpusHead" {
+        return Err("Not an Ogg Opus file.".to_string());
+    }
+    if head[9] != 1 {
+        return Err("Only mono Opus recordings can be decoded.".to_string());
+    }
+    let pre_skip = u16::from_le_bytes([head[10], head[11]]) as u64;
+    let input_rate = u32::from_le_bytes([head[12], head[13], head[14], head[15]]);
+    let (sample_rate, rate) = match opus_rate(input_rate) {
+        Some(rate) => (input_rate, rate),
+        None => (48000, SampleRate::Hz48000),
+    };
+    let scale = GRANULE_RATE / sample_rate as u64;
+    reader
+        .read_packet()
+        .map_err(read_err)?
+        .ok_or_else(|| "Ogg Opus file has no comment header.".to_string())?;
+
+    let opus_err = |e: audiopus::Error| format!("Opus decoding failed: {}", e);
+    let mut decoder = Decoder::new(rate, Channels::Mono).map_err(opus_err)?;
+    let mut frame = vec![0.0f32; sample_rate as usize * MAX_PACKET_MS / 1000];
+    let mut samples = Vec::new();
+    let mut end = None;
+    while let Some(packet) = reader.read_packet().map_err(read_err)? {
+        if packet.data.is_empty() {
+            continue;
+        }
+        let data = Packet::try_from(&packet.data[..]).map_err(opus_err)?;
+        let signals = MutSignals::try_from(&mut frame[..]).map_err(opus_err)?;
+        let len = decoder.decode_float(Some(data), signals, false).map_err(opus_err)?;
+        samples.extend_from_slice(&frame[..len]);
+        end = Some(packet.absgp_page());
+    }
+
+    // The final granule position marks where the real audio ends
+    if let Some(end) = end {
+        samples.truncate((end / scale) as usize);
+    }
+    let pre_skip = ((pre_skip / scale) as usize).min(samples.len());
+    Ok((samples.split_off(pre_skip), sample_rate))
+}
+
+/// Identification header; see RFC 7845 section 5.1.
+fn opus_head(pre_skip: u16, input_sample_rate: u32) -> Box<[u8]> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mono/stereo channel mapping
+    head.into_boxed_slice()
+}
+
+/// Comment header with a vendor string and no tags; see RFC 7845 section 5.2.
+fn opus_tags() -> Box<[u8]> {
+    let vendor = concat!("TalkyTexty ", env!("CARGO_PKG_VERSION"));
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<f32> {
+        let (samples, sample_rate) = decode_ogg_opus(bytes).unwrap();
+        assert_eq!(sample_rate, 16000);
+        samples
+    }
+
+    #[test]
+    fn test_roundtrip_keeps_length_and_level() {
+        let samples: Vec<f32> = (0..16000 + 123)
+            .map(|i| 0.4 * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / 16000.0).sin())
+            .collect();
+
+        let bytes = encode_ogg_opus(&samples, 16000).unwrap();
+        let decoded = decode(&bytes);
+
+        assert_eq!(decoded.len(), samples.len());
+        let rms = |s: &[f32]| (s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32).sqrt();
+        let (original, roundtrip) = (rms(&samples[1600..]), rms(&decoded[1600..]));
+        assert!((original - roundtrip).abs() < 0.05, "{} vs {}", original, roundtrip);
+        // Far smaller than 16-bit PCM
+        assert!(bytes.len() < samples.len() / 4, "{} bytes", bytes.len());
+    }
+
+    #[test]
+    fn test_rejects_unsupported_rate() {
+        assert!(encode_ogg_opus(&[0.0; 441], 44100).is_err());
+    }
+}
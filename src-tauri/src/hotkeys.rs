@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 use crate::preferences::{HotkeyAction, RecordingMode};
 
 /// Represents a hotkey event with its press/release state.
@@ -18,6 +20,14 @@ pub enum HotkeyResponse {
     NoOp,
 }
 
+/// Payload of the `hotkey-start-recording` event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartRecordingRequest {
+    /// The hotkey that started the recording translates into English.
+    pub translate: bool,
+}
+
 /// Resolve a hotkey event into an application response.
 ///
 /// This pure function encapsulates the hotkey routing logic,
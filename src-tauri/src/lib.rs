@@ -7,10 +7,14 @@ pub mod preferences;
 pub mod transcription;
 
 use std::path::PathBuf;
-use std::sync::{Mutex, RwLock};
-
-use audio::capture::AudioCapture;
-use hotkeys::{resolve_hotkey_event, HotkeyEvent, HotkeyResponse};
+use std::sync::{Arc, Mutex, RwLock};
+
+use audio::capture::WarmMicrophone;
+use audio::loopback::LoopbackCapture;
+use audio::playback::AudioPlayer;
+use audio::resample::ResampledCapture;
+use audio::source::AudioSource;
+use hotkeys::{resolve_hotkey_event, HotkeyEvent, HotkeyResponse, StartRecordingRequest};
 use preferences::storage;
 use preferences::UserPreferences;
 use tauri::{
@@ -20,23 +24,42 @@ use tauri::{
     Emitter, Manager, RunEvent, WindowEvent, Wry,
 };
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
-use transcription::engine::TranscriptionEngine;
+use transcription::engine::TranscribeOptions;
+use transcription::worker::{JobKind, LoadedEngine, TranscriptionWorker};
+use transcription::streaming::LiveTranscription;
 
 pub struct AppState {
     pub preferences: RwLock<UserPreferences>,
+    /// Custom vocabulary, persisted next to the preferences. There is one
+    /// list for the whole app; per-profile lists wait on profiles existing.
+    pub vocabulary: RwLock<Vec<String>>,
     pub app_data_dir: PathBuf,
     pub recording_active: RwLock<bool>,
-    pub engine: RwLock<Option<Box<dyn TranscriptionEngine>>>,
-    pub active_capture: Mutex<Option<AudioCapture>>,
+    /// Owns the loaded engine and runs every transcription and model switch.
+    pub transcriber: TranscriptionWorker,
+    pub active_capture: Mutex<Option<Box<dyn AudioSource>>>,
     pub recording_started_at: Mutex<Option<std::time::Instant>>,
+    pub live_transcription: Mutex<Option<LiveTranscription>>,
+    /// 16kHz copy of the active recording, filled as audio is captured.
+    pub session_resampler: Mutex<Option<Arc<Mutex<ResampledCapture>>>>,
+    /// System audio track when recording a call.
+    pub system_capture: Mutex<Option<LoopbackCapture>>,
+    /// How the active recording is transcribed, fixed when it starts.
+    pub session_options: Mutex<Option<TranscribeOptions>>,
+    pub warm_microphone: Mutex<Option<WarmMicrophone>>,
+    pub mic_test: Mutex<Option<Box<dyn AudioSource>>>,
+    pub mic_test_playback: Mutex<Option<AudioPlayer>>,
 }
 
 fn build_tray_menu(
     app: &tauri::AppHandle,
     is_recording: bool,
+    mic_armed: bool,
 ) -> tauri::Result<tauri::menu::Menu<Wry>> {
     let status_text = if is_recording {
         "Recording..."
+    } else if mic_armed {
+        "Recording: Idle (Mic Armed)"
     } else {
         "Recording: Idle"
     };
@@ -75,9 +98,21 @@ fn build_tray_menu(
 
 pub fn update_tray_recording_state(app: &tauri::AppHandle, is_recording: bool) {
     if let Some(tray) = app.tray_by_id("main-tray") {
-        if let Ok(menu) = build_tray_menu(app, is_recording) {
+        // The warm microphone keeps the input device open between recordings
+        let mic_armed = app
+            .try_state::<AppState>()
+            .and_then(|state| state.warm_microphone.lock().map(|w| w.is_some()).ok())
+            .unwrap_or(false);
+
+        if let Ok(menu) = build_tray_menu(app, is_recording, mic_armed) {
             let _ = tray.set_menu(Some(menu));
         }
+        let tooltip = if mic_armed {
+            "TalkyTexty (Microphone Armed)"
+        } else {
+            "TalkyTexty"
+        };
+        let _ = tray.set_tooltip(Some(tooltip));
 
         // Swap icon: normal icon or icon with red recording dot
         let icon = if is_recording {
@@ -146,6 +181,10 @@ pub fn run() {
                             let _ = app.emit("hotkey-cancel-recording", ());
                             return;
                         }
+                        // Escape while transcribing abandons the dictation
+                        if state.transcriber.cancel_kind(JobKind::Dictation) {
+                            return;
+                        }
                     }
 
                     let state = app.state::<AppState>();
@@ -169,12 +208,12 @@ pub fn run() {
                                     .map(|parsed| parsed == *shortcut)
                                     .unwrap_or(false)
                         })
-                        .map(|h| h.action.clone());
+                        .map(|h| (h.action.clone(), h.translate));
 
                     let recording_mode = prefs.recording_mode.clone();
                     drop(prefs);
 
-                    let Some(action) = action else {
+                    let Some((action, translate)) = action else {
                         eprintln!("[hotkey] No matching action for shortcut: {}", shortcut);
                         return;
                     };
@@ -198,7 +237,7 @@ pub fn run() {
                     match response {
                         HotkeyResponse::StartRecording => {
                             eprintln!("[hotkey] Emitting hotkey-start-recording to all windows");
-                            let _ = app.emit("hotkey-start-recording", ());
+                            let _ = app.emit("hotkey-start-recording", StartRecordingRequest { translate });
                         }
                         HotkeyResponse::StopRecordingAndTranscribe => {
                             let _ = app.emit("hotkey-stop-recording", ());
@@ -239,9 +278,25 @@ pub fn run() {
                 .app_data_dir()
                 .expect("failed to get app data dir");
 
-            let prefs = storage::load_preferences(&app_data_dir)
+            let mut prefs = storage::load_preferences(&app_data_dir)
                 .unwrap_or_default();
 
+            // Preferences from older versions identify devices by name
+            if let Ok(devices) = audio::capture::list_input_devices() {
+                if prefs.migrate_device_ids(&devices) {
+                    if let Err(e) = storage::save_preferences(&app_data_dir, &prefs) {
+                        log::warn!("Failed to save migrated preferences: {}", e);
+                    }
+                }
+            }
+
+            // Spill files from a recording interrupted by a crash are never read again
+            audio::buffer::remove_stale_spill_files(&audio::buffer::get_spill_dir(&app_data_dir));
+
+            // Finish converting recordings to a compressed format if the app
+            // quit part way through
+            history::spawn_wav_migration(app_data_dir.clone(), prefs.history_audio_format);
+
             // Register enabled hotkeys
             let global_shortcut = app.global_shortcut();
             for hotkey in &prefs.hotkeys {
@@ -258,7 +313,7 @@ pub fn run() {
             }
 
             // Auto-load transcription engine if an active model is set
-            let initial_engine: Option<Box<dyn TranscriptionEngine>> = if let Some(ref model_id) = prefs.active_model_id {
+            let initial_engine: Option<LoadedEngine> = if let Some(ref model_id) = prefs.active_model_id {
                 let all_models = transcription::models::get_builtin_models(&app_data_dir);
                 all_models
                     .iter()
@@ -268,10 +323,13 @@ pub fn run() {
                             model.download_status
                         {
                             eprintln!("[setup] Auto-loading model: {} from {}", model_id, local_path);
-                            match commands::model_commands::load_engine_for_model(model, local_path) {
-                                Ok(engine) => {
+                            // Kept by the worker to load the model again after a panic
+                            let (loader_model, local_path) = (model.clone(), local_path.clone());
+                            let loader = move || commands::model_commands::load_engine_for_model(&loader_model, &local_path);
+                            match LoadedEngine::load(model.clone(), loader) {
+                                Ok(loaded) => {
                                     eprintln!("[setup] Model loaded successfully");
-                                    Some(engine)
+                                    Some(loaded)
                                 }
                                 Err(e) => {
                                     eprintln!("[setup] Failed to load model: {}", e);
@@ -288,16 +346,35 @@ pub fn run() {
                 None
             };
 
+            let vocabulary = storage::load_vocabulary(&app_data_dir).unwrap_or_else(|e| {
+                log::warn!("Failed to load vocabulary: {}", e);
+                Vec::new()
+            });
+
+            let job_events = app.handle().clone();
+            let transcriber = TranscriptionWorker::spawn(initial_engine, move |event| {
+                let _ = job_events.emit("transcription-job", event);
+            });
+
             let overlay_position = prefs.overlay_position.clone();
             let overlay_custom_position = prefs.overlay_custom_position.clone();
+            let startup_prefs = prefs.clone();
 
             app.manage(AppState {
                 preferences: RwLock::new(prefs),
+                vocabulary: RwLock::new(vocabulary),
                 app_data_dir,
                 recording_active: RwLock::new(false),
-                engine: RwLock::new(initial_engine),
+                transcriber,
                 active_capture: Mutex::new(None),
                 recording_started_at: Mutex::new(None),
+                live_transcription: Mutex::new(None),
+                session_resampler: Mutex::new(None),
+                system_capture: Mutex::new(None),
+                session_options: Mutex::new(None),
+                warm_microphone: Mutex::new(None),
+                mic_test: Mutex::new(None),
+                mic_test_playback: Mutex::new(None),
             });
 
             // Always hide the zoom (green) traffic-light button on the main window
@@ -364,7 +441,7 @@ pub fn run() {
             }
 
             // System tray
-            let menu = build_tray_menu(app.handle(), false)?;
+            let menu = build_tray_menu(app.handle(), false, false)?;
 
             let _tray = TrayIconBuilder::with_id("main-tray")
                 .icon(Image::from_bytes(include_bytes!("../icons/32x32.png"))?)
@@ -383,7 +460,7 @@ pub fn run() {
                             if is_recording {
                                 let _ = app.emit("hotkey-stop-recording", ());
                             } else {
-                                let _ = app.emit("hotkey-start-recording", ());
+                                let _ = app.emit("hotkey-start-recording", StartRecordingRequest::default());
                             }
                         }
                         "show_settings" => {
@@ -421,6 +498,15 @@ pub fn run() {
                 })
                 .build(app)?;
 
+            // Arm the warm microphone once the tray exists to reflect it
+            commands::audio_commands::sync_warm_microphone(app.handle(), &startup_prefs);
+
+            // Notify the frontend when microphones are plugged in or removed
+            let watcher_handle = app.handle().clone();
+            audio::watcher::watch_input_devices(move |devices| {
+                commands::audio_commands::handle_devices_changed(&watcher_handle, devices);
+            });
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -436,6 +522,11 @@ pub fn run() {
             commands::system_commands::request_permission,
             commands::preferences_commands::get_preferences,
             commands::preferences_commands::update_preferences,
+            commands::preferences_commands::get_vocabulary,
+            commands::preferences_commands::set_vocabulary,
+            commands::preferences_commands::add_vocabulary_term,
+            commands::preferences_commands::remove_vocabulary_term,
+            commands::preferences_commands::get_whisper_decode_options,
             commands::model_commands::list_models,
             commands::model_commands::set_active_model,
             commands::model_commands::download_model,
@@ -444,6 +535,9 @@ pub fn run() {
             commands::audio_commands::stop_recording,
             commands::audio_commands::cancel_recording,
             commands::audio_commands::list_audio_devices,
+            commands::audio_commands::start_mic_test,
+            commands::audio_commands::stop_mic_test,
+            commands::audio_commands::transcribe_file,
             commands::injection_commands::inject_text,
             commands::injection_commands::list_windows,
             commands::injection_commands::copy_to_clipboard,
@@ -452,6 +546,8 @@ pub fn run() {
             commands::history_commands::delete_history_entry,
             commands::history_commands::clear_history,
             commands::history_commands::get_history_audio,
+            commands::history_commands::get_history_transcript,
+            commands::history_commands::retranscribe_history_track,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -1,7 +1,12 @@
 pub mod storage;
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::audio::capture::AudioDeviceInfo;
+use crate::transcription::{ModelVariant, TranscriptionModel};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserPreferences {
@@ -13,29 +18,138 @@ pub struct UserPreferences {
     pub overlay_position: OverlayPosition,
     pub overlay_opacity: f32,
     pub overlay_visualization: VisualizationStyle,
+    /// What each visualization style draws; styles not listed use the envelope.
+    #[serde(default)]
+    pub visualization_modes: HashMap<VisualizationStyle, VisualizationMode>,
     #[serde(default)]
     pub overlay_processing_animation: ProcessingAnimation,
     #[serde(default)]
     pub overlay_mode: OverlayMode,
     #[serde(default)]
     pub overlay_custom_position: Option<OverlayCustomPosition>,
+    /// Display name of the selected input device, kept for the UI and history.
     #[serde(default)]
     pub selected_audio_device: Option<String>,
+    /// Stable id of the selected input device; this is what capture uses.
+    #[serde(default)]
+    pub selected_audio_device_id: Option<String>,
     pub launch_at_login: bool,
     #[serde(default = "default_true")]
     pub remove_filler_words: bool,
+    #[serde(default)]
+    pub hands_free: HandsFreeSettings,
+    #[serde(default)]
+    pub live_transcription: bool,
+    #[serde(default)]
+    pub warm_microphone: WarmMicrophoneSettings,
+    #[serde(default)]
+    pub audio_processing: AudioProcessingSettings,
+    /// Continue recording on the default device if the selected one is disconnected.
+    #[serde(default = "default_true")]
+    pub device_failover: bool,
+    /// Which input channel to record, keyed by device id.
+    #[serde(default)]
+    pub input_channels: HashMap<String, InputChannel>,
+    /// Recordings are stopped automatically after this many minutes.
+    #[serde(default = "default_max_recording_minutes")]
+    pub max_recording_minutes: u32,
+    /// Where recordings take their audio from. Overridden by the
+    /// `TALKYTEXTY_AUDIO_SOURCE` environment variable.
+    #[serde(default)]
+    pub audio_source: AudioSourceSetting,
+    /// Format recordings are stored in for history playback.
+    #[serde(default)]
+    pub history_audio_format: HistoryAudioFormat,
+    /// Trade-off between speed and fidelity when converting audio to 16kHz.
+    #[serde(default)]
+    pub resampler_quality: ResamplerQuality,
+    /// Record system audio alongside the microphone, for transcribing calls.
+    #[serde(default)]
+    pub system_audio: SystemAudioSettings,
+    /// Language dictation is spoken in, passed to engines that need to know.
+    #[serde(default)]
+    pub spoken_language: SpokenLanguage,
+    #[serde(default)]
+    pub low_confidence: LowConfidenceSettings,
+    /// How Whisper models decode; Parakeet models ignore it.
+    #[serde(default)]
+    pub whisper_decoding: WhisperDecodeSetting,
+}
+
+impl UserPreferences {
+    /// Zero-based channel to record from `device_id`, or `None` to mix all
+    /// channels. The system default device always mixes.
+    pub fn input_channel_for(&self, device_id: Option<&str>) -> Option<usize> {
+        match device_id.and_then(|id| self.input_channels.get(id)) {
+            Some(InputChannel::Single { index }) => Some(*index as usize),
+            Some(InputChannel::Mix) | None => None,
+        }
+    }
+
+    /// What the overlay's current visualization style draws.
+    pub fn overlay_visualization_mode(&self) -> VisualizationMode {
+        self.visualization_modes
+            .get(&self.overlay_visualization)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Convert device settings saved by name into settings keyed by device id,
+    /// for whichever of those devices are currently connected. Returns whether
+    /// anything changed.
+    pub fn migrate_device_ids(&mut self, devices: &[AudioDeviceInfo]) -> bool {
+        let id_for_name = |name: &str| {
+            devices
+                .iter()
+                .find(|d| d.name == name)
+                .map(|d| d.id.clone())
+        };
+        let mut changed = false;
+
+        if self.selected_audio_device_id.is_none() {
+            if let Some(id) = self.selected_audio_device.as_deref().and_then(id_for_name) {
+                self.selected_audio_device_id = Some(id);
+                changed = true;
+            }
+        }
+
+        let name_keys: Vec<String> = self
+            .input_channels
+            .keys()
+            .filter(|key| !devices.iter().any(|d| &d.id == *key))
+            .cloned()
+            .collect();
+        for name in name_keys {
+            let Some(id) = id_for_name(&name) else {
+                continue;
+            };
+            if let Some(channel) = self.input_channels.remove(&name) {
+                self.input_channels.entry(id).or_insert(channel);
+                changed = true;
+            }
+        }
+
+        changed
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_max_recording_minutes() -> u32 {
+    30
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HotkeyBinding {
     pub action: HotkeyAction,
     pub key_combination: String,
     pub enabled: bool,
+    /// Recordings started with this hotkey are translated into English.
+    #[serde(default)]
+    pub translate: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -50,6 +164,30 @@ pub enum HotkeyAction {
 pub enum RecordingMode {
     PushToTalk,
     Toggle,
+    /// Start with the toggle hotkey; stops automatically after trailing silence.
+    HandsFree,
+}
+
+/// Silence detection settings for hands-free recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HandsFreeSettings {
+    /// RMS level (0.0–1.0) below which input counts as silence.
+    pub silence_threshold: f32,
+    /// Silence after speech that ends the session.
+    pub trailing_silence_ms: u32,
+    /// Speech required before trailing silence can end the session.
+    pub min_speech_ms: u32,
+}
+
+impl Default for HandsFreeSettings {
+    fn default() -> Self {
+        Self {
+            silence_threshold: 0.01,
+            trailing_silence_ms: 1500,
+            min_speech_ms: 300,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,44 +211,318 @@ pub enum OverlayPosition {
     BottomRight,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum VisualizationStyle {
     Bars,
     Sine,
     Rainbow,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The audio data a visualization style is drawn from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VisualizationMode {
+    /// Amplitude over the last ~50ms of audio.
+    #[default]
+    Envelope,
+    /// Log-spaced frequency band levels with peak hold.
+    Spectrum,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum ProcessingAnimation {
+    #[default]
     Pulse,
     FrozenFrame,
     TypingParrot,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum OverlayMode {
     None,
+    #[default]
     Full,
     Mini,
 }
 
-impl Default for OverlayMode {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayCustomPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Keep the microphone open between recordings so the start of speech isn't clipped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WarmMicrophoneSettings {
+    pub enabled: bool,
+    /// Audio from before the hotkey press to include in each recording.
+    pub pre_roll_ms: u32,
+}
+
+impl Default for WarmMicrophoneSettings {
     fn default() -> Self {
-        OverlayMode::Full
+        Self {
+            enabled: false,
+            pre_roll_ms: 500,
+        }
     }
 }
 
-impl Default for ProcessingAnimation {
+/// Ask before typing out transcripts the engine wasn't sure about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LowConfidenceSettings {
+    /// Hold low-confidence transcripts for confirmation instead of injecting them.
+    pub hold: bool,
+    /// Overall confidence (0.0–1.0) below which a transcript is held.
+    pub threshold: f32,
+}
+
+impl Default for LowConfidenceSettings {
     fn default() -> Self {
-        ProcessingAnimation::Pulse
+        Self {
+            hold: false,
+            threshold: 0.6,
+        }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OverlayCustomPosition {
-    pub x: f64,
-    pub y: f64,
+impl LowConfidenceSettings {
+    /// Whether a transcript with `confidence` should wait for confirmation.
+    /// Transcripts from engines that don't report confidence are never held.
+    pub fn should_hold(&self, confidence: Option<f32>) -> bool {
+        self.hold && confidence.is_some_and(|c| c < self.threshold)
+    }
+}
+
+/// Recording a loopback device (such as a PulseAudio/PipeWire `.monitor`
+/// source) as a second track next to the microphone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SystemAudioSettings {
+    pub enabled: bool,
+    /// Loopback device id from `list_input_devices`; the first loopback
+    /// device found is used when unset.
+    pub device_id: Option<String>,
+}
+
+/// Channel selection for a multi-channel input device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputChannel {
+    /// Average all channels into one.
+    #[default]
+    Mix,
+    /// Record only one channel, e.g. input 1 of an audio interface.
+    Single { index: u16 },
+}
+
+/// Storage format for history recordings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryAudioFormat {
+    /// Uncompressed 16-bit PCM.
+    #[default]
+    Wav,
+    /// Lossless, roughly half the size of WAV.
+    Flac,
+    /// Lossy speech codec, a small fraction of the size of WAV.
+    Opus,
+}
+
+/// How audio is converted to the 16kHz the transcription engines expect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResamplerQuality {
+    /// Linear interpolation: cheapest, but lets some aliasing through.
+    Fast,
+    /// FFT-based resampling.
+    #[default]
+    Balanced,
+    /// Long windowed-sinc filter with the flattest passband.
+    High,
+}
+
+/// Language the user speaks when dictating.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SpokenLanguage {
+    /// Let the engine detect the language of each recording.
+    Auto,
+    /// Always transcribe as this language (ISO 639-1 code, e.g. `de`).
+    Fixed { code: String },
+}
+
+impl Default for SpokenLanguage {
+    fn default() -> Self {
+        Self::Fixed {
+            code: "en".to_string(),
+        }
+    }
+}
+
+/// Whisper decoding parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WhisperDecodeOptions {
+    /// Beam search width; 1 decodes greedily.
+    pub beam_size: u32,
+    /// Sampling temperature of the first decoding attempt.
+    pub temperature: f32,
+    /// Added to the temperature each time a segment is decoded again after
+    /// failing Whisper's quality checks; 0 disables the fallback.
+    pub temperature_increment: f32,
+    /// No-speech probability above which a segment counts as silence.
+    pub no_speech_threshold: f32,
+    /// CPU threads to decode on; 0 lets whisper.cpp choose.
+    pub threads: u32,
+    /// Suppress non-speech tokens such as `[music]` and `♪`.
+    pub suppress_non_speech: bool,
+}
+
+/// Matches whisper.cpp's own defaults with greedy decoding.
+impl Default for WhisperDecodeOptions {
+    fn default() -> Self {
+        Self {
+            beam_size: 1,
+            temperature: 0.0,
+            temperature_increment: 0.2,
+            no_speech_threshold: 0.6,
+            threads: 0,
+            suppress_non_speech: false,
+        }
+    }
+}
+
+/// Trade-off between decoding speed and accuracy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WhisperDecodePreset {
+    /// Greedy decoding without temperature fallback.
+    Fast,
+    /// Greedy decoding with whisper.cpp's temperature fallback, as used
+    /// before decoding was configurable.
+    #[default]
+    Standard,
+    /// Beam search where the model is small enough for it to be cheap.
+    Balanced,
+    /// Wide beam search, fallback and non-speech suppression.
+    Accurate,
+}
+
+impl WhisperDecodeOptions {
+    /// Options for `preset` on a model of size `variant`. Smaller models
+    /// decode fast enough to afford a wider beam.
+    pub fn preset(preset: WhisperDecodePreset, variant: &ModelVariant) -> Self {
+        let (balanced_beam, accurate_beam) = match variant {
+            ModelVariant::Tiny | ModelVariant::Base => (5, 8),
+            ModelVariant::Small => (3, 5),
+            ModelVariant::Medium | ModelVariant::LargeV3Turbo => (1, 5),
+            _ => (1, 3),
+        };
+        match preset {
+            WhisperDecodePreset::Fast => Self {
+                temperature_increment: 0.0,
+                ..Self::default()
+            },
+            WhisperDecodePreset::Standard => Self::default(),
+            WhisperDecodePreset::Balanced => Self {
+                beam_size: balanced_beam,
+                ..Self::default()
+            },
+            WhisperDecodePreset::Accurate => Self {
+                beam_size: accurate_beam,
+                suppress_non_speech: true,
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// Whisper decoding: a preset tuned to the active model, or explicit options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WhisperDecodeSetting {
+    Preset { preset: WhisperDecodePreset },
+    Custom { options: WhisperDecodeOptions },
+}
+
+impl Default for WhisperDecodeSetting {
+    fn default() -> Self {
+        Self::Preset {
+            preset: WhisperDecodePreset::default(),
+        }
+    }
+}
+
+impl WhisperDecodeSetting {
+    /// The decoding options to use with a model of size `variant`.
+    pub fn options_for(&self, variant: &ModelVariant) -> WhisperDecodeOptions {
+        match self {
+            Self::Preset { preset } => WhisperDecodeOptions::preset(*preset, variant),
+            Self::Custom { options } => options.clone(),
+        }
+    }
+}
+
+/// Audio source for recordings. Anything other than the microphone is meant
+/// for testing and for replaying problem recordings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AudioSourceSetting {
+    /// The selected input device.
+    #[default]
+    Microphone,
+    /// Play back an audio file as if it were being spoken into the microphone.
+    File { path: String },
+    /// A generated signal of alternating tone bursts and silence.
+    Synthetic,
+}
+
+impl std::str::FromStr for AudioSourceSetting {
+    type Err = String;
+
+    /// Parse `microphone`, `synthetic` or `file:<path>`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(path) = value.strip_prefix("file:") {
+            if path.is_empty() {
+                return Err("Audio source 'file:' needs a path.".to_string());
+            }
+            return Ok(Self::File {
+                path: path.to_string(),
+            });
+        }
+        match value.to_ascii_lowercase().as_str() {
+            "microphone" | "mic" => Ok(Self::Microphone),
+            "synthetic" => Ok(Self::Synthetic),
+            _ => Err(format!(
+                "Unknown audio source '{}'. Expected microphone, synthetic or file:<path>.",
+                value
+            )),
+        }
+    }
+}
+
+/// Cleanup applied to recorded audio before it is transcribed and saved.
+/// Every stage is off by default, so recordings are left as captured.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioProcessingSettings {
+    /// Remove DC offset and low-frequency rumble.
+    pub high_pass: bool,
+    /// Attenuate steady background noise (fans, air conditioning, chatter).
+    pub noise_suppression: bool,
+    /// Normalize speech loudness for quiet or distant microphones.
+    pub auto_gain: bool,
+}
+
+impl AudioProcessingSettings {
+    pub fn chain(&self) -> crate::audio::dsp::ProcessingChain {
+        crate::audio::dsp::ProcessingChain {
+            high_pass: self.high_pass,
+            noise_suppression: self.noise_suppression,
+            auto_gain: self.auto_gain,
+        }
+    }
 }
 
 /// Known modifier keys in Tauri shortcut format.
@@ -160,6 +572,211 @@ pub fn validate_hotkey_format(combo: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Validate hands-free silence detection settings.
+pub fn validate_hands_free_settings(settings: &HandsFreeSettings) -> Result<(), String> {
+    if !(settings.silence_threshold > 0.0 && settings.silence_threshold < 1.0) {
+        return Err(format!(
+            "Silence threshold must be between 0 and 1, got {}.",
+            settings.silence_threshold
+        ));
+    }
+    if !(200..=10_000).contains(&settings.trailing_silence_ms) {
+        return Err(format!(
+            "Trailing silence must be between 200 and 10000 ms, got {}.",
+            settings.trailing_silence_ms
+        ));
+    }
+    if !(100..=10_000).contains(&settings.min_speech_ms) {
+        return Err(format!(
+            "Minimum speech length must be between 100 and 10000 ms, got {}.",
+            settings.min_speech_ms
+        ));
+    }
+    Ok(())
+}
+
+/// Validate warm microphone settings.
+pub fn validate_warm_microphone_settings(settings: &WarmMicrophoneSettings) -> Result<(), String> {
+    if !(100..=2000).contains(&settings.pre_roll_ms) {
+        return Err(format!(
+            "Pre-roll must be between 100 and 2000 ms, got {}.",
+            settings.pre_roll_ms
+        ));
+    }
+    Ok(())
+}
+
+/// Validate low-confidence hold settings.
+pub fn validate_low_confidence_settings(settings: &LowConfidenceSettings) -> Result<(), String> {
+    if !(settings.threshold > 0.0 && settings.threshold < 1.0) {
+        return Err(format!(
+            "Confidence threshold must be between 0 and 1, got {}.",
+            settings.threshold
+        ));
+    }
+    Ok(())
+}
+
+/// Widest beam accepted in [`WhisperDecodeOptions::beam_size`]; whisper.cpp
+/// gains little past this and slows down sharply.
+const MAX_BEAM_SIZE: u32 = 16;
+/// Most threads accepted in [`WhisperDecodeOptions::threads`].
+const MAX_DECODE_THREADS: u32 = 64;
+
+/// Validate custom Whisper decoding options; presets are always valid.
+pub fn validate_whisper_decoding(setting: &WhisperDecodeSetting) -> Result<(), String> {
+    let WhisperDecodeSetting::Custom { options } = setting else {
+        return Ok(());
+    };
+    if !(1..=MAX_BEAM_SIZE).contains(&options.beam_size) {
+        return Err(format!(
+            "Beam size must be between 1 and {}, got {}.",
+            MAX_BEAM_SIZE, options.beam_size
+        ));
+    }
+    if !(0.0..=1.0).contains(&options.temperature) {
+        return Err(format!("Temperature must be between 0 and 1, got {}.", options.temperature));
+    }
+    if !(0.0..=1.0).contains(&options.temperature_increment) {
+        return Err(format!(
+            "Temperature fallback step must be between 0 and 1, got {}.",
+            options.temperature_increment
+        ));
+    }
+    if !(options.no_speech_threshold > 0.0 && options.no_speech_threshold < 1.0) {
+        return Err(format!(
+            "No-speech threshold must be between 0 and 1, got {}.",
+            options.no_speech_threshold
+        ));
+    }
+    if options.threads > MAX_DECODE_THREADS {
+        return Err(format!(
+            "Thread count must be at most {}, got {}.",
+            MAX_DECODE_THREADS, options.threads
+        ));
+    }
+    Ok(())
+}
+
+/// Validate the maximum recording duration.
+pub fn validate_max_recording_minutes(minutes: u32) -> Result<(), String> {
+    if !(1..=240).contains(&minutes) {
+        return Err(format!(
+            "Maximum recording length must be between 1 and 240 minutes, got {}.",
+            minutes
+        ));
+    }
+    Ok(())
+}
+
+/// Highest channel index accepted in [`InputChannel::Single`].
+const MAX_INPUT_CHANNEL: u16 = 63;
+
+/// Validate per-device input channel selections.
+pub fn validate_input_channels(channels: &HashMap<String, InputChannel>) -> Result<(), String> {
+    for (device, channel) in channels {
+        if let InputChannel::Single { index } = channel {
+            if *index > MAX_INPUT_CHANNEL {
+                return Err(format!(
+                    "Input channel for '{}' must be at most {}, got {}.",
+                    device,
+                    MAX_INPUT_CHANNEL + 1,
+                    index + 1
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate the recording audio source.
+pub fn validate_audio_source(source: &AudioSourceSetting) -> Result<(), String> {
+    if let AudioSourceSetting::File { path } = source {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("Audio source file '{}' does not exist.", path));
+        }
+    }
+    Ok(())
+}
+
+/// Validate the spoken language against what `model` can transcribe.
+pub fn validate_spoken_language(language: &SpokenLanguage, model: &TranscriptionModel) -> Result<(), String> {
+    match language {
+        SpokenLanguage::Auto if model.languages.len() < 2 => Err(format!(
+            "{} only transcribes {}, so it can't detect the language. Choose a multilingual model.",
+            model.name,
+            model.languages.join(", ")
+        )),
+        SpokenLanguage::Fixed { code } if !model.languages.iter().any(|l| l == code) => Err(format!(
+            "{} does not support language '{}'. Supported: {}.",
+            model.name,
+            code,
+            model.languages.join(", ")
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Most terms the custom vocabulary holds.
+pub const MAX_VOCABULARY_TERMS: usize = 500;
+/// Longest custom vocabulary term, in characters.
+const MAX_VOCABULARY_TERM_CHARS: usize = 64;
+
+/// Validate a custom vocabulary term.
+pub fn validate_vocabulary_term(term: &str) -> Result<(), String> {
+    if term.trim().is_empty() {
+        return Err("Vocabulary terms cannot be empty.".to_string());
+    }
+    if term.chars().count() > MAX_VOCABULARY_TERM_CHARS {
+        return Err(format!(
+            "Vocabulary term '{}' is longer than {} characters.",
+            term, MAX_VOCABULARY_TERM_CHARS
+        ));
+    }
+    if term.chars().any(char::is_control) {
+        return Err(format!("Vocabulary term '{}' contains control characters.", term.escape_default()));
+    }
+    Ok(())
+}
+
+/// Trim and validate vocabulary terms, dropping duplicates that differ only
+/// in case (the first spelling wins).
+pub fn normalize_vocabulary(terms: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(terms.len());
+    for term in terms {
+        let term = term.trim();
+        validate_vocabulary_term(term)?;
+        if !normalized.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            normalized.push(term.to_string());
+        }
+    }
+    if normalized.len() > MAX_VOCABULARY_TERMS {
+        return Err(format!(
+            "The vocabulary can hold at most {} terms, got {}.",
+            MAX_VOCABULARY_TERMS,
+            normalized.len()
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Check that `model` can translate speech into English.
+pub fn validate_translation(model: &TranscriptionModel) -> Result<(), String> {
+    if model.model_family != "Whisper" {
+        return Err(format!(
+            "{} can't translate. Choose a multilingual Whisper model to translate speech into English.",
+            model.name
+        ));
+    }
+    if model.languages.len() < 2 {
+        return Err(format!(
+            "{} is English-only and can't translate. Choose a multilingual Whisper model to translate speech into English.",
+            model.name
+        ));
+    }
+    Ok(())
+}
+
 /// Detect conflicting hotkey bindings (same key combination on multiple enabled actions).
 /// Returns a list of conflicting key combinations.
 pub fn detect_hotkey_conflicts(bindings: &[HotkeyBinding]) -> Vec<String> {
@@ -191,21 +808,25 @@ impl Default for UserPreferences {
                     action: HotkeyAction::ToggleRecording,
                     key_combination: "CommandOrControl+Shift+Space".to_string(),
                     enabled: true,
+                    translate: false,
                 },
                 HotkeyBinding {
                     action: HotkeyAction::PushToTalk,
                     key_combination: "CommandOrControl+Shift+V".to_string(),
                     enabled: false,
+                    translate: false,
                 },
                 HotkeyBinding {
                     action: HotkeyAction::OpenTargetSelector,
                     key_combination: "CommandOrControl+Shift+T".to_string(),
                     enabled: true,
+                    translate: false,
                 },
                 HotkeyBinding {
                     action: HotkeyAction::OpenSettings,
                     key_combination: "CommandOrControl+Shift+,".to_string(),
                     enabled: true,
+                    translate: false,
                 },
             ],
             target_mode: TargetMode::ActiveWindow,
@@ -213,12 +834,303 @@ impl Default for UserPreferences {
             overlay_position: OverlayPosition::TopCenter,
             overlay_opacity: 0.9,
             overlay_visualization: VisualizationStyle::Bars,
+            visualization_modes: HashMap::new(),
             overlay_processing_animation: ProcessingAnimation::Pulse,
             overlay_mode: OverlayMode::Full,
             overlay_custom_position: None,
             selected_audio_device: None,
+            selected_audio_device_id: None,
             launch_at_login: false,
             remove_filler_words: true,
+            hands_free: HandsFreeSettings::default(),
+            live_transcription: false,
+            warm_microphone: WarmMicrophoneSettings::default(),
+            audio_processing: AudioProcessingSettings::default(),
+            device_failover: true,
+            input_channels: HashMap::new(),
+            max_recording_minutes: default_max_recording_minutes(),
+            audio_source: AudioSourceSetting::default(),
+            history_audio_format: HistoryAudioFormat::default(),
+            resampler_quality: ResamplerQuality::default(),
+            system_audio: SystemAudioSettings::default(),
+            spoken_language: SpokenLanguage::default(),
+            low_confidence: LowConfidenceSettings::default(),
+            whisper_decoding: WhisperDecodeSetting::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, name: &str) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            is_default: false,
+            channels: 2,
+            is_loopback: false,
+        }
+    }
+
+    #[test]
+    fn test_migrate_selected_device_name_to_id() {
+        let mut prefs = UserPreferences {
+            selected_audio_device: Some("USB Mic".to_string()),
+            ..Default::default()
+        };
+        let devices = vec![device("coreaudio:built-in", "MacBook Microphone"), device("coreaudio:usb-1", "USB Mic")];
+
+        assert!(prefs.migrate_device_ids(&devices));
+        assert_eq!(prefs.selected_audio_device_id.as_deref(), Some("coreaudio:usb-1"));
+        assert!(!prefs.migrate_device_ids(&devices));
+    }
+
+    #[test]
+    fn test_migrate_waits_for_disconnected_device() {
+        let mut prefs = UserPreferences {
+            selected_audio_device: Some("USB Mic".to_string()),
+            ..Default::default()
+        };
+        assert!(!prefs.migrate_device_ids(&[device("coreaudio:built-in", "MacBook Microphone")]));
+        assert!(prefs.selected_audio_device_id.is_none());
+    }
+
+    #[test]
+    fn test_migrate_input_channels_to_ids() {
+        let mut prefs = UserPreferences::default();
+        prefs
+            .input_channels
+            .insert("Scarlett 2i2".to_string(), InputChannel::Single { index: 0 });
+        prefs
+            .input_channels
+            .insert("wasapi:already-an-id".to_string(), InputChannel::Mix);
+        let devices = vec![
+            device("wasapi:scarlett", "Scarlett 2i2"),
+            device("wasapi:already-an-id", "Headset"),
+        ];
+
+        assert!(prefs.migrate_device_ids(&devices));
+        assert_eq!(prefs.input_channel_for(Some("wasapi:scarlett")), Some(0));
+        assert!(!prefs.input_channels.contains_key("Scarlett 2i2"));
+        assert_eq!(prefs.input_channels.len(), 2);
+    }
+
+    #[test]
+    fn test_visualization_mode_per_style() {
+        let mut prefs: UserPreferences = serde_json::from_value(serde_json::json!({
+            "activeModelId": null,
+            "recordingMode": "PushToTalk",
+            "hotkeys": [],
+            "targetMode": { "type": "ActiveWindow" },
+            "textInjectionMethod": "SimulatedKeystrokes",
+            "overlayPosition": "TopCenter",
+            "overlayOpacity": 0.9,
+            "overlayVisualization": "Rainbow",
+            "visualizationModes": { "Rainbow": "Spectrum" },
+            "launchAtLogin": false
+        }))
+        .unwrap();
+        assert_eq!(prefs.overlay_visualization_mode(), VisualizationMode::Spectrum);
+
+        prefs.overlay_visualization = VisualizationStyle::Bars;
+        assert_eq!(prefs.overlay_visualization_mode(), VisualizationMode::Envelope);
+    }
+
+    #[test]
+    fn test_system_audio_settings() {
+        let prefs = UserPreferences::default();
+        assert!(!prefs.system_audio.enabled);
+
+        let settings: SystemAudioSettings = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "deviceId": "alsa:pulse:Monitor of Built-in Audio"
+        }))
+        .unwrap();
+        assert!(settings.enabled);
+        assert_eq!(settings.device_id.as_deref(), Some("alsa:pulse:Monitor of Built-in Audio"));
+        let settings: SystemAudioSettings = serde_json::from_value(serde_json::json!({ "enabled": true })).unwrap();
+        assert_eq!(settings.device_id, None);
+    }
+
+    #[test]
+    fn test_low_confidence_hold() {
+        let settings = LowConfidenceSettings::default();
+        assert!(!settings.should_hold(Some(0.1)));
+
+        let settings = LowConfidenceSettings {
+            hold: true,
+            threshold: 0.6,
+        };
+        assert!(settings.should_hold(Some(0.45)));
+        assert!(!settings.should_hold(Some(0.8)));
+        assert!(!settings.should_hold(None));
+
+        assert!(validate_low_confidence_settings(&settings).is_ok());
+        for threshold in [0.0, 1.0, 1.5, f32::NAN] {
+            let settings = LowConfidenceSettings { hold: true, threshold };
+            assert!(validate_low_confidence_settings(&settings).is_err(), "{} accepted", threshold);
         }
     }
+
+    #[test]
+    fn test_whisper_decoding_presets_scale_with_model() {
+        let setting = UserPreferences::default().whisper_decoding;
+        assert_eq!(setting, WhisperDecodeSetting::Preset { preset: WhisperDecodePreset::Standard });
+        assert_eq!(setting.options_for(&ModelVariant::Base), WhisperDecodeOptions::default());
+
+        let balanced = WhisperDecodeSetting::Preset { preset: WhisperDecodePreset::Balanced };
+        assert_eq!(balanced.options_for(&ModelVariant::Base).beam_size, 5);
+        assert_eq!(balanced.options_for(&ModelVariant::LargeV3), WhisperDecodeOptions::default());
+
+        let fast = WhisperDecodeOptions::preset(WhisperDecodePreset::Fast, &ModelVariant::Tiny);
+        assert_eq!(fast.beam_size, 1);
+        assert_eq!(fast.temperature_increment, 0.0);
+        let accurate = WhisperDecodeOptions::preset(WhisperDecodePreset::Accurate, &ModelVariant::Medium);
+        assert!(accurate.beam_size > 1 && accurate.suppress_non_speech);
+
+        let custom = WhisperDecodeSetting::Custom {
+            options: WhisperDecodeOptions {
+                threads: 4,
+                ..fast.clone()
+            },
+        };
+        assert_eq!(custom.options_for(&ModelVariant::Base).threads, 4);
+    }
+
+    #[test]
+    fn test_whisper_decoding_validation() {
+        assert!(validate_whisper_decoding(&WhisperDecodeSetting::default()).is_ok());
+        let custom = |options: WhisperDecodeOptions| WhisperDecodeSetting::Custom { options };
+        assert!(validate_whisper_decoding(&custom(WhisperDecodeOptions::default())).is_ok());
+
+        let invalid = [
+            WhisperDecodeOptions { beam_size: 0, ..Default::default() },
+            WhisperDecodeOptions { beam_size: 17, ..Default::default() },
+            WhisperDecodeOptions { temperature: -0.1, ..Default::default() },
+            WhisperDecodeOptions { temperature_increment: 2.0, ..Default::default() },
+            WhisperDecodeOptions { no_speech_threshold: 1.0, ..Default::default() },
+            WhisperDecodeOptions { threads: 65, ..Default::default() },
+        ];
+        for options in invalid {
+            assert!(validate_whisper_decoding(&custom(options.clone())).is_err(), "{:?} accepted", options);
+        }
+
+        let setting: WhisperDecodeSetting =
+            serde_json::from_value(serde_json::json!({ "type": "Custom", "options": { "beamSize": 4 } })).unwrap();
+        assert_eq!(setting.options_for(&ModelVariant::Small).beam_size, 4);
+        assert_eq!(setting.options_for(&ModelVariant::Small).no_speech_threshold, 0.6);
+    }
+
+    fn model(languages: &[&str]) -> TranscriptionModel {
+        serde_json::from_value(serde_json::json!({
+            "id": "test-model",
+            "name": "Test Model",
+            "modelFamily": "Whisper",
+            "variant": "Small",
+            "sizeBytes": 0,
+            "languages": languages,
+            "quantization": null,
+            "downloadStatus": { "status": "NotDownloaded" },
+            "huggingfaceRepo": "",
+            "huggingfaceFilenames": []
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_spoken_language_defaults_to_english() {
+        let prefs: UserPreferences = serde_json::from_value(serde_json::to_value(UserPreferences::default()).unwrap()).unwrap();
+        assert_eq!(prefs.spoken_language, SpokenLanguage::Fixed { code: "en".to_string() });
+
+        let language: SpokenLanguage = serde_json::from_value(serde_json::json!({ "type": "Auto" })).unwrap();
+        assert_eq!(language, SpokenLanguage::Auto);
+        let language: SpokenLanguage =
+            serde_json::from_value(serde_json::json!({ "type": "Fixed", "code": "de" })).unwrap();
+        assert_eq!(language, SpokenLanguage::Fixed { code: "de".to_string() });
+    }
+
+    #[test]
+    fn test_spoken_language_validated_against_model() {
+        let multilingual = model(&["en", "de", "fr"]);
+        let english = model(&["en"]);
+        let german = SpokenLanguage::Fixed { code: "de".to_string() };
+
+        assert!(validate_spoken_language(&german, &multilingual).is_ok());
+        assert!(validate_spoken_language(&SpokenLanguage::Auto, &multilingual).is_ok());
+        assert!(validate_spoken_language(&SpokenLanguage::default(), &english).is_ok());
+
+        let err = validate_spoken_language(&german, &english).unwrap_err();
+        assert!(err.contains("'de'"), "got: {}", err);
+        assert!(validate_spoken_language(&SpokenLanguage::Auto, &english).is_err());
+    }
+
+    #[test]
+    fn test_translation_needs_multilingual_whisper() {
+        assert!(validate_translation(&model(&["en", "de", "fr"])).is_ok());
+
+        let err = validate_translation(&model(&["en"])).unwrap_err();
+        assert!(err.contains("English-only"), "got: {}", err);
+
+        let mut parakeet = model(&["en", "de", "fr"]);
+        parakeet.model_family = "Parakeet".to_string();
+        let err = validate_translation(&parakeet).unwrap_err();
+        assert!(err.contains("can't translate"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_hotkey_translate_defaults_off() {
+        let binding: HotkeyBinding = serde_json::from_value(serde_json::json!({
+            "action": "ToggleRecording",
+            "keyCombination": "CommandOrControl+Shift+Space",
+            "enabled": true
+        }))
+        .unwrap();
+        assert!(!binding.translate);
+        assert!(UserPreferences::default().hotkeys.iter().all(|h| !h.translate));
+    }
+
+    #[test]
+    fn test_hands_free_needs_some_speech() {
+        let mut settings = HandsFreeSettings::default();
+        assert!(validate_hands_free_settings(&settings).is_ok());
+        settings.min_speech_ms = 0;
+        assert!(validate_hands_free_settings(&settings).is_err());
+        settings.min_speech_ms = 100;
+        assert!(validate_hands_free_settings(&settings).is_ok());
+    }
+
+    #[test]
+    fn test_live_transcription_defaults_off() {
+        assert!(!UserPreferences::default().live_transcription);
+        let mut saved = serde_json::to_value(UserPreferences::default()).unwrap();
+        saved.as_object_mut().unwrap().remove("liveTranscription");
+        let prefs: UserPreferences = serde_json::from_value(saved).unwrap();
+        assert!(!prefs.live_transcription);
+    }
+
+    #[test]
+    fn test_vocabulary_term_validation() {
+        assert!(validate_vocabulary_term("TalkyTexty").is_ok());
+        assert!(validate_vocabulary_term("Grafana Cloud").is_ok());
+        assert!(validate_vocabulary_term("  ").is_err());
+        assert!(validate_vocabulary_term(&"x".repeat(65)).is_err());
+        assert!(validate_vocabulary_term("null\0byte").is_err());
+    }
+
+    #[test]
+    fn test_vocabulary_is_trimmed_and_deduplicated() {
+        let terms = normalize_vocabulary(vec![
+            " TalkyTexty ".to_string(),
+            "Kubernetes".to_string(),
+            "talkytexty".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(terms, vec!["TalkyTexty".to_string(), "Kubernetes".to_string()]);
+
+        assert!(normalize_vocabulary(vec!["ok".to_string(), "".to_string()]).is_err());
+        let too_many = (0..=MAX_VOCABULARY_TERMS).map(|i| format!("term{}", i)).collect();
+        assert!(normalize_vocabulary(too_many).is_err());
+    }
 }
@@ -41,6 +41,35 @@ pub fn save_preferences(
         .map_err(|e| format!("Failed to write preferences file: {}", e))
 }
 
+pub fn get_vocabulary_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("vocabulary.json")
+}
+
+/// Load the custom vocabulary; empty until the user adds a term.
+pub fn load_vocabulary(app_data_dir: &Path) -> Result<Vec<String>, String> {
+    let path = get_vocabulary_path(app_data_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read vocabulary file: {}", e))?;
+
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse vocabulary: {}", e))
+}
+
+pub fn save_vocabulary(app_data_dir: &Path, terms: &[String]) -> Result<(), String> {
+    fs::create_dir_all(app_data_dir)
+        .map_err(|e| format!("Failed to create preferences directory: {}", e))?;
+
+    let contents = serde_json::to_string_pretty(terms)
+        .map_err(|e| format!("Failed to serialize vocabulary: {}", e))?;
+
+    fs::write(get_vocabulary_path(app_data_dir), contents)
+        .map_err(|e| format!("Failed to write vocabulary file: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,9 +94,11 @@ mod tests {
         let _ = fs::remove_dir_all(&temp_dir);
         fs::create_dir_all(&temp_dir).unwrap();
 
-        let mut prefs = UserPreferences::default();
-        prefs.active_model_id = Some("small-en".to_string());
-        prefs.overlay_opacity = 0.75;
+        let prefs = UserPreferences {
+            active_model_id: Some("small-en".to_string()),
+            overlay_opacity: 0.75,
+            ..Default::default()
+        };
 
         save_preferences(&temp_dir, &prefs).unwrap();
         let loaded = load_preferences(&temp_dir).unwrap();
@@ -77,4 +108,17 @@ mod tests {
 
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_vocabulary_roundtrip() {
+        let temp_dir = std::env::temp_dir().join("ttc_test_vocabulary");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        assert!(load_vocabulary(&temp_dir).unwrap().is_empty());
+        let terms = vec!["TalkyTexty".to_string(), "Grafana Cloud".to_string()];
+        save_vocabulary(&temp_dir, &terms).unwrap();
+        assert_eq!(load_vocabulary(&temp_dir).unwrap(), terms);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -1,13 +1,312 @@
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use parakeet_rs::Transcriber;
+use serde::{Deserialize, Serialize};
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+use super::vocabulary::initial_prompt;
+use super::TranscriptionModel;
+use crate::preferences::{validate_translation, SpokenLanguage, WhisperDecodeOptions, WhisperDecodeSetting};
+
+/// Error message of a transcription that was cancelled before it finished.
+pub const CANCELLED: &str = "Transcription cancelled.";
+
+/// Settings for a single transcription. Engines ignore what they can't honor.
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    pub language: SpokenLanguage,
+    /// Translate the speech into English instead of transcribing it as spoken.
+    pub translate: bool,
+    /// Terms the engine should spell the way they are given.
+    pub vocabulary: Vec<String>,
+    /// Whisper decoding as the user set it up.
+    pub whisper_decoding: WhisperDecodeSetting,
+    /// Decoding parameters for Whisper models: `whisper_decoding` for the
+    /// model that runs the transcription, filled in by [`Self::for_model`].
+    pub whisper: WhisperDecodeOptions,
+    /// Cancellation and progress of the job this transcription belongs to.
+    pub job: JobControl,
+}
+
+impl TranscribeOptions {
+    /// These options for a transcription run by `model`, which has to be
+    /// able to translate if asked to.
+    pub fn for_model(self, model: &TranscriptionModel) -> Result<Self, String> {
+        if self.translate {
+            validate_translation(model)?;
+        }
+        Ok(Self {
+            whisper: self.whisper_decoding.options_for(&model.variant),
+            ..self
+        })
+    }
+}
+
+/// Lets another thread stop a transcription and follow its progress. Clones
+/// share the same job.
+#[derive(Clone, Default)]
+pub struct JobControl {
+    cancelled: Arc<AtomicBool>,
+    on_progress: Option<Arc<dyn Fn(f32) + Send + Sync>>,
+}
+
+impl JobControl {
+    /// A job that reports progress, as a fraction from 0 to 1, to `on_progress`.
+    pub fn with_progress(on_progress: impl Fn(f32) + Send + Sync + 'static) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            on_progress: Some(Arc::new(on_progress)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `Err(CANCELLED)` once the job is cancelled, for engines and loops to
+    /// bail out between chunks of work.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn report_progress(&self, fraction: f32) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Control for one step of this job, whose progress covers `from..to`
+    /// of the whole. Cancelling either cancels both.
+    pub fn step(&self, from: f32, to: f32) -> Self {
+        let on_progress = self.on_progress.clone().map(|on_progress| {
+            Arc::new(move |fraction: f32| on_progress(from + (to - from) * fraction)) as Arc<dyn Fn(f32) + Send + Sync>
+        });
+        Self {
+            cancelled: self.cancelled.clone(),
+            on_progress,
+        }
+    }
+}
+
+impl std::fmt::Debug for JobControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobControl")
+            .field("cancelled", &self.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A word and when it was spoken, relative to the start of the audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedWord {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Lowest probability among the tokens making up the word, if the
+    /// engine reports token probabilities.
+    #[serde(default)]
+    pub probability: Option<f32>,
+}
+
+/// A stretch of a transcript, about a sentence long, with its timing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Word timings; empty when the engine only times whole segments.
+    #[serde(default)]
+    pub words: Vec<TimedWord>,
+}
+
+/// Text produced by an engine.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    pub text: String,
+    /// Language code the audio was transcribed as, if the engine reports it.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Timed segments of the text; empty when the engine doesn't report timing.
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl TranscriptSegment {
+    /// Mean probability of the segment's words, if the engine reports them.
+    pub fn confidence(&self) -> Option<f32> {
+        mean_probability(&self.words)
+    }
+}
+
+impl Transcript {
+    /// Mean probability of all words in the transcript, if the engine
+    /// reports them.
+    pub fn confidence(&self) -> Option<f32> {
+        mean_probability(self.segments.iter().flat_map(|segment| &segment.words))
+    }
+
+    /// Move all timings by `offset_ms`, e.g. to make a transcript of a slice
+    /// of a recording relative to the whole recording. Timings that would
+    /// fall before the start are clamped to zero.
+    pub fn shifted(mut self, offset_ms: i64) -> Self {
+        let shift = |ms: &mut u64| *ms = ms.saturating_add_signed(offset_ms);
+        for segment in &mut self.segments {
+            shift(&mut segment.start_ms);
+            shift(&mut segment.end_ms);
+            for word in &mut segment.words {
+                shift(&mut word.start_ms);
+                shift(&mut word.end_ms);
+            }
+        }
+        self
+    }
+
+    /// Join transcripts of consecutive stretches of audio, each given with
+    /// the offset of its audio in milliseconds.
+    pub fn concat(parts: impl IntoIterator<Item = (u64, Transcript)>) -> Self {
+        let parts: Vec<Transcript> = parts
+            .into_iter()
+            .map(|(offset_ms, transcript)| transcript.shifted(offset_ms as i64))
+            .filter(|transcript| !transcript.text.trim().is_empty())
+            .collect();
+        Self {
+            text: parts.iter().map(|t| t.text.trim()).collect::<Vec<_>>().join(" "),
+            language: dominant_language(parts.iter().map(|t| (t.text.as_str(), t.language.as_deref()))),
+            segments: parts.into_iter().flat_map(|t| t.segments).collect(),
+        }
+    }
+}
+
 /// Domain trait for speech-to-text engines.
 /// All engines accept 16kHz mono f32 audio and return transcribed text.
 pub trait TranscriptionEngine: Send + Sync {
     fn transcribe(&self, audio: &[f32]) -> Result<String, String>;
+
+    /// Whether the engine spells `TranscribeOptions::vocabulary` terms itself;
+    /// the output of other engines is corrected afterwards.
+    fn supports_vocabulary(&self) -> bool {
+        false
+    }
+
+    /// Whether transcripts carry word probabilities, and so a confidence.
+    /// parakeet-rs doesn't expose token scores, so Parakeet transcripts never do.
+    fn reports_confidence(&self) -> bool {
+        false
+    }
+
+    /// Transcribe with per-call options, reporting the language when known.
+    fn transcribe_with(&self, audio: &[f32], _options: &TranscribeOptions) -> Result<Transcript, String> {
+        Ok(Transcript {
+            text: self.transcribe(audio)?,
+            ..Default::default()
+        })
+    }
+}
+
+fn mean_probability<'a>(words: impl IntoIterator<Item = &'a TimedWord>) -> Option<f32> {
+    let (sum, count) = words
+        .into_iter()
+        .filter_map(|word| word.probability)
+        .fold((0.0, 0), |(sum, count), p| (sum + p, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Group sub-word tokens into words. A token starting with a space (or the
+/// SentencePiece "▁" marker) begins a new word; any other token, punctuation
+/// included, continues the current one.
+pub fn group_words(tokens: impl IntoIterator<Item = TimedWord>) -> Vec<TimedWord> {
+    let mut words: Vec<TimedWord> = Vec::new();
+    for token in tokens {
+        let starts_word = token.text.starts_with([' ', '▁']);
+        let text = token.text.trim_start_matches([' ', '▁']).trim_end();
+        if text.is_empty() {
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if !starts_word => {
+                word.text.push_str(text);
+                word.end_ms = token.end_ms;
+                word.probability = match (word.probability, token.probability) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => words.push(TimedWord {
+                text: text.to_string(),
+                ..token
+            }),
+        }
+    }
+    words
+}
+
+/// Split words into segments at sentence-ending punctuation.
+pub fn group_sentences(words: Vec<TimedWord>) -> Vec<TranscriptSegment> {
+    let mut segments = Vec::new();
+    let mut current: Vec<TimedWord> = Vec::new();
+    for word in words {
+        let ends_sentence = word.text.ends_with(['.', '?', '!']);
+        current.push(word);
+        if ends_sentence {
+            segments.push(segment_of(std::mem::take(&mut current)));
+        }
+    }
+    if !current.is_empty() {
+        segments.push(segment_of(current));
+    }
+    segments
+}
+
+fn segment_of(words: Vec<TimedWord>) -> TranscriptSegment {
+    TranscriptSegment {
+        text: words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+        start_ms: words.first().map_or(0, |w| w.start_ms),
+        end_ms: words.last().map_or(0, |w| w.end_ms),
+        words,
+    }
+}
+
+/// Duration of 16kHz audio in milliseconds.
+pub fn samples_to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / 16000
+}
+
+/// Number of 16kHz samples in `ms` milliseconds.
+pub fn ms_to_samples(ms: u64) -> usize {
+    (ms * 16000 / 1000) as usize
+}
+
+/// The language most of the text was transcribed as, weighting each part by
+/// its length. Parts without a language don't count.
+pub fn dominant_language<'a>(parts: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> Option<String> {
+    let mut totals: Vec<(&str, usize)> = Vec::new();
+    for (text, language) in parts {
+        let Some(language) = language else { continue };
+        let len = text.trim().chars().count();
+        match totals.iter_mut().find(|(l, _)| *l == language) {
+            Some((_, total)) => *total += len,
+            None => totals.push((language, len)),
+        }
+    }
+    // First seen wins a tie
+    totals
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, total)| *total)
+        .map(|(language, _)| language.to_string())
 }
 
 /// Thread-safe wrapper around WhisperContext.
@@ -36,49 +335,132 @@ impl WhisperEngine {
 
     /// Transcribe 16kHz mono f32 audio samples to text.
     pub fn transcribe_audio(&self, audio: &[f32]) -> Result<String, String> {
+        self.transcribe_audio_with(audio, &TranscribeOptions::default())
+            .map(|transcript| transcript.text)
+    }
+
+    /// Transcribe 16kHz mono f32 audio samples, reporting the language Whisper
+    /// decoded them as (the detected one when auto-detecting).
+    pub fn transcribe_audio_with(&self, audio: &[f32], options: &TranscribeOptions) -> Result<Transcript, String> {
         if audio.is_empty() {
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
 
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        params.set_language(Some("en"));
+        let decode = &options.whisper;
+        let strategy = if decode.beam_size > 1 {
+            SamplingStrategy::BeamSearch {
+                beam_size: decode.beam_size as i32,
+                patience: -1.0,
+            }
+        } else {
+            SamplingStrategy::Greedy { best_of: 1 }
+        };
+        let mut params = FullParams::new(strategy);
+        if decode.threads > 0 {
+            params.set_n_threads(decode.threads as i32);
+        }
+        params.set_temperature(decode.temperature);
+        params.set_temperature_inc(decode.temperature_increment);
+        params.set_no_speech_thold(decode.no_speech_threshold);
+        params.set_suppress_nst(decode.suppress_non_speech);
+        match &options.language {
+            SpokenLanguage::Auto => params.set_language(Some("auto")),
+            SpokenLanguage::Fixed { code } => params.set_language(Some(code.as_str())),
+        }
+        params.set_translate(options.translate);
+        let prompt = initial_prompt(&options.vocabulary);
+        if let Some(prompt) = &prompt {
+            params.set_initial_prompt(prompt);
+        }
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
+        params.set_token_timestamps(true);
         params.set_suppress_blank(true);
         params.set_single_segment(false);
         params.set_no_context(true);
+        let job = options.job.clone();
+        params.set_abort_callback_safe(move || job.is_cancelled());
+        let job = options.job.clone();
+        params.set_progress_callback_safe(move |percent: i32| job.report_progress(percent as f32 / 100.0));
 
         let mut state = self
             .ctx
             .create_state()
             .map_err(|e| format!("Failed to create whisper state: {}", e))?;
 
-        state
-            .full(params, audio)
-            .map_err(|e| format!("Transcription failed: {}", e))?;
+        let result = state.full(params, audio);
+        // An aborted run fails or stops early; either way there's nothing to keep
+        options.job.check()?;
+        result.map_err(|e| format!("Transcription failed: {}", e))?;
 
         let num_segments = state.full_n_segments();
 
+        // Token ids from end-of-text on are special tokens and timestamps
+        let first_special = self.ctx.token_eot();
         let mut text = String::new();
+        let mut segments = Vec::new();
         for i in 0..num_segments {
             if let Some(segment) = state.get_segment(i) {
                 let segment_text = segment
                     .to_str_lossy()
                     .map_err(|e| format!("Failed to get segment {} text: {}", i, e))?;
                 text.push_str(&segment_text);
+
+                let tokens = (0..segment.n_tokens()).filter_map(|t| {
+                    let token = segment.get_token(t)?;
+                    let data = token.token_data();
+                    if data.id >= first_special {
+                        return None;
+                    }
+                    Some(TimedWord {
+                        text: token.to_str_lossy().ok()?.into_owned(),
+                        start_ms: centiseconds_to_ms(data.t0),
+                        end_ms: centiseconds_to_ms(data.t1),
+                        probability: Some(data.p),
+                    })
+                });
+                segments.push(TranscriptSegment {
+                    text: segment_text.trim().to_string(),
+                    start_ms: centiseconds_to_ms(segment.start_timestamp()),
+                    end_ms: centiseconds_to_ms(segment.end_timestamp()),
+                    words: group_words(tokens),
+                });
             }
         }
+        segments.retain(|segment| !segment.text.is_empty());
+
+        let language = whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(str::to_string);
 
-        Ok(text.trim().to_string())
+        Ok(Transcript {
+            text: text.trim().to_string(),
+            language,
+            segments,
+        })
     }
 }
 
+fn centiseconds_to_ms(centiseconds: i64) -> u64 {
+    centiseconds.max(0) as u64 * 10
+}
+
 impl TranscriptionEngine for WhisperEngine {
     fn transcribe(&self, audio: &[f32]) -> Result<String, String> {
         self.transcribe_audio(audio)
     }
+
+    fn supports_vocabulary(&self) -> bool {
+        true
+    }
+
+    fn reports_confidence(&self) -> bool {
+        true
+    }
+
+    fn transcribe_with(&self, audio: &[f32], options: &TranscribeOptions) -> Result<Transcript, String> {
+        self.transcribe_audio_with(audio, options)
+    }
 }
 
 /// Parakeet speech-to-text engine wrapping ONNX Runtime models.
@@ -127,9 +509,17 @@ impl ParakeetEngine {
 
 impl TranscriptionEngine for ParakeetEngine {
     fn transcribe(&self, audio: &[f32]) -> Result<String, String> {
+        self.transcribe_with(audio, &TranscribeOptions::default())
+            .map(|transcript| transcript.text)
+    }
+
+    fn transcribe_with(&self, audio: &[f32], options: &TranscribeOptions) -> Result<Transcript, String> {
         if audio.is_empty() {
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
+        // ONNX inference can't be interrupted, so a job cancelled while
+        // Parakeet runs stops at the next check
+        options.job.check()?;
 
         let mut inner = self
             .inner
@@ -155,15 +545,32 @@ impl TranscriptionEngine for ParakeetEngine {
                         .transcribe(chunk, is_last)
                         .map_err(|e| format!("Parakeet EOU transcription failed: {}", e))?;
                     full_text.push_str(&text);
+                    // Stopping halfway would leave the decoder mid-utterance,
+                    // so a cancelled job still runs to the end
+                    options.job.report_progress((i + 1) as f32 / chunks.len() as f32);
                 }
                 // Clean up raw token output:
                 // - Replace SentencePiece word boundary marker (▁ U+2581) with spaces
                 // - Strip <EOU> tokens emitted at utterance boundaries
                 let cleaned = full_text.replace('▁', " ").replace("<EOU>", "");
-                return Ok(cleaned.trim().to_string());
+                // The streaming decoder doesn't report token timing
+                return Ok(Transcript {
+                    text: cleaned.trim().to_string(),
+                    ..Default::default()
+                });
             }
         };
 
-        Ok(result.text.trim().to_string())
+        let words = group_words(result.tokens.into_iter().map(|token| TimedWord {
+            text: token.text,
+            start_ms: (token.start.max(0.0) * 1000.0) as u64,
+            end_ms: (token.end.max(0.0) * 1000.0) as u64,
+            probability: None,
+        }));
+        Ok(Transcript {
+            text: result.text.trim().to_string(),
+            language: None,
+            segments: group_sentences(words),
+        })
     }
 }
@@ -0,0 +1,222 @@
+use std::ops::Range;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::engine::{samples_to_ms, TranscribeOptions, Transcript, TranscriptionEngine};
+use super::streaming::find_quiet_cut;
+use crate::audio::decode::decode_audio_file;
+use crate::audio::resample::resample_to_16khz_with;
+use crate::preferences::ResamplerQuality;
+
+const SAMPLE_RATE: u32 = 16000;
+/// Longest stretch of audio handed to the engine in one call.
+const MAX_SEGMENT_SECS: usize = 30;
+/// Earliest point within a segment where it may be cut.
+const MIN_SEGMENT_SECS: usize = 20;
+
+/// Which part of a file transcription is running.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum FileTranscriptionStage {
+    Decoding,
+    Transcribing,
+}
+
+/// Progress payload for the `file-transcription-progress` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTranscriptionProgress {
+    pub file_name: String,
+    pub stage: FileTranscriptionStage,
+    pub percent: f32,
+}
+
+/// An audio file ready to transcribe.
+#[derive(Debug)]
+pub struct DecodedFile {
+    /// The decoded audio, downmixed and resampled to 16kHz.
+    pub audio_16khz: Vec<f32>,
+    /// Length of the audio in the file.
+    pub audio_duration_ms: u64,
+}
+
+/// Decode an audio file and resample it to 16kHz mono, reporting decoding
+/// progress. Transcribe the result with [`transcribe_segments`].
+pub fn decode_file(
+    path: &Path,
+    resampler_quality: ResamplerQuality,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<DecodedFile, String> {
+    on_progress(0.0);
+    let decoded = decode_audio_file(path, on_progress)?;
+    let audio_duration_ms = decoded.duration_ms();
+    let audio_16khz = resample_to_16khz_with(&decoded.samples, decoded.sample_rate, resampler_quality)?;
+    Ok(DecodedFile {
+        audio_16khz,
+        audio_duration_ms,
+    })
+}
+
+/// Transcribe 16kHz audio segment by segment and join the transcripts.
+///
+/// Long files are transcribed in segments cut at quiet points, which keeps
+/// memory use bounded inside the engine and lets progress be reported.
+pub fn transcribe_segments(
+    engine: &dyn TranscriptionEngine,
+    audio: &[f32],
+    options: &TranscribeOptions,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<Transcript, String> {
+    let mut parts = Vec::new();
+    for range in split_segments(audio) {
+        options.job.check()?;
+        let (start, end) = (range.start, range.end);
+        let (from, to) = (start as f32 / audio.len() as f32, end as f32 / audio.len() as f32);
+        let segment_options = TranscribeOptions {
+            job: options.job.step(from, to),
+            ..options.clone()
+        };
+        let transcript = engine.transcribe_with(&audio[range], &segment_options)?;
+        parts.push((samples_to_ms(start), transcript));
+        on_progress(to);
+    }
+    Ok(Transcript::concat(parts))
+}
+
+/// Split 16kHz audio into segments of at most [`MAX_SEGMENT_SECS`], cutting at
+/// the quietest point after [`MIN_SEGMENT_SECS`].
+pub fn split_segments(audio: &[f32]) -> Vec<Range<usize>> {
+    let rate = SAMPLE_RATE as usize;
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while audio.len() - start > MAX_SEGMENT_SECS * rate {
+        let window = &audio[start..start + MAX_SEGMENT_SECS * rate];
+        let cut = find_quiet_cut(window, SAMPLE_RATE, MIN_SEGMENT_SECS * rate);
+        segments.push(start..start + cut);
+        start += cut;
+    }
+    if start < audio.len() {
+        segments.push(start..audio.len());
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::TranscriptSegment;
+    use std::sync::Mutex;
+
+    struct RecordingEngine {
+        calls: Mutex<Vec<usize>>,
+    }
+
+    impl TranscriptionEngine for RecordingEngine {
+        fn transcribe(&self, audio: &[f32]) -> Result<String, String> {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(audio.len());
+            Ok(format!(" part{} ", calls.len()))
+        }
+    }
+
+    /// Reports one segment covering the middle of whatever it is given.
+    struct TimingEngine;
+
+    impl TranscriptionEngine for TimingEngine {
+        fn transcribe(&self, _audio: &[f32]) -> Result<String, String> {
+            Ok("words".to_string())
+        }
+
+        fn transcribe_with(&self, audio: &[f32], _options: &TranscribeOptions) -> Result<Transcript, String> {
+            let len_ms = samples_to_ms(audio.len());
+            Ok(Transcript {
+                text: "words".to_string(),
+                language: None,
+                segments: vec![TranscriptSegment {
+                    text: "words".to_string(),
+                    start_ms: len_ms / 4,
+                    end_ms: len_ms * 3 / 4,
+                    words: Vec::new(),
+                }],
+            })
+        }
+    }
+
+    #[test]
+    fn test_short_audio_is_one_segment() {
+        let audio = vec![0.1; 16000 * 5];
+        assert_eq!(split_segments(&audio), vec![0..audio.len()]);
+    }
+
+    #[test]
+    fn test_empty_audio_has_no_segments() {
+        assert!(split_segments(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_long_audio_is_cut_at_gap() {
+        // 25s of signal, 1s gap, 25s of signal
+        let mut audio = vec![0.3; 16000 * 25];
+        audio.extend(vec![0.0; 16000]);
+        audio.extend(vec![0.3; 16000 * 25]);
+
+        let segments = split_segments(&audio);
+        assert_eq!(segments.len(), 2);
+        let cut = segments[0].end;
+        assert!(
+            (16000 * 25..16000 * 26).contains(&cut),
+            "Expected cut inside the gap, got {}",
+            cut
+        );
+        assert_eq!(segments[1], cut..audio.len());
+    }
+
+    #[test]
+    fn test_segments_cover_audio_contiguously() {
+        let audio = vec![0.2; 16000 * 95];
+        let segments = split_segments(&audio);
+        assert!(segments.len() >= 4);
+        assert_eq!(segments[0].start, 0);
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        assert_eq!(segments.last().unwrap().end, audio.len());
+        assert!(segments.iter().all(|r| r.len() <= 16000 * MAX_SEGMENT_SECS));
+    }
+
+    #[test]
+    fn test_transcribe_segments_joins_text_and_reports_progress() {
+        let engine = RecordingEngine {
+            calls: Mutex::new(Vec::new()),
+        };
+        let audio = vec![0.2; 16000 * 70];
+        let mut progress = Vec::new();
+
+        let transcript =
+            transcribe_segments(&engine, &audio, &TranscribeOptions::default(), &mut |p| progress.push(p)).unwrap();
+
+        let calls = engine.calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls.iter().sum::<usize>(), audio.len());
+        assert_eq!(transcript.text, "part1 part2 part3");
+        assert_eq!(transcript.language, None);
+        assert_eq!(progress.len(), 3);
+        assert_eq!(*progress.last().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_transcribe_segments_times_from_start_of_audio() {
+        let mut audio = vec![0.3; 16000 * 25];
+        audio.extend(vec![0.0; 16000]);
+        audio.extend(vec![0.3; 16000 * 25]);
+        let cut_ms = samples_to_ms(split_segments(&audio)[0].end);
+
+        let transcript =
+            transcribe_segments(&TimingEngine, &audio, &TranscribeOptions::default(), &mut |_| {}).unwrap();
+
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].start_ms, cut_ms / 4);
+        let second_len_ms = samples_to_ms(audio.len()) - cut_ms;
+        assert!(transcript.segments[1].start_ms.abs_diff(cut_ms + second_len_ms / 4) <= 1);
+    }
+}
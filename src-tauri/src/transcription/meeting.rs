@@ -0,0 +1,240 @@
+//! Transcripts of calls recorded as two tracks: the microphone (the user)
+//! and the system audio (everyone else on the call).
+
+use serde::{Deserialize, Serialize};
+
+use super::engine::{samples_to_ms, TranscribeOptions, TranscriptSegment, TranscriptionEngine};
+use super::file::split_segments;
+use crate::audio::vad::find_speech_regions;
+
+const SAMPLE_RATE: u32 = 16000;
+/// A pause at least this long ends a turn in the conversation.
+const TURN_GAP_MS: usize = 800;
+
+/// One track of a microphone + system audio recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeetingTrack {
+    Microphone,
+    System,
+}
+
+impl MeetingTrack {
+    /// Speaker label for the track in the combined transcript.
+    pub fn label(self) -> &'static str {
+        match self {
+            MeetingTrack::Microphone => "Me",
+            MeetingTrack::System => "Others",
+        }
+    }
+}
+
+/// Text of one stretch of speech on one track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    pub track: MeetingTrack,
+    /// Offset of the speech within the 16kHz track.
+    pub start: usize,
+    pub text: String,
+    /// Language the speech was transcribed as, if the engine reports it.
+    pub language: Option<String>,
+    /// Timed segments of the text, relative to the start of the track.
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// Transcribe each stretch of speech on a 16kHz track on its own, so the
+/// turns can be interleaved with the other track's.
+pub fn transcribe_track(
+    engine: &dyn TranscriptionEngine,
+    audio: &[f32],
+    track: MeetingTrack,
+    options: &TranscribeOptions,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<Vec<Utterance>, String> {
+    let mut utterances = Vec::new();
+    for region in find_speech_regions(audio, SAMPLE_RATE, TURN_GAP_MS) {
+        let speech = &audio[region.clone()];
+        for segment in split_segments(speech) {
+            options.job.check()?;
+            let start = region.start + segment.start;
+            let segment_options = TranscribeOptions {
+                job: options
+                    .job
+                    .step(start as f32 / audio.len() as f32, (start + segment.len()) as f32 / audio.len() as f32),
+                ..options.clone()
+            };
+            let transcript = engine
+                .transcribe_with(&speech[segment], &segment_options)?
+                .shifted(samples_to_ms(start) as i64);
+            let text = transcript.text.trim();
+            if !text.is_empty() {
+                utterances.push(Utterance {
+                    track,
+                    start,
+                    text: text.to_string(),
+                    language: transcript.language,
+                    segments: transcript.segments,
+                });
+            }
+        }
+        on_progress(region.end as f32 / audio.len() as f32);
+    }
+    Ok(utterances)
+}
+
+/// Interleave utterances from both tracks by start time into one labelled
+/// line per turn. Consecutive utterances from the same track are joined.
+pub fn combine_transcripts(mut utterances: Vec<Utterance>) -> String {
+    utterances.sort_by_key(|u| u.start);
+    let mut turns: Vec<(MeetingTrack, String)> = Vec::new();
+    for utterance in utterances.into_iter().filter(|u| !u.text.is_empty()) {
+        match turns.last_mut() {
+            Some((track, text)) if *track == utterance.track => {
+                text.push(' ');
+                text.push_str(&utterance.text);
+            }
+            _ => turns.push((utterance.track, utterance.text)),
+        }
+    }
+    turns
+        .iter()
+        .map(|(track, text)| format!("{}: {}", track.label(), text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Timed segments of both tracks, in order of start time. The tracks cover
+/// the same stretch of time, so either track's audio can be sought with them.
+pub fn combine_segments(utterances: &[Utterance]) -> Vec<TranscriptSegment> {
+    let mut segments: Vec<TranscriptSegment> = utterances
+        .iter()
+        .flat_map(|utterance| utterance.segments.iter().cloned())
+        .collect();
+    segments.sort_by_key(|segment| segment.start_ms);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Numbers its results in call order.
+    struct CountingEngine {
+        calls: Mutex<usize>,
+    }
+
+    impl TranscriptionEngine for CountingEngine {
+        fn transcribe(&self, _audio: &[f32]) -> Result<String, String> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            Ok(format!(" turn{} ", calls))
+        }
+    }
+
+    fn tone(secs: f32) -> Vec<f32> {
+        (0..(secs * SAMPLE_RATE as f32) as usize)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence(secs: f32) -> Vec<f32> {
+        vec![0.001; (secs * SAMPLE_RATE as f32) as usize]
+    }
+
+    fn utterance(track: MeetingTrack, start_secs: usize, text: &str) -> Utterance {
+        Utterance {
+            track,
+            start: start_secs * SAMPLE_RATE as usize,
+            text: text.to_string(),
+            language: None,
+            segments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_track_is_transcribed_turn_by_turn() {
+        let engine = CountingEngine {
+            calls: Mutex::new(0),
+        };
+        let mut audio = silence(1.0);
+        audio.extend(tone(1.0));
+        audio.extend(silence(2.0));
+        audio.extend(tone(1.5));
+        audio.extend(silence(1.0));
+        let mut progress = Vec::new();
+
+        let utterances = transcribe_track(
+            &engine,
+            &audio,
+            MeetingTrack::System,
+            &TranscribeOptions::default(),
+            &mut |p| progress.push(p),
+        )
+        .unwrap();
+
+        assert_eq!(utterances.len(), 2);
+        assert_eq!(utterances[0].text, "turn1");
+        assert_eq!(utterances[0].track, MeetingTrack::System);
+        let rate = SAMPLE_RATE as usize;
+        assert!(utterances[0].start.abs_diff(rate * 3 / 4) < rate / 50);
+        assert!(utterances[1].start.abs_diff(rate * 15 / 4) < rate / 50);
+        assert_eq!(progress.len(), 2);
+    }
+
+    #[test]
+    fn test_silent_track_has_no_utterances() {
+        let engine = CountingEngine {
+            calls: Mutex::new(0),
+        };
+        let utterances = transcribe_track(
+            &engine,
+            &silence(3.0),
+            MeetingTrack::Microphone,
+            &TranscribeOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(utterances.is_empty());
+        assert_eq!(*engine.calls.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_tracks_interleave_by_start_time() {
+        let text = combine_transcripts(vec![
+            utterance(MeetingTrack::Microphone, 0, "Hi, can you hear me?"),
+            utterance(MeetingTrack::Microphone, 12, "Great."),
+            utterance(MeetingTrack::Microphone, 14, "Let's start."),
+            utterance(MeetingTrack::System, 3, "Yes, loud and clear."),
+            utterance(MeetingTrack::System, 20, ""),
+        ]);
+        assert_eq!(
+            text,
+            "Me: Hi, can you hear me?\nOthers: Yes, loud and clear.\nMe: Great. Let's start."
+        );
+    }
+
+    #[test]
+    fn test_segments_interleave_by_start_time() {
+        let timed = |track, start_secs: usize, text: &str| Utterance {
+            segments: vec![TranscriptSegment {
+                text: text.to_string(),
+                start_ms: start_secs as u64 * 1000,
+                end_ms: start_secs as u64 * 1000 + 800,
+                words: Vec::new(),
+            }],
+            ..utterance(track, start_secs, text)
+        };
+        let segments = combine_segments(&[
+            timed(MeetingTrack::Microphone, 0, "Hi."),
+            timed(MeetingTrack::Microphone, 12, "Great."),
+            timed(MeetingTrack::System, 3, "Hello."),
+        ]);
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hi.", "Hello.", "Great."]);
+    }
+
+    #[test]
+    fn test_combine_empty() {
+        assert_eq!(combine_transcripts(Vec::new()), "");
+    }
+}
@@ -1,6 +1,11 @@
 pub mod engine;
+pub mod file;
+pub mod meeting;
 pub mod models;
 pub mod postprocess;
+pub mod streaming;
+pub mod vocabulary;
+pub mod worker;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
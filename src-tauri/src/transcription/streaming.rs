@@ -0,0 +1,380 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::engine::{ms_to_samples, samples_to_ms, Transcript};
+use crate::audio::capture::{calculate_rms, CaptureTap};
+use crate::audio::dsp::ProcessingChain;
+use crate::audio::resample::resample_to_16khz_with;
+use crate::preferences::ResamplerQuality;
+
+/// How often the live worker looks at newly captured audio.
+const PARTIAL_INTERVAL: Duration = Duration::from_millis(1000);
+/// Minimum uncommitted audio before a tentative partial is worth decoding.
+const MIN_PARTIAL_SECS: usize = 1;
+/// Uncommitted audio length at which a segment is committed.
+const COMMIT_AFTER_SECS: usize = 10;
+/// Earliest point in the uncommitted audio where a segment may be cut.
+const MIN_SEGMENT_SECS: usize = 5;
+
+/// Audio and text that the live worker has already decoded and will not revisit.
+#[derive(Debug, Default)]
+pub struct StreamingProgress {
+    /// Number of samples (at the capture rate) covered by committed segments.
+    pub committed_samples: usize,
+    /// Committed audio, resampled to 16kHz.
+    pub committed_audio: Vec<f32>,
+    /// Transcript of each committed segment, in order, timed from the start
+    /// of the recording.
+    pub committed: Vec<Transcript>,
+}
+
+impl StreamingProgress {
+    /// Record a decoded segment as committed.
+    pub fn commit(&mut self, native_len: usize, audio_16khz: Vec<f32>, transcript: Transcript) {
+        let offset_ms = samples_to_ms(self.committed_audio.len());
+        self.committed_samples += native_len;
+        self.committed_audio.extend(audio_16khz);
+        self.committed.push(transcript.shifted(offset_ms as i64));
+    }
+
+    /// Join committed segment text with an optional tentative tail.
+    pub fn text_with(&self, tail: &str) -> String {
+        self.committed
+            .iter()
+            .map(|t| t.text.as_str())
+            .chain(std::iter::once(tail))
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Part of the 16kHz recording to keep, given the speech bounds found in
+    /// it, or `None` when there is no speech at all. Committed segments are
+    /// already transcribed, so the range never cuts into them: it starts no
+    /// later than their first speech and ends no earlier than their end.
+    pub fn speech_range(&self, bounds: Option<Range<usize>>) -> Option<Range<usize>> {
+        let committed_len = self.committed_audio.len();
+        // Engines that don't time their output leave the committed audio untrimmed
+        let committed_start = self
+            .committed
+            .iter()
+            .find(|transcript| !transcript.text.trim().is_empty())
+            .map(|transcript| {
+                transcript
+                    .segments
+                    .first()
+                    .map_or(0, |segment| ms_to_samples(segment.start_ms).min(committed_len))
+            });
+        match (bounds, committed_start) {
+            (Some(bounds), Some(start)) => Some(bounds.start.min(start)..bounds.end.max(committed_len)),
+            (Some(bounds), None) => Some(bounds.start..bounds.end.max(committed_len)),
+            (None, Some(start)) => Some(start..committed_len),
+            (None, None) => None,
+        }
+    }
+
+    /// The whole transcript: committed segments followed by `tail`, the
+    /// transcript of audio starting `tail_offset_ms` into the recording.
+    pub fn transcript_with(&self, tail_offset_ms: u64, tail: Transcript) -> Transcript {
+        Transcript::concat(
+            self.committed
+                .iter()
+                .cloned()
+                .map(|transcript| (0, transcript))
+                .chain(std::iter::once((tail_offset_ms, tail))),
+        )
+    }
+}
+
+/// Find a low-energy point to split a segment, searching from `min_offset` onward.
+/// Returns the center of the quietest 100ms window, or `samples.len()` if there
+/// is no room to search.
+pub fn find_quiet_cut(samples: &[f32], sample_rate: u32, min_offset: usize) -> usize {
+    let window = (sample_rate as usize / 10).max(1);
+    if min_offset + window > samples.len() {
+        return samples.len();
+    }
+
+    let mut best = (f32::MAX, min_offset);
+    let mut start = min_offset;
+    while start + window <= samples.len() {
+        let rms = calculate_rms(&samples[start..start + window]);
+        if rms < best.0 {
+            best = (rms, start);
+        }
+        start += window / 2;
+    }
+    best.1 + window / 2
+}
+
+/// Resample captured audio to 16kHz and run the processing chain over it.
+///
+/// The chain adapts to the audio it sees (noise profile, gain), so each
+/// committed segment and the final tail are processed on their own; the
+/// saved recording is then exactly the audio that was transcribed.
+pub fn prepare_segment(
+    samples: &[f32],
+    sample_rate: u32,
+    quality: ResamplerQuality,
+    chain: ProcessingChain,
+) -> Result<Vec<f32>, String> {
+    let mut audio = resample_to_16khz_with(samples, sample_rate, quality)?;
+    chain.process(&mut audio, 16000);
+    Ok(audio)
+}
+
+/// Speech-to-text function used by the live worker (16kHz mono in, text out).
+pub type TranscribeFn = Box<dyn Fn(&[f32]) -> Result<Transcript, String> + Send + 'static>;
+
+/// Callback receiving the best text available so far.
+pub type PartialCallback = Box<dyn Fn(String) + Send + 'static>;
+
+/// Background worker that transcribes a capture session while it is recording.
+///
+/// Long recordings are committed in segments cut at quiet points so that, on
+/// stop, only the audio after the last commit still needs to be decoded.
+pub struct LiveTranscription {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<StreamingProgress>,
+}
+
+impl LiveTranscription {
+    pub fn start(
+        tap: CaptureTap,
+        quality: ResamplerQuality,
+        chain: ProcessingChain,
+        transcribe: TranscribeFn,
+        on_partial: PartialCallback,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = Arc::clone(&stop);
+        let handle =
+            std::thread::spawn(move || run(stop_clone, tap, quality, chain, transcribe, on_partial));
+        Self { stop, handle }
+    }
+
+    /// Stop the worker and return everything it committed.
+    /// Waits for an in-flight segment so its work is not thrown away.
+    pub fn finish(self) -> StreamingProgress {
+        self.stop.store(true, Ordering::Release);
+        self.handle.thread().unpark();
+        self.handle.join().unwrap_or_default()
+    }
+
+    /// Stop the worker without waiting for it.
+    pub fn cancel(self) {
+        self.stop.store(true, Ordering::Release);
+        self.handle.thread().unpark();
+    }
+}
+
+fn run(
+    stop: Arc<AtomicBool>,
+    tap: CaptureTap,
+    quality: ResamplerQuality,
+    chain: ProcessingChain,
+    transcribe: TranscribeFn,
+    on_partial: PartialCallback,
+) -> StreamingProgress {
+    let sample_rate = tap.sample_rate();
+    let rate = sample_rate as usize;
+    let mut progress = StreamingProgress::default();
+    let mut last_pending_len = 0;
+
+    loop {
+        std::thread::park_timeout(PARTIAL_INTERVAL);
+        if stop.load(Ordering::Acquire) {
+            break;
+        }
+
+        let pending = tap.samples_since(progress.committed_samples);
+        if pending.len() < MIN_PARTIAL_SECS * rate || pending.len() == last_pending_len {
+            continue;
+        }
+        last_pending_len = pending.len();
+
+        if pending.len() >= COMMIT_AFTER_SECS * rate {
+            let cut = find_quiet_cut(&pending, sample_rate, MIN_SEGMENT_SECS * rate);
+            let segment = match prepare_segment(&pending[..cut], sample_rate, quality, chain) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("[live_transcription] Resampling failed: {}", e);
+                    continue;
+                }
+            };
+            match transcribe(&segment) {
+                Ok(transcript) => {
+                    progress.commit(cut, segment, transcript);
+                    last_pending_len = 0;
+                    on_partial(progress.text_with(""));
+                }
+                Err(e) => eprintln!("[live_transcription] Segment transcription failed: {}", e),
+            }
+        } else {
+            let audio = match prepare_segment(&pending, sample_rate, quality, chain) {
+                Ok(a) => a,
+                Err(e) => {
+                    eprintln!("[live_transcription] Resampling failed: {}", e);
+                    continue;
+                }
+            };
+            // The stop path decodes the tail itself, so a late tentative result is useless
+            if stop.load(Ordering::Acquire) {
+                break;
+            }
+            match transcribe(&audio) {
+                Ok(transcript) => on_partial(progress.text_with(&transcript.text)),
+                Err(e) => eprintln!("[live_transcription] Partial transcription failed: {}", e),
+            }
+        }
+    }
+
+    progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::engine::TranscriptSegment;
+
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_quiet_cut_finds_gap() {
+        let mut audio = tone(16000);
+        audio.extend(vec![0.0; 3200]);
+        audio.extend(tone(16000));
+
+        let cut = find_quiet_cut(&audio, 16000, 8000);
+        assert!(
+            (16000..19200).contains(&cut),
+            "Expected cut inside the silent gap, got {}",
+            cut
+        );
+    }
+
+    #[test]
+    fn test_quiet_cut_respects_min_offset() {
+        let mut audio = vec![0.0; 3200];
+        audio.extend(tone(32000));
+
+        let cut = find_quiet_cut(&audio, 16000, 16000);
+        assert!(cut >= 16000);
+    }
+
+    #[test]
+    fn test_quiet_cut_short_input_returns_len() {
+        let audio = tone(1000);
+        assert_eq!(find_quiet_cut(&audio, 16000, 900), audio.len());
+    }
+
+    fn transcript(text: &str, language: Option<&str>) -> Transcript {
+        Transcript {
+            text: text.to_string(),
+            language: language.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_progress_joins_committed_and_tail() {
+        let mut progress = StreamingProgress::default();
+        progress.commit(48000, vec![0.0; 16000], transcript(" Hello there.", None));
+        progress.commit(48000, vec![0.0; 16000], transcript("How are you?", None));
+
+        assert_eq!(progress.committed_samples, 96000);
+        assert_eq!(progress.committed_audio.len(), 32000);
+        assert_eq!(progress.text_with(" I'm"), "Hello there. How are you? I'm");
+        assert_eq!(progress.text_with(""), "Hello there. How are you?");
+    }
+
+    #[test]
+    fn test_progress_language_covers_committed_and_tail() {
+        let mut progress = StreamingProgress::default();
+        progress.commit(48000, vec![0.0; 16000], transcript("Guten Morgen, ich wollte kurz", Some("de")));
+        progress.commit(48000, vec![0.0; 16000], transcript("Okay", Some("en")));
+
+        let whole = progress.transcript_with(2000, Transcript::default());
+        assert_eq!(whole.language.as_deref(), Some("de"));
+        let tail = transcript("so that is settled then, see you all tomorrow", Some("en"));
+        assert_eq!(progress.transcript_with(2000, tail).language.as_deref(), Some("en"));
+    }
+
+    fn timed(text: &str, start_ms: u64, end_ms: u64) -> Transcript {
+        Transcript {
+            text: text.to_string(),
+            segments: vec![TranscriptSegment {
+                text: text.to_string(),
+                start_ms,
+                end_ms,
+                words: Vec::new(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_speech_range_keeps_committed_speech() {
+        let mut progress = StreamingProgress::default();
+        assert_eq!(progress.speech_range(None), None);
+        assert_eq!(progress.speech_range(Some(8000..24000)), Some(8000..24000));
+
+        // Leading silence before committed speech starting at 1.5s
+        progress.commit(48000 * 6, vec![0.0; 16000 * 6], timed("First.", 1500, 5800));
+        assert_eq!(progress.speech_range(Some(40_000..120_000)), Some(24_000..120_000));
+        assert_eq!(progress.speech_range(Some(8000..60_000)), Some(8000..96_000));
+        assert_eq!(progress.speech_range(None), Some(24_000..96_000));
+
+        // Seek positions stay inside the saved audio after the front is cut
+        let range = progress.speech_range(Some(40_000..120_000)).unwrap();
+        let whole = progress
+            .transcript_with(6000, timed("Second.", 0, 1000))
+            .shifted(-(samples_to_ms(range.start) as i64));
+        let starts: Vec<u64> = whole.segments.iter().map(|s| s.start_ms).collect();
+        assert_eq!(starts, vec![0, 4500]);
+
+        // Without timing, committed audio is kept from the start
+        let mut untimed = StreamingProgress::default();
+        untimed.commit(48000, vec![0.0; 16000], transcript("Hello.", None));
+        assert_eq!(untimed.speech_range(Some(8000..24000)), Some(0..24000));
+    }
+
+    #[test]
+    fn test_prepared_segment_is_what_gets_committed() {
+        let quiet: Vec<f32> = tone(16000).iter().map(|s| s * 0.05).collect();
+        let chain = ProcessingChain {
+            auto_gain: true,
+            ..Default::default()
+        };
+        let segment = prepare_segment(&quiet, 16000, ResamplerQuality::default(), chain).unwrap();
+        assert!(calculate_rms(&segment) > calculate_rms(&quiet) * 2.0);
+
+        let mut progress = StreamingProgress::default();
+        progress.commit(quiet.len(), segment.clone(), Transcript::default());
+        assert_eq!(progress.committed_audio, segment);
+
+        let untouched = prepare_segment(&quiet, 16000, ResamplerQuality::default(), ProcessingChain::default()).unwrap();
+        assert_eq!(untouched, quiet);
+    }
+
+    #[test]
+    fn test_progress_times_segments_from_recording_start() {
+        let mut progress = StreamingProgress::default();
+        progress.commit(48000, vec![0.0; 16000 * 10], timed("First.", 500, 9000));
+        progress.commit(48000, vec![0.0; 16000 * 8], timed("Second.", 0, 7000));
+
+        let whole = progress.transcript_with(18_500, timed("Third.", 200, 1200));
+        assert_eq!(whole.text, "First. Second. Third.");
+        let starts: Vec<u64> = whole.segments.iter().map(|s| s.start_ms).collect();
+        assert_eq!(starts, vec![500, 10_000, 18_700]);
+        assert_eq!(whole.segments[2].end_ms, 19_700);
+    }
+}
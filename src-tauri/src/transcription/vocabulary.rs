@@ -0,0 +1,241 @@
+//! User vocabulary: product names, people and identifiers the engines tend
+//! to misspell. Whisper is primed with it through the initial prompt; output
+//! of engines that can't be prompted is corrected afterwards.
+
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Whisper only looks at the last 224 tokens of the prompt; stay well inside.
+const MAX_PROMPT_CHARS: usize = 600;
+/// Terms shorter than this (ignoring case and punctuation) are never snapped.
+const MIN_SNAP_LEN: usize = 4;
+/// Terms shorter than this only snap exact spellings (ignoring case, spacing
+/// and punctuation): one slip in a short word usually makes another real word,
+/// like "nation" for "Notion".
+const EXACT_BELOW: usize = 8;
+/// Possessive and plural endings kept outside the matched word.
+const PLURAL_SUFFIXES: [&str; 3] = ["'s", "\u{2019}s", "s"];
+
+static WORD_RE: OnceLock<Regex> = OnceLock::new();
+
+fn word_re() -> &'static Regex {
+    WORD_RE.get_or_init(|| Regex::new(r"\S+").unwrap())
+}
+
+/// Initial prompt listing the vocabulary, or `None` when it is empty.
+/// Terms that don't fit in the prompt are left out.
+pub fn initial_prompt(terms: &[String]) -> Option<String> {
+    let mut prompt = String::from("Glossary:");
+    let mut included = 0;
+    for term in terms {
+        if prompt.len() + term.len() + 2 > MAX_PROMPT_CHARS {
+            break;
+        }
+        prompt.push_str(if included == 0 { " " } else { ", " });
+        prompt.push_str(term);
+        included += 1;
+    }
+    (included > 0).then(|| prompt + ".")
+}
+
+/// Replace near-miss spellings of vocabulary terms in `text` with the terms
+/// themselves. A term may match a run of one more or one fewer words than it
+/// has, so "Post Gres" snaps to "Postgres" and "Kuber" "netes" to "Kubernetes".
+/// A possessive or plural ending stays on the word: "Kubernetis's" becomes
+/// "Kubernetes's" and "TalkyTexty's" is left alone.
+pub fn snap_to_vocabulary(text: &str, terms: &[String]) -> String {
+    let terms: Vec<(&str, String, usize)> = terms
+        .iter()
+        .map(|term| (term.as_str(), normalize(term), term.split_whitespace().count()))
+        .filter(|(_, normalized, _)| normalized.chars().count() >= MIN_SNAP_LEN)
+        .collect();
+    if terms.is_empty() {
+        return text.to_string();
+    }
+
+    let words: Vec<Range<usize>> = word_re().find_iter(text).map(|m| m.range()).collect();
+    let mut output = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < words.len() {
+        let Some((term, len, plural_len)) = best_match(text, &words[i..], &terms) else {
+            i += 1;
+            continue;
+        };
+        let span = words[i].start..words[i + len - 1].end;
+        let (prefix, core, suffix) = split_punctuation(&text[span.clone()]);
+        let (stem, plural) = core.split_at(core.len() - plural_len);
+        output.push_str(&text[copied..span.start]);
+        if stem == term {
+            output.push_str(&text[span.clone()]);
+        } else {
+            output.push_str(prefix);
+            output.push_str(term);
+            output.push_str(plural);
+            output.push_str(suffix);
+        }
+        copied = span.end;
+        i += len;
+    }
+    output.push_str(&text[copied..]);
+    output
+}
+
+/// The closest term to a run of words starting at `words[0]`, with the
+/// number of words it covers and the byte length of the possessive or plural
+/// ending left off the match.
+fn best_match<'a>(
+    text: &str,
+    words: &[Range<usize>],
+    terms: &[(&'a str, String, usize)],
+) -> Option<(&'a str, usize, usize)> {
+    let mut best: Option<(&str, usize, usize, usize)> = None;
+    for (term, normalized, term_words) in terms {
+        let shortest = term_words.saturating_sub(1).max(1);
+        for len in shortest..=(term_words + 1).min(words.len()) {
+            let (_, core, _) = split_punctuation(&text[words[0].start..words[len - 1].end]);
+            let plurals = PLURAL_SUFFIXES
+                .iter()
+                .filter(|plural| core.len() > plural.len() && core.ends_with(*plural))
+                .map(|plural| plural.len());
+            for plural_len in std::iter::once(0).chain(plurals) {
+                let candidate = normalize(&core[..core.len() - plural_len]);
+                let Some(distance) = snap_distance(&candidate, normalized) else {
+                    continue;
+                };
+                // Prefer closer matches, then longer runs
+                if best.is_none_or(|(_, best_len, _, best_distance)| {
+                    distance < best_distance || (distance == best_distance && len > best_len)
+                }) {
+                    best = Some((term, len, plural_len, distance));
+                }
+            }
+        }
+    }
+    best.map(|(term, len, plural_len, _)| (term, len, plural_len))
+}
+
+/// Edit distance between a candidate and a term, if it is close enough to snap.
+fn snap_distance(candidate: &str, term: &str) -> Option<usize> {
+    let term_len = term.chars().count();
+    let max_edits = if term_len < EXACT_BELOW { 0 } else { term_len / 4 };
+    if candidate.chars().count().abs_diff(term_len) > max_edits {
+        return None;
+    }
+    let distance = levenshtein(candidate, term);
+    (distance <= max_edits).then_some(distance)
+}
+
+/// Lowercase letters and digits only, so spacing, case and punctuation don't count.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Split leading and trailing punctuation off a run of words.
+fn split_punctuation(text: &str) -> (&str, &str, &str) {
+    let start = text.find(|c: char| c.is_alphanumeric()).unwrap_or(text.len());
+    let end = text
+        .rfind(|c: char| c.is_alphanumeric())
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(start)
+        .max(start);
+    (&text[..start], &text[start..end], &text[end..])
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_prompt_lists_terms() {
+        assert_eq!(
+            initial_prompt(&terms(&["TalkyTexty", "Parakeet", "Anneliese"])).as_deref(),
+            Some("Glossary: TalkyTexty, Parakeet, Anneliese.")
+        );
+        assert_eq!(initial_prompt(&[]), None);
+    }
+
+    #[test]
+    fn test_prompt_is_capped() {
+        let many: Vec<String> = (0..200).map(|i| format!("identifier_{}", i)).collect();
+        let prompt = initial_prompt(&many).unwrap();
+        assert!(prompt.len() <= MAX_PROMPT_CHARS + 1);
+        assert!(prompt.starts_with("Glossary: identifier_0, identifier_1,"));
+    }
+
+    #[test]
+    fn test_snaps_near_misses() {
+        let vocabulary = terms(&["TalkyTexty", "Kubernetes", "Postgres", "Anneliese"]);
+        assert_eq!(
+            snap_to_vocabulary("Ask Annelise to deploy talky texty on kubernetis.", &vocabulary),
+            "Ask Anneliese to deploy TalkyTexty on Kubernetes."
+        );
+        assert_eq!(snap_to_vocabulary("We moved to Post Gres, finally", &vocabulary), "We moved to Postgres, finally");
+    }
+
+    #[test]
+    fn test_leaves_unrelated_words() {
+        let vocabulary = terms(&["Slack", "Jira", "API"]);
+        let text = "Put it on the black board and ask Sarah about the app.";
+        assert_eq!(snap_to_vocabulary(text, &vocabulary), text);
+    }
+
+    #[test]
+    fn test_keeps_possessive_and_plural_endings() {
+        let vocabulary = terms(&["TalkyTexty", "Kubernetes", "Slack"]);
+        let text = "TalkyTexty's docs mention two Slacks.";
+        assert_eq!(snap_to_vocabulary(text, &vocabulary), text);
+        assert_eq!(
+            snap_to_vocabulary("talky texty's and kubernetis's config", &vocabulary),
+            "TalkyTexty's and Kubernetes's config"
+        );
+    }
+
+    #[test]
+    fn test_short_terms_need_exact_spelling() {
+        let vocabulary = terms(&["Notion", "Slack"]);
+        let text = "The nation will slacken, so stack it.";
+        assert_eq!(snap_to_vocabulary(text, &vocabulary), text);
+        assert_eq!(snap_to_vocabulary("write it in notion", &vocabulary), "write it in Notion");
+    }
+
+    #[test]
+    fn test_multi_word_terms_and_line_breaks() {
+        let vocabulary = terms(&["Grafana Cloud"]);
+        assert_eq!(
+            snap_to_vocabulary("Me: check grafana cloud\nOthers: grafanna clowd is down", &vocabulary),
+            "Me: check Grafana Cloud\nOthers: Grafana Cloud is down"
+        );
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
@@ -0,0 +1,623 @@
+//! Dedicated thread that owns the loaded engine and runs transcription jobs
+//! one at a time, in the order they were submitted. Commands wait for their
+//! job without holding a lock across inference, and model switches are jobs
+//! too, so they queue behind transcriptions or cancel them.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use super::engine::{JobControl, TranscribeOptions, TranscriptionEngine, CANCELLED};
+use super::TranscriptionModel;
+
+/// Error message of a job that needs a model when none is loaded.
+pub const NO_MODEL: &str = "No model loaded.";
+/// Error message of a job whose worker is gone.
+const WORKER_STOPPED: &str = "Transcription worker stopped.";
+
+/// What a job is for, so listeners can tell a dictation from a file import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum JobKind {
+    /// The recording that was just stopped.
+    Dictation,
+    /// A segment decoded while still recording.
+    LivePreview,
+    /// An imported file or a saved recording being transcribed again.
+    File,
+    ModelLoad,
+}
+
+/// How a model switch treats jobs submitted before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModelSwitch {
+    /// Let them finish on the current model first.
+    #[default]
+    Queue,
+    /// Cancel them, including the one running.
+    Preempt,
+}
+
+/// Lifecycle of a job, emitted as `transcription-job` events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum JobEvent {
+    Queued {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+        kind: JobKind,
+    },
+    Started {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+        kind: JobKind,
+    },
+    /// Sent each time the job gets a whole percent further.
+    Progress {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+        percent: f32,
+    },
+    Finished {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+    },
+    Cancelled {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+    },
+    Failed {
+        #[serde(rename = "jobId")]
+        job_id: u64,
+        message: String,
+    },
+}
+
+type Loader = Arc<dyn Fn() -> Result<Box<dyn TranscriptionEngine>, String> + Send + Sync>;
+
+/// An engine, the model it runs and how to load it again.
+pub struct LoadedEngine {
+    model: TranscriptionModel,
+    engine: Box<dyn TranscriptionEngine>,
+    loader: Loader,
+}
+
+impl LoadedEngine {
+    /// Load `model` with `loader`, which is kept to load the model again if
+    /// a job panics.
+    pub fn load<F>(model: TranscriptionModel, loader: F) -> Result<Self, String>
+    where
+        F: Fn() -> Result<Box<dyn TranscriptionEngine>, String> + Send + Sync + 'static,
+    {
+        Self::load_with(model, Arc::new(loader))
+    }
+
+    fn load_with(model: TranscriptionModel, loader: Loader) -> Result<Self, String> {
+        let engine = catch_panic(|| loader())?;
+        Ok(Self { model, engine, loader })
+    }
+}
+
+/// The loaded model and what its engine can do.
+#[derive(Debug, Clone)]
+pub struct EngineInfo {
+    pub model: TranscriptionModel,
+    pub supports_vocabulary: bool,
+    /// Whether transcripts carry a confidence to hold unsure results by.
+    pub reports_confidence: bool,
+}
+
+impl EngineInfo {
+    fn of(loaded: &LoadedEngine) -> Self {
+        Self {
+            model: loaded.model.clone(),
+            supports_vocabulary: loaded.engine.supports_vocabulary(),
+            reports_confidence: loaded.engine.reports_confidence(),
+        }
+    }
+}
+
+/// The worker's engine; `None` when no model is loaded.
+type EngineSlot = Option<LoadedEngine>;
+
+/// Replace the engine after a job panicked in it, since the panic may have
+/// left it broken. The slot is empty if it can't be reloaded.
+fn reload(slot: &mut EngineSlot, shared: &Shared) {
+    if let Some(LoadedEngine { model, engine, loader }) = slot.take() {
+        // Free the broken engine's memory before loading another
+        drop(engine);
+        match LoadedEngine::load_with(model, loader) {
+            Ok(loaded) => *slot = Some(loaded),
+            Err(e) => log::warn!("Failed to reload the model after a panic: {}", e),
+        }
+    }
+    shared.set_loaded(slot.as_ref());
+}
+
+/// Runs the job with the worker's engine, or with `None` when the job was
+/// cancelled before it started. Returns the error, if any, for the event.
+type Task = Box<dyn FnOnce(Option<&mut EngineSlot>) -> Result<(), String> + Send>;
+
+struct Job {
+    id: u64,
+    kind: JobKind,
+    control: JobControl,
+    task: Task,
+}
+
+/// A job that was submitted and hasn't finished.
+struct PendingJob {
+    id: u64,
+    kind: JobKind,
+    control: JobControl,
+}
+
+struct Shared {
+    /// Oldest first; the first one may be running.
+    pending: Mutex<Vec<PendingJob>>,
+    /// `None` when no model is loaded.
+    loaded: RwLock<Option<EngineInfo>>,
+    on_event: Box<dyn Fn(JobEvent) + Send + Sync>,
+}
+
+impl Shared {
+    fn finish(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|job| job.id != id);
+        }
+    }
+
+    fn set_loaded(&self, engine: Option<&LoadedEngine>) {
+        if let Ok(mut loaded) = self.loaded.write() {
+            *loaded = engine.map(EngineInfo::of);
+        }
+    }
+}
+
+/// Run `f`, turning a panic into an error so it fails only the job it
+/// happened in.
+fn catch_panic<R>(f: impl FnOnce() -> Result<R, String>) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(panic_message(payload)))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string());
+    format!("Transcription panicked: {}", detail)
+}
+
+pub struct TranscriptionWorker {
+    jobs: mpsc::Sender<Job>,
+    shared: Arc<Shared>,
+    next_id: AtomicU64,
+}
+
+impl TranscriptionWorker {
+    /// Start the worker thread with `engine` loaded, if any. `on_event` is
+    /// called with every job's lifecycle events.
+    pub fn spawn(engine: Option<LoadedEngine>, on_event: impl Fn(JobEvent) + Send + Sync + 'static) -> Self {
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Vec::new()),
+            loaded: RwLock::new(engine.as_ref().map(EngineInfo::of)),
+            on_event: Box::new(on_event),
+        });
+        let (jobs, receiver) = mpsc::channel();
+        let worker_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("transcription".to_string())
+            .spawn(move || run(engine, receiver, worker_shared))
+            .expect("failed to spawn transcription worker");
+        Self {
+            jobs,
+            shared,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// The loaded model and its engine's capabilities, or `None` when no
+    /// model is loaded.
+    pub fn engine_info(&self) -> Option<EngineInfo> {
+        self.shared.loaded.read().ok().and_then(|loaded| loaded.clone())
+    }
+
+    /// Queue `task` to run with the loaded engine. It gets a copy of
+    /// `options` carrying the job's cancellation and progress reporting,
+    /// resolved for the model loaded when the job starts.
+    pub fn submit<R, F>(&self, kind: JobKind, options: &TranscribeOptions, task: F) -> JobHandle<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&dyn TranscriptionEngine, &TranscribeOptions) -> Result<R, String> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let control = self.progress_control(id);
+        let mut options = options.clone();
+        options.job = control.clone();
+        self.enqueue(id, kind, control, move |slot| {
+            let loaded = slot.as_ref().ok_or_else(|| NO_MODEL.to_string())?;
+            let options = options.for_model(&loaded.model)?;
+            task(loaded.engine.as_ref(), &options)
+        })
+    }
+
+    /// Queue loading `model` with `loader`. The new engine replaces the
+    /// current one only if it loads; jobs submitted afterwards use it.
+    /// `loader` is kept to load the model again if a job panics.
+    pub fn load<F>(&self, model: TranscriptionModel, loader: F, switch: ModelSwitch) -> JobHandle<()>
+    where
+        F: Fn() -> Result<Box<dyn TranscriptionEngine>, String> + Send + Sync + 'static,
+    {
+        if switch == ModelSwitch::Preempt {
+            self.cancel_where(|_| true);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let shared = self.shared.clone();
+        self.enqueue(id, JobKind::ModelLoad, JobControl::default(), move |slot| {
+            let loaded = LoadedEngine::load(model, loader)?;
+            shared.set_loaded(Some(&loaded));
+            *slot = Some(loaded);
+            Ok(())
+        })
+    }
+
+    /// Cancel a job, running or queued. Returns whether it was still pending.
+    pub fn cancel(&self, job_id: u64) -> bool {
+        self.cancel_where(|job| job.id == job_id)
+    }
+
+    /// Cancel every pending job of `kind`. Returns whether there were any.
+    pub fn cancel_kind(&self, kind: JobKind) -> bool {
+        self.cancel_where(|job| job.kind == kind)
+    }
+
+    fn cancel_where(&self, matches: impl Fn(&PendingJob) -> bool) -> bool {
+        let Ok(pending) = self.shared.pending.lock() else {
+            return false;
+        };
+        let mut cancelled = false;
+        for job in pending.iter().filter(|job| matches(job)) {
+            job.control.cancel();
+            cancelled = true;
+        }
+        cancelled
+    }
+
+    /// Control that emits a progress event for each whole percent.
+    fn progress_control(&self, job_id: u64) -> JobControl {
+        let shared = self.shared.clone();
+        let last_percent = AtomicI32::new(-1);
+        JobControl::with_progress(move |fraction| {
+            let percent = (fraction * 100.0).floor() as i32;
+            if last_percent.swap(percent, Ordering::Relaxed) != percent {
+                (shared.on_event)(JobEvent::Progress {
+                    job_id,
+                    percent: percent as f32,
+                });
+            }
+        })
+    }
+
+    fn enqueue<R, F>(&self, id: u64, kind: JobKind, control: JobControl, run: F) -> JobHandle<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut EngineSlot) -> Result<R, String> + Send + 'static,
+    {
+        let (sender, result) = oneshot::channel();
+        let shared = self.shared.clone();
+        let task: Task = Box::new(move |slot| {
+            let outcome = match slot {
+                Some(slot) => {
+                    panic::catch_unwind(AssertUnwindSafe(|| run(slot))).unwrap_or_else(|payload| {
+                        reload(slot, &shared);
+                        Err(panic_message(payload))
+                    })
+                }
+                None => Err(CANCELLED.to_string()),
+            };
+            // Done before the submitter hears back, so it can't cancel it anymore
+            shared.finish(id);
+            let error = outcome.as_ref().err().cloned();
+            // The submitter may have stopped waiting
+            let _ = sender.send(outcome);
+            error.map_or(Ok(()), Err)
+        });
+
+        if let Ok(mut pending) = self.shared.pending.lock() {
+            pending.push(PendingJob {
+                id,
+                kind,
+                control: control.clone(),
+            });
+        }
+        (self.shared.on_event)(JobEvent::Queued { job_id: id, kind });
+        let job = Job {
+            id,
+            kind,
+            control: control.clone(),
+            task,
+        };
+        if let Err(mpsc::SendError(job)) = self.jobs.send(job) {
+            self.shared.finish(job.id);
+        }
+        JobHandle { id, control, result }
+    }
+}
+
+fn run(mut slot: EngineSlot, jobs: mpsc::Receiver<Job>, shared: Arc<Shared>) {
+    for job in jobs {
+        let outcome = if job.control.is_cancelled() {
+            (job.task)(None)
+        } else {
+            (shared.on_event)(JobEvent::Started {
+                job_id: job.id,
+                kind: job.kind,
+            });
+            (job.task)(Some(&mut slot))
+        };
+        let event = match outcome {
+            Ok(()) => JobEvent::Finished { job_id: job.id },
+            Err(e) if e == CANCELLED => JobEvent::Cancelled { job_id: job.id },
+            Err(message) => JobEvent::Failed {
+                job_id: job.id,
+                message,
+            },
+        };
+        (shared.on_event)(event);
+    }
+}
+
+/// A submitted job. Dropping it doesn't cancel the job.
+pub struct JobHandle<R> {
+    id: u64,
+    control: JobControl,
+    result: oneshot::Receiver<Result<R, String>>,
+}
+
+impl<R> JobHandle<R> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn cancel(&self) {
+        self.control.cancel();
+    }
+
+    /// Wait for the job's result without blocking the async runtime.
+    pub async fn join(self) -> Result<R, String> {
+        self.result.await.unwrap_or_else(|_| Err(WORKER_STOPPED.to_string()))
+    }
+
+    /// Block the calling thread until the job is done. Must not be called
+    /// from async code.
+    pub fn wait(self) -> Result<R, String> {
+        self.result
+            .blocking_recv()
+            .unwrap_or_else(|_| Err(WORKER_STOPPED.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preferences::{WhisperDecodePreset, WhisperDecodeSetting};
+    use crate::transcription::engine::Transcript;
+    use crate::transcription::{DownloadStatus, ModelVariant};
+    use std::sync::mpsc::Receiver;
+    use std::time::Duration;
+
+    /// Engine that answers with its name, or waits to be cancelled when
+    /// given audio starting with a negative sample.
+    struct NamedEngine(&'static str);
+
+    impl TranscriptionEngine for NamedEngine {
+        fn transcribe(&self, _audio: &[f32]) -> Result<String, String> {
+            Ok(self.0.to_string())
+        }
+
+        fn transcribe_with(&self, audio: &[f32], options: &TranscribeOptions) -> Result<Transcript, String> {
+            if audio.first().is_some_and(|sample| *sample < 0.0) {
+                while !options.job.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                options.job.check()?;
+            }
+            options.job.report_progress(1.0);
+            Ok(Transcript {
+                text: self.0.to_string(),
+                ..Default::default()
+            })
+        }
+    }
+
+    /// A Whisper model; "tiny" and "base" are sized accordingly, anything else is large.
+    fn model(name: &'static str) -> TranscriptionModel {
+        TranscriptionModel {
+            id: name.to_string(),
+            name: name.to_string(),
+            model_family: "Whisper".to_string(),
+            variant: match name {
+                "tiny" => ModelVariant::Tiny,
+                "base" => ModelVariant::Base,
+                _ => ModelVariant::LargeV3,
+            },
+            size_bytes: 0,
+            languages: vec!["en".to_string(), "de".to_string()],
+            quantization: None,
+            download_status: DownloadStatus::NotDownloaded,
+            huggingface_repo: String::new(),
+            huggingface_filenames: Vec::new(),
+        }
+    }
+
+    fn engine(name: &'static str) -> Result<Box<dyn TranscriptionEngine>, String> {
+        Ok(Box::new(NamedEngine(name)))
+    }
+
+    fn worker(engine: Option<&'static str>) -> (TranscriptionWorker, Receiver<JobEvent>) {
+        let (events, received) = mpsc::channel();
+        let events = Mutex::new(events);
+        let engine = engine.map(|name| LoadedEngine::load(model(name), move || self::engine(name)).unwrap());
+        let worker = TranscriptionWorker::spawn(engine, move |event| {
+            let _ = events.lock().unwrap().send(event);
+        });
+        (worker, received)
+    }
+
+    fn transcribe(worker: &TranscriptionWorker, audio: Vec<f32>) -> JobHandle<String> {
+        worker.submit(JobKind::Dictation, &TranscribeOptions::default(), move |engine, options| {
+            engine.transcribe_with(&audio, options).map(|t| t.text)
+        })
+    }
+
+    /// Block until the running job is waiting to be cancelled.
+    fn wait_until_started(events: &Receiver<JobEvent>, job_id: u64) {
+        while !matches!(events.recv().unwrap(), JobEvent::Started { job_id: id, .. } if id == job_id) {}
+    }
+
+    #[test]
+    fn test_runs_jobs_in_order() {
+        let (worker, events) = worker(Some("tiny"));
+        let first = transcribe(&worker, vec![0.0]);
+        let second = transcribe(&worker, vec![0.0]);
+        assert_eq!(first.wait().unwrap(), "tiny");
+        assert_eq!(second.wait().unwrap(), "tiny");
+
+        // Queued events come from the submitting thread, so they may interleave
+        let mut queued = 0;
+        let mut lifecycle = Vec::new();
+        for event in events.iter() {
+            match event {
+                JobEvent::Queued { .. } => queued += 1,
+                JobEvent::Finished { job_id: 2 } => {
+                    lifecycle.push(event);
+                    break;
+                }
+                event => lifecycle.push(event),
+            }
+        }
+        assert_eq!(queued, 2);
+        assert_eq!(
+            lifecycle,
+            vec![
+                JobEvent::Started { job_id: 1, kind: JobKind::Dictation },
+                JobEvent::Progress { job_id: 1, percent: 100.0 },
+                JobEvent::Finished { job_id: 1 },
+                JobEvent::Started { job_id: 2, kind: JobKind::Dictation },
+                JobEvent::Progress { job_id: 2, percent: 100.0 },
+                JobEvent::Finished { job_id: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_model() {
+        let (worker, _events) = worker(None);
+        assert!(worker.engine_info().is_none());
+        assert_eq!(transcribe(&worker, vec![0.0]).wait().unwrap_err(), NO_MODEL);
+    }
+
+    #[test]
+    fn test_cancel_running_and_queued_jobs() {
+        let (worker, events) = worker(Some("tiny"));
+        let running = transcribe(&worker, vec![-1.0]);
+        let queued = transcribe(&worker, vec![0.0]);
+        let other = worker.submit(JobKind::File, &TranscribeOptions::default(), |_, _| Ok(()));
+        wait_until_started(&events, running.id());
+
+        assert!(worker.cancel_kind(JobKind::Dictation));
+        assert_eq!(running.wait().unwrap_err(), CANCELLED);
+        assert_eq!(queued.wait().unwrap_err(), CANCELLED);
+        assert!(other.wait().is_ok());
+        assert!(!worker.cancel_kind(JobKind::Dictation));
+    }
+
+    #[test]
+    fn test_model_switch_queues_behind_jobs() {
+        let (worker, _events) = worker(Some("tiny"));
+        let before = transcribe(&worker, vec![0.0]);
+        let load = worker.load(model("base"), || engine("base"), ModelSwitch::Queue);
+        let after = transcribe(&worker, vec![0.0]);
+
+        assert_eq!(before.wait().unwrap(), "tiny");
+        assert!(load.wait().is_ok());
+        assert_eq!(after.wait().unwrap(), "base");
+    }
+
+    #[test]
+    fn test_model_switch_preempts_jobs() {
+        let (worker, events) = worker(Some("tiny"));
+        let running = transcribe(&worker, vec![-1.0]);
+        wait_until_started(&events, running.id());
+        let load = worker.load(model("base"), || engine("base"), ModelSwitch::Preempt);
+        let after = transcribe(&worker, vec![0.0]);
+
+        assert_eq!(running.wait().unwrap_err(), CANCELLED);
+        assert!(load.wait().is_ok());
+        assert_eq!(after.wait().unwrap(), "base");
+    }
+
+    #[test]
+    fn test_failed_load_keeps_current_model() {
+        let (worker, _events) = worker(Some("tiny"));
+        let load = worker.load(model("base"), || Err("corrupt model".to_string()), ModelSwitch::Queue);
+        assert_eq!(load.wait().unwrap_err(), "corrupt model");
+        assert_eq!(transcribe(&worker, vec![0.0]).wait().unwrap(), "tiny");
+        let info = worker.engine_info().unwrap();
+        assert_eq!(info.model.id, "tiny");
+        assert!(!info.supports_vocabulary && !info.reports_confidence);
+    }
+
+    #[test]
+    fn test_panicking_job_reloads_model() {
+        // Loaded before the worker started, like the model restored at launch
+        let loads = Arc::new(AtomicU64::new(0));
+        let counter = loads.clone();
+        let loaded = LoadedEngine::load(model("tiny"), move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            engine("tiny")
+        });
+        let worker = TranscriptionWorker::spawn(Some(loaded.unwrap()), |_| {});
+
+        let panicked = worker.submit(JobKind::File, &TranscribeOptions::default(), |_, _| -> Result<(), String> {
+            panic!("decoder state corrupted")
+        });
+        assert_eq!(panicked.wait().unwrap_err(), "Transcription panicked: decoder state corrupted");
+        assert_eq!(transcribe(&worker, vec![0.0]).wait().unwrap(), "tiny");
+        assert_eq!(loads.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_panicking_load_keeps_current_model() {
+        let (worker, _events) = worker(Some("tiny"));
+        let load = worker.load(model("base"), || panic!("truncated model file"), ModelSwitch::Queue);
+        assert_eq!(load.wait().unwrap_err(), "Transcription panicked: truncated model file");
+        assert_eq!(transcribe(&worker, vec![0.0]).wait().unwrap(), "tiny");
+    }
+
+    #[test]
+    fn test_options_follow_the_model_loaded_when_the_job_starts() {
+        let (worker, _events) = worker(Some("tiny"));
+        let options = TranscribeOptions {
+            whisper_decoding: WhisperDecodeSetting::Preset {
+                preset: WhisperDecodePreset::Balanced,
+            },
+            ..Default::default()
+        };
+        let beam_size = |worker: &TranscriptionWorker| {
+            worker.submit(JobKind::File, &options, |_, options| Ok(options.whisper.beam_size))
+        };
+
+        let before = beam_size(&worker);
+        let load = worker.load(model("large"), || engine("large"), ModelSwitch::Queue);
+        let after = beam_size(&worker);
+        assert_eq!(before.wait().unwrap(), 5);
+        assert!(load.wait().is_ok());
+        assert_eq!(after.wait().unwrap(), 1);
+    }
+}
@@ -4,6 +4,7 @@
 // - start_recording returns NoModelSelected when no model loaded
 // - start_recording returns AlreadyRecording when already recording
 // - stop_recording returns NotRecording when not recording
+// - start_mic_test needs no model, but not while recording or testing
 //
 // These test the extracted validation functions directly against AppState,
 // avoiding the need for a full Tauri runtime.
@@ -12,17 +13,31 @@ use std::path::PathBuf;
 use std::sync::{Mutex, RwLock};
 
 use text_to_code_lib::commands::audio_commands::{
-    validate_can_start_recording, validate_can_stop_recording,
+    validate_can_start_mic_test, validate_can_start_recording, validate_can_stop_recording,
 };
+use text_to_code_lib::audio::capture::CaptureOptions;
+use text_to_code_lib::audio::source::SyntheticSource;
+use text_to_code_lib::transcription::engine::TranscriptionEngine;
+use text_to_code_lib::transcription::models::get_builtin_models;
+use text_to_code_lib::transcription::worker::{LoadedEngine, TranscriptionWorker};
 use text_to_code_lib::AppState;
 
 fn make_test_state() -> AppState {
     AppState {
         preferences: RwLock::new(Default::default()),
+        vocabulary: RwLock::new(Vec::new()),
         app_data_dir: PathBuf::from("/tmp/ttc_test"),
         recording_active: RwLock::new(false),
-        engine: RwLock::new(None),
+        transcriber: TranscriptionWorker::spawn(None, |_| {}),
         active_capture: Mutex::new(None),
+        recording_started_at: Mutex::new(None),
+        live_transcription: Mutex::new(None),
+        session_resampler: Mutex::new(None),
+        system_capture: Mutex::new(None),
+        session_options: Mutex::new(None),
+        warm_microphone: Mutex::new(None),
+        mic_test: Mutex::new(None),
+        mic_test_playback: Mutex::new(None),
     }
 }
 
@@ -74,6 +89,27 @@ fn test_start_recording_succeeds_with_model_and_not_recording() {
     assert_eq!(result.unwrap_err().code, "NoModelSelected");
 }
 
+/// Engine that hears nothing, standing in for a loaded model.
+struct SilentEngine;
+
+impl TranscriptionEngine for SilentEngine {
+    fn transcribe(&self, _audio: &[f32]) -> Result<String, String> {
+        Ok(String::new())
+    }
+}
+
+#[test]
+fn test_start_recording_with_model_loaded() {
+    let mut state = make_test_state();
+    let model = get_builtin_models(&state.app_data_dir).remove(0);
+    let loaded = LoadedEngine::load(model, || Ok(Box::new(SilentEngine))).unwrap();
+    state.transcriber = TranscriptionWorker::spawn(Some(loaded), |_| {});
+    assert!(validate_can_start_recording(&state).is_ok());
+
+    *state.recording_active.write().unwrap() = true;
+    assert_eq!(validate_can_start_recording(&state).unwrap_err().code, "AlreadyRecording");
+}
+
 // --- T032: stop_recording contract tests ---
 
 #[test]
@@ -113,3 +149,35 @@ fn test_stop_recording_error_message_is_descriptive() {
         "Error message should be descriptive"
     );
 }
+
+// --- start_mic_test contract tests ---
+
+#[test]
+fn test_start_mic_test_needs_no_model() {
+    let state = make_test_state();
+    assert!(validate_can_start_mic_test(&state).is_ok());
+}
+
+#[test]
+fn test_start_mic_test_errors_while_recording() {
+    let state = make_test_state();
+    *state.recording_active.write().unwrap() = true;
+
+    let err = validate_can_start_mic_test(&state).unwrap_err();
+    assert_eq!(err.code, "AlreadyRecording");
+}
+
+#[test]
+fn test_start_mic_test_errors_while_testing() {
+    let state = make_test_state();
+    *state.mic_test.lock().unwrap() = Some(Box::new(SyntheticSource::start(
+        Box::new(|_| {}),
+        CaptureOptions::default(),
+    )));
+
+    let err = validate_can_start_mic_test(&state).unwrap_err();
+    assert_eq!(err.code, "MicTestActive");
+
+    let capture = state.mic_test.lock().unwrap().take().unwrap();
+    capture.stop();
+}
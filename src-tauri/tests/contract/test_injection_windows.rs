@@ -9,15 +9,25 @@ use std::path::PathBuf;
 use std::sync::{Mutex, RwLock};
 
 use text_to_code_lib::preferences::{TargetMode, UserPreferences};
+use text_to_code_lib::transcription::worker::TranscriptionWorker;
 use text_to_code_lib::AppState;
 
 fn make_test_state() -> AppState {
     AppState {
         preferences: RwLock::new(UserPreferences::default()),
+        vocabulary: RwLock::new(Vec::new()),
         app_data_dir: PathBuf::from("/tmp/ttc_test_injection_windows"),
         recording_active: RwLock::new(false),
-        engine: RwLock::new(None),
+        transcriber: TranscriptionWorker::spawn(None, |_| {}),
         active_capture: Mutex::new(None),
+        recording_started_at: Mutex::new(None),
+        live_transcription: Mutex::new(None),
+        session_resampler: Mutex::new(None),
+        system_capture: Mutex::new(None),
+        session_options: Mutex::new(None),
+        warm_microphone: Mutex::new(None),
+        mic_test: Mutex::new(None),
+        mic_test_playback: Mutex::new(None),
     }
 }
 
@@ -15,15 +15,25 @@ use text_to_code_lib::commands::model_commands::validate_can_set_active_model;
 use text_to_code_lib::preferences::UserPreferences;
 use text_to_code_lib::transcription::models;
 use text_to_code_lib::transcription::DownloadStatus;
+use text_to_code_lib::transcription::worker::TranscriptionWorker;
 use text_to_code_lib::AppState;
 
 fn make_test_state_with_dir(dir: &std::path::Path) -> AppState {
     AppState {
         preferences: RwLock::new(UserPreferences::default()),
+        vocabulary: RwLock::new(Vec::new()),
         app_data_dir: dir.to_path_buf(),
         recording_active: RwLock::new(false),
-        engine: RwLock::new(None),
+        transcriber: TranscriptionWorker::spawn(None, |_| {}),
         active_capture: Mutex::new(None),
+        recording_started_at: Mutex::new(None),
+        live_transcription: Mutex::new(None),
+        session_resampler: Mutex::new(None),
+        system_capture: Mutex::new(None),
+        session_options: Mutex::new(None),
+        warm_microphone: Mutex::new(None),
+        mic_test: Mutex::new(None),
+        mic_test_playback: Mutex::new(None),
     }
 }
 
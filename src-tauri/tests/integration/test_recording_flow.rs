@@ -9,24 +9,39 @@
 //
 // The full round-trip requires hardware (microphone, model file, accessibility),
 // so the end-to-end test is marked #[ignore] for manual validation.
-// Component-level integration is tested with available resources.
+// Component-level integration is tested with available resources; capture
+// runs from the synthetic and file audio sources instead of a microphone.
 
 use std::path::PathBuf;
 use std::sync::{Mutex, RwLock};
 
+use text_to_code_lib::audio::capture::CaptureOptions;
 use text_to_code_lib::audio::resample::resample_to_16khz;
+use text_to_code_lib::audio::source::start_source;
+use text_to_code_lib::audio::vad::find_speech_bounds;
 use text_to_code_lib::commands::audio_commands::{
     validate_can_start_recording, validate_can_stop_recording,
 };
+use text_to_code_lib::preferences::AudioSourceSetting;
+use text_to_code_lib::transcription::worker::TranscriptionWorker;
 use text_to_code_lib::AppState;
 
 fn make_test_state() -> AppState {
     AppState {
         preferences: RwLock::new(Default::default()),
+        vocabulary: RwLock::new(Vec::new()),
         app_data_dir: PathBuf::from("/tmp/ttc_test_integration"),
         recording_active: RwLock::new(false),
-        engine: RwLock::new(None),
+        transcriber: TranscriptionWorker::spawn(None, |_| {}),
         active_capture: Mutex::new(None),
+        recording_started_at: Mutex::new(None),
+        live_transcription: Mutex::new(None),
+        session_resampler: Mutex::new(None),
+        system_capture: Mutex::new(None),
+        session_options: Mutex::new(None),
+        warm_microphone: Mutex::new(None),
+        mic_test: Mutex::new(None),
+        mic_test_playback: Mutex::new(None),
     }
 }
 
@@ -91,6 +106,57 @@ fn test_resample_then_validate_pipeline() {
     assert!(resampled.iter().all(|s| s.is_finite()));
 }
 
+/// Record from `setting` for `millis` and return the 16kHz audio, as stop_recording would.
+fn record_from(setting: &AudioSourceSetting, millis: u64) -> Vec<f32> {
+    let capture = start_source(setting, Box::new(|_| {}), CaptureOptions::default())
+        .expect("Failed to start audio source");
+    std::thread::sleep(std::time::Duration::from_millis(millis));
+    let (mut buffer, sample_rate) = capture.stop();
+    let audio = buffer.to_vec().expect("Failed to read capture buffer");
+    resample_to_16khz(&audio, sample_rate).unwrap()
+}
+
+#[test]
+fn test_synthetic_source_capture_pipeline() {
+    let audio = record_from(&AudioSourceSetting::Synthetic, 600);
+
+    // The synthetic signal opens with a tone burst, which counts as speech
+    assert!(audio.len() > 16000 / 4, "Only {} samples recorded", audio.len());
+    let speech = find_speech_bounds(&audio, 16000).expect("Expected speech in synthetic audio");
+    assert!(speech.start < 16000 / 10);
+}
+
+#[test]
+fn test_file_source_replays_recording() {
+    let dir = PathBuf::from("/tmp/ttc_test_integration");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("replay_source.wav");
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for i in 0..44100 / 4 {
+        let sample = (0.4 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin()
+            * i16::MAX as f32) as i16;
+        writer.write_sample(sample).unwrap();
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let setting = AudioSourceSetting::File {
+        path: path.to_string_lossy().into_owned(),
+    };
+    let audio = record_from(&setting, 500);
+
+    // A quarter second of tone, then silence once the file runs out
+    let speech = find_speech_bounds(&audio, 16000).expect("Expected the replayed tone");
+    assert_eq!(speech.start, 0);
+    assert!(audio[16000 * 3 / 8..].iter().all(|s| s.abs() < 0.01));
+}
+
 #[test]
 #[ignore] // Requires microphone + model file + accessibility permission
 fn test_full_round_trip_record_transcribe_inject() {
@@ -1,5 +1,6 @@
 mod test_audio_capture;
 mod test_resample;
+mod test_audio_decode;
 mod test_engine;
 mod test_keyboard;
 mod test_push_to_talk;
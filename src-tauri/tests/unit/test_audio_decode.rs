@@ -0,0 +1,113 @@
+// Unit tests for decoding audio files for transcribe_file
+//
+// WAV fixtures are generated with hound; the other formats go through the
+// same symphonia pipeline, except Opus history recordings.
+
+use std::fs;
+use std::path::PathBuf;
+
+use text_to_code_lib::audio::decode::decode_audio_file;
+use text_to_code_lib::history::{get_recordings_dir, save_audio};
+use text_to_code_lib::preferences::{HistoryAudioFormat, ResamplerQuality};
+use text_to_code_lib::transcription::engine::{TranscribeOptions, TranscriptionEngine};
+use text_to_code_lib::transcription::file::{decode_file, transcribe_segments};
+
+fn write_wav(name: &str, channels: u16, sample_rate: u32, frames: &[Vec<f32>]) -> PathBuf {
+    let dir = std::env::temp_dir().join("ttc_test_decode");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for frame in frames {
+        for &sample in frame {
+            writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+        }
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_decode_mono_wav() {
+    let frames: Vec<Vec<f32>> = (0..16000)
+        .map(|i| vec![0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin()])
+        .collect();
+    let path = write_wav("mono.wav", 1, 16000, &frames);
+
+    let decoded = decode_audio_file(&path, &mut |_| {}).unwrap();
+    assert_eq!(decoded.sample_rate, 16000);
+    assert_eq!(decoded.samples.len(), 16000);
+    assert_eq!(decoded.duration_ms(), 1000);
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_decode_stereo_wav_is_downmixed() {
+    let frames: Vec<Vec<f32>> = (0..44100).map(|_| vec![0.5, -0.5]).collect();
+    let path = write_wav("stereo.wav", 2, 44100, &frames);
+
+    let decoded = decode_audio_file(&path, &mut |_| {}).unwrap();
+    assert_eq!(decoded.sample_rate, 44100);
+    assert_eq!(decoded.samples.len(), 44100);
+    assert!(decoded.samples.iter().all(|s| s.abs() < 0.001));
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_decode_reports_progress() {
+    let frames: Vec<Vec<f32>> = (0..48000).map(|_| vec![0.1]).collect();
+    let path = write_wav("progress.wav", 1, 48000, &frames);
+
+    let mut progress = Vec::new();
+    decode_audio_file(&path, &mut |p| progress.push(p)).unwrap();
+    assert!(!progress.is_empty());
+    assert!(progress.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(*progress.last().unwrap(), 1.0);
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_decode_rejects_unsupported_extension() {
+    let result = decode_audio_file(std::path::Path::new("/tmp/notes.txt"), &mut |_| {});
+    assert!(result.unwrap_err().contains("Unsupported audio format"));
+}
+
+#[test]
+fn test_decode_missing_file_errors() {
+    let result = decode_audio_file(std::path::Path::new("/tmp/ttc_missing_file.wav"), &mut |_| {});
+    assert!(result.unwrap_err().contains("Failed to open"));
+}
+
+/// Engine that reports how much audio it was given.
+struct LengthEngine;
+
+impl TranscriptionEngine for LengthEngine {
+    fn transcribe(&self, audio: &[f32]) -> Result<String, String> {
+        Ok(format!("{} samples", audio.len()))
+    }
+}
+
+#[test]
+fn test_opus_history_track_can_be_retranscribed() {
+    let dir = std::env::temp_dir().join("ttc_test_decode_opus");
+    let _ = fs::remove_dir_all(&dir);
+    let samples: Vec<f32> = (0..16000)
+        .map(|i| 0.4 * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / 16000.0).sin())
+        .collect();
+    let name = save_audio(&dir, "call-system", &samples, 16000, HistoryAudioFormat::Opus).unwrap();
+    assert!(name.ends_with(".opus"));
+
+    // The same steps retranscribe_history_track takes
+    let path = get_recordings_dir(&dir).join(&name);
+    let decoded = decode_file(&path, ResamplerQuality::default(), &mut |_| {}).unwrap();
+    assert_eq!(decoded.audio_16khz.len(), samples.len());
+    let transcript =
+        transcribe_segments(&LengthEngine, &decoded.audio_16khz, &TranscribeOptions::default(), &mut |_| {}).unwrap();
+    assert_eq!(transcript.text, "16000 samples");
+    let _ = fs::remove_dir_all(&dir);
+}
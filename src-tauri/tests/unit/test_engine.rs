@@ -5,7 +5,10 @@
 // Error handling and edge cases are tested without a model.
 
 use std::path::PathBuf;
-use text_to_code_lib::transcription::engine::{ParakeetEngine, WhisperEngine};
+use text_to_code_lib::transcription::engine::{
+    dominant_language, group_sentences, group_words, ParakeetEngine, TimedWord, Transcript, TranscriptSegment,
+    WhisperEngine,
+};
 
 #[test]
 fn test_engine_load_invalid_path_returns_error() {
@@ -127,3 +130,133 @@ fn test_parakeet_load_unknown_variant_returns_error() {
         err
     );
 }
+
+// --- Detected language ---
+
+#[test]
+fn test_dominant_language_weights_by_text_length() {
+    let parts = [
+        ("Hallo zusammen, wie geht es euch heute?", Some("de")),
+        ("Okay.", Some("en")),
+        ("Gut, dann fangen wir an.", Some("de")),
+    ];
+    assert_eq!(dominant_language(parts).as_deref(), Some("de"));
+}
+
+#[test]
+fn test_dominant_language_ignores_unknown_parts() {
+    let parts = [("A long stretch the engine reported no language for", None), ("Ja", Some("de"))];
+    assert_eq!(dominant_language(parts).as_deref(), Some("de"));
+    assert_eq!(dominant_language([("Hello", None)]), None);
+}
+
+#[test]
+fn test_dominant_language_tie_goes_to_first() {
+    assert_eq!(dominant_language([("abc", Some("fr")), ("xyz", Some("es"))]).as_deref(), Some("fr"));
+}
+
+// --- Timed transcripts ---
+
+fn token(text: &str, start_ms: u64, end_ms: u64, probability: Option<f32>) -> TimedWord {
+    TimedWord {
+        text: text.to_string(),
+        start_ms,
+        end_ms,
+        probability,
+    }
+}
+
+#[test]
+fn test_group_words_joins_sub_word_tokens() {
+    let words = group_words([
+        token(" Kub", 0, 200, Some(0.9)),
+        token("ern", 200, 300, Some(0.4)),
+        token("etes", 300, 500, Some(0.8)),
+        token(",", 500, 510, Some(0.95)),
+        token("▁runs", 600, 900, None),
+        token(" ", 900, 900, None),
+        token(" fine.", 950, 1300, Some(0.7)),
+    ]);
+    assert_eq!(
+        words,
+        vec![
+            token("Kubernetes,", 0, 510, Some(0.4)),
+            token("runs", 600, 900, None),
+            token("fine.", 950, 1300, Some(0.7)),
+        ]
+    );
+}
+
+#[test]
+fn test_group_sentences_splits_at_sentence_end() {
+    let segments = group_sentences(vec![
+        token("Hello", 0, 300, None),
+        token("there.", 300, 700, None),
+        token("Ready?", 900, 1400, None),
+        token("Go", 1600, 1800, None),
+    ]);
+    let summary: Vec<(&str, u64, u64)> = segments.iter().map(|s| (s.text.as_str(), s.start_ms, s.end_ms)).collect();
+    assert_eq!(summary, vec![("Hello there.", 0, 700), ("Ready?", 900, 1400), ("Go", 1600, 1800)]);
+    assert_eq!(segments[0].words.len(), 2);
+}
+
+fn timed(text: &str, start_ms: u64, end_ms: u64, language: Option<&str>) -> Transcript {
+    Transcript {
+        text: text.to_string(),
+        language: language.map(str::to_string),
+        segments: vec![TranscriptSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            words: vec![token(text, start_ms, end_ms, None)],
+        }],
+    }
+}
+
+#[test]
+fn test_shifted_moves_segments_and_words() {
+    let transcript = timed("Hi", 100, 400, None).shifted(1000);
+    assert_eq!((transcript.segments[0].start_ms, transcript.segments[0].end_ms), (1100, 1400));
+    assert_eq!(transcript.segments[0].words[0].start_ms, 1100);
+
+    // Earlier than the start of the audio clamps to zero
+    let transcript = transcript.shifted(-1200);
+    assert_eq!((transcript.segments[0].start_ms, transcript.segments[0].end_ms), (0, 200));
+}
+
+#[test]
+fn test_concat_offsets_parts() {
+    let transcript = Transcript::concat([
+        (0, timed(" Guten Morgen allerseits. ", 200, 1800, Some("de"))),
+        (30_000, Transcript::default()),
+        (45_000, timed("Okay.", 100, 600, Some("en"))),
+    ]);
+    assert_eq!(transcript.text, "Guten Morgen allerseits. Okay.");
+    assert_eq!(transcript.language.as_deref(), Some("de"));
+    let starts: Vec<u64> = transcript.segments.iter().map(|s| s.start_ms).collect();
+    assert_eq!(starts, vec![200, 45_100]);
+}
+
+#[test]
+fn test_confidence_is_mean_word_probability() {
+    let segment = |words: Vec<TimedWord>| TranscriptSegment {
+        text: String::new(),
+        start_ms: 0,
+        end_ms: 0,
+        words,
+    };
+    let transcript = Transcript {
+        text: "a b c".to_string(),
+        language: None,
+        segments: vec![
+            segment(vec![token("a", 0, 1, Some(0.9)), token("b", 1, 2, Some(0.5))]),
+            segment(vec![token("c", 2, 3, Some(0.4)), token("d", 3, 4, None)]),
+        ],
+    };
+    assert!((transcript.segments[0].confidence().unwrap() - 0.7).abs() < 1e-6);
+    assert!((transcript.confidence().unwrap() - 0.6).abs() < 1e-6);
+
+    // Engines without probabilities report no confidence
+    assert_eq!(timed("Hi", 0, 100, None).confidence(), None);
+    assert_eq!(Transcript::default().confidence(), None);
+}
@@ -46,11 +46,13 @@ fn test_conflict_detected_for_duplicate_combinations() {
             action: HotkeyAction::ToggleRecording,
             key_combination: "CommandOrControl+Shift+Space".to_string(),
             enabled: true,
+            translate: false,
         },
         HotkeyBinding {
             action: HotkeyAction::OpenSettings,
             key_combination: "CommandOrControl+Shift+Space".to_string(),
             enabled: true,
+            translate: false,
         },
     ];
 
@@ -68,11 +70,13 @@ fn test_no_conflict_for_unique_combinations() {
             action: HotkeyAction::ToggleRecording,
             key_combination: "CommandOrControl+Shift+Space".to_string(),
             enabled: true,
+            translate: false,
         },
         HotkeyBinding {
             action: HotkeyAction::OpenSettings,
             key_combination: "CommandOrControl+Shift+,".to_string(),
             enabled: true,
+            translate: false,
         },
     ];
 
@@ -90,11 +94,13 @@ fn test_disabled_hotkeys_do_not_conflict() {
             action: HotkeyAction::ToggleRecording,
             key_combination: "CommandOrControl+Shift+Space".to_string(),
             enabled: true,
+            translate: false,
         },
         HotkeyBinding {
             action: HotkeyAction::PushToTalk,
             key_combination: "CommandOrControl+Shift+Space".to_string(),
             enabled: false, // disabled — should not conflict
+            translate: false,
         },
     ];
 
@@ -120,6 +120,38 @@ fn test_toggle_recording_stops_when_recording() {
     assert_eq!(response, HotkeyResponse::StopRecordingAndTranscribe);
 }
 
+// --- Hands-free mode: toggle hotkey starts, silence (or a second press) stops ---
+
+#[test]
+fn test_hands_free_toggle_starts_recording() {
+    let response = resolve_hotkey_event(
+        HotkeyEvent::Pressed(HotkeyAction::ToggleRecording),
+        &RecordingMode::HandsFree,
+        false,
+    );
+    assert_eq!(response, HotkeyResponse::StartRecording);
+}
+
+#[test]
+fn test_hands_free_toggle_stops_early_when_recording() {
+    let response = resolve_hotkey_event(
+        HotkeyEvent::Pressed(HotkeyAction::ToggleRecording),
+        &RecordingMode::HandsFree,
+        true,
+    );
+    assert_eq!(response, HotkeyResponse::StopRecordingAndTranscribe);
+}
+
+#[test]
+fn test_hands_free_push_to_talk_is_noop() {
+    let response = resolve_hotkey_event(
+        HotkeyEvent::Pressed(HotkeyAction::PushToTalk),
+        &RecordingMode::HandsFree,
+        false,
+    );
+    assert_eq!(response, HotkeyResponse::NoOp);
+}
+
 // --- Other hotkey actions ---
 
 #[test]
@@ -1,15 +1,19 @@
 import { useEffect, useRef, useState, useMemo } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { usePreferences } from "@/hooks/use-preferences";
 import { useModels } from "@/hooks/use-models";
 import { useHistory } from "@/hooks/use-history";
+import { useMicTest } from "@/hooks/use-mic-test";
+import { useVocabulary } from "@/hooks/use-vocabulary";
 import { HotkeyRecorder } from "@/components/hotkey-recorder";
 import { CompanyBadge } from "@/components/company-badge";
 import { SettingsGroup, SettingsRow } from "@/components/settings-group";
 import { VISUALIZATIONS } from "@/components/visualizations";
 import { PROCESSING_ANIMATIONS } from "@/components/processing-animations";
 import { ToggleSwitch } from "@/components/toggle-switch";
-import type { VisualizationStyle, ProcessingAnimation, OverlayMode, AudioDevice, TranscriptionModel, RecordingMode, HotkeyBinding, HistoryEntry } from "@/types";
+import { formatLanguageName } from "@/utils/format-language";
+import type { VisualizationStyle, ProcessingAnimation, OverlayMode, AudioDevice, InputChannel, TranscriptionModel, RecordingMode, HotkeyBinding, HistoryEntry, HistoryAudioFormat, ResamplerQuality, SpokenLanguage, VisualizationMode, LevelWarningKind, WhisperDecodeOptions, WhisperDecodePreset, WhisperDecodeSetting } from "@/types";
 
 const STYLE_KEYS: VisualizationStyle[] = ["Bars", "Sine", "Rainbow"];
 const PROCESSING_ANIM_KEYS: ProcessingAnimation[] = ["Pulse", "FrozenFrame", "TypingParrot"];
@@ -169,8 +173,12 @@ export function GeneralPanel() {
   const { models, setActiveModel, activatingModelId } = useModels();
   const { entries } = useHistory();
   const fakeAmplitudes = useFakeAmplitudes();
+  const audioDevices = useAudioDevices();
 
   const isWindowPicker = preferences?.targetMode.type === "WindowPicker";
+  const selectedDevice = preferences?.selectedAudioDeviceId
+    ? audioDevices.find((d) => d.id === preferences.selectedAudioDeviceId)
+    : audioDevices.find((d) => d.name === preferences?.selectedAudioDevice);
 
   if (loading) {
     return <p className="text-gray-500 dark:text-gray-400">Loading preferences...</p>;
@@ -181,6 +189,10 @@ export function GeneralPanel() {
   }
 
   const downloadedModels = models.filter((m) => m.downloadStatus.status === "Downloaded");
+  const activeModel = models.find((m) => m.id === preferences?.activeModelId);
+  const translateRecordings = preferences?.hotkeys.find(
+    (h: HotkeyBinding) => h.enabled && (h.action === "ToggleRecording" || h.action === "PushToTalk"),
+  )?.translate ?? false;
 
   return (
     <div>
@@ -196,6 +208,37 @@ export function GeneralPanel() {
             onSelect={setActiveModel}
           />
         </div>
+        {activeModel && (
+          <SettingsRow label="Spoken Language" description="Language you dictate in, or detect it for each recording">
+            <SpokenLanguageSelector
+              languages={activeModel.languages}
+              value={preferences?.spokenLanguage ?? { type: "Fixed", code: "en" }}
+              onChange={async (language) => {
+                if (!preferences) return;
+                await updatePreferences({
+                  ...preferences,
+                  spokenLanguage: language,
+                });
+              }}
+            />
+          </SettingsRow>
+        )}
+        {activeModel?.modelFamily === "Whisper" && preferences && (
+          <WhisperDecodingSettings
+            value={preferences.whisperDecoding ?? { type: "Preset", preset: "Standard" }}
+            onChange={async (whisperDecoding) => {
+              await updatePreferences({
+                ...preferences,
+                whisperDecoding,
+              });
+            }}
+          />
+        )}
+      </SettingsGroup>
+
+      {/* Vocabulary */}
+      <SettingsGroup title="Vocabulary">
+        <VocabularyEditor />
       </SettingsGroup>
 
       {/* Recording */}
@@ -203,7 +246,9 @@ export function GeneralPanel() {
         <SettingsRow label="Mode" description={
           preferences?.recordingMode === "PushToTalk"
             ? "Hold hotkey to record, release to transcribe"
-            : "Press hotkey to start, press again to stop"
+            : preferences?.recordingMode === "HandsFree"
+              ? "Press hotkey to start, stops automatically when you stop speaking"
+              : "Press hotkey to start, press again to stop"
         }>
           <InlineRecordingModeSelector
             value={preferences?.recordingMode ?? "Toggle"}
@@ -243,6 +288,22 @@ export function GeneralPanel() {
             }}
           />
         </SettingsRow>
+        <SettingsRow label="Translate to English" description="Recordings from this hotkey are translated into English (multilingual Whisper models only)">
+          <ToggleSwitch
+            checked={translateRecordings}
+            onChange={async () => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                hotkeys: preferences.hotkeys.map((h: HotkeyBinding) =>
+                  h.action === "ToggleRecording" || h.action === "PushToTalk"
+                    ? { ...h, translate: !translateRecordings }
+                    : h,
+                ),
+              });
+            }}
+          />
+        </SettingsRow>
         <SettingsRow label="Target Window" description={
           isWindowPicker
             ? "Choose target window after recording"
@@ -304,12 +365,106 @@ export function GeneralPanel() {
       <SettingsGroup title="Input">
         <SettingsRow label="Microphone">
           <MicrophoneSelector
-            value={preferences?.selectedAudioDevice ?? null}
+            devices={audioDevices}
+            value={selectedDevice?.id ?? preferences?.selectedAudioDeviceId ?? null}
+            fallbackLabel={preferences?.selectedAudioDevice ?? null}
             onChange={async (device) => {
               if (!preferences) return;
               await updatePreferences({
                 ...preferences,
-                selectedAudioDevice: device,
+                selectedAudioDevice: device?.name ?? null,
+                selectedAudioDeviceId: device?.id ?? null,
+              });
+            }}
+          />
+        </SettingsRow>
+        <SettingsRow label="Test Microphone" description="Record a few seconds and hear them played back">
+          <MicTestControl />
+        </SettingsRow>
+        <SettingsRow label="Record System Audio" description="Also record what others say on a call, as a second track">
+          <ToggleSwitch
+            checked={preferences?.systemAudio?.enabled ?? false}
+            onChange={async () => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                systemAudio: {
+                  ...preferences.systemAudio,
+                  enabled: !preferences.systemAudio?.enabled,
+                },
+              });
+            }}
+          />
+        </SettingsRow>
+        {preferences?.systemAudio?.enabled && (
+          <SettingsRow label="System Audio Device" description="A loopback or monitor input that hears your speakers">
+            <MicrophoneSelector
+              devices={audioDevices.filter((d) => d.isLoopback)}
+              value={preferences.systemAudio.deviceId}
+              fallbackLabel={null}
+              defaultLabel="Automatic"
+              onChange={async (device) => {
+                await updatePreferences({
+                  ...preferences,
+                  systemAudio: {
+                    ...preferences.systemAudio,
+                    deviceId: device?.id ?? null,
+                  },
+                });
+              }}
+            />
+          </SettingsRow>
+        )}
+        {selectedDevice && selectedDevice.channels > 1 && (
+          <SettingsRow label="Input Channel" description="Record one input of a multi-channel interface, or mix them all">
+            <InputChannelSelector
+              channels={selectedDevice.channels}
+              value={preferences?.inputChannels?.[selectedDevice.id] ?? { type: "Mix" }}
+              onChange={async (channel) => {
+                if (!preferences) return;
+                await updatePreferences({
+                  ...preferences,
+                  inputChannels: {
+                    ...preferences.inputChannels,
+                    [selectedDevice.id]: channel,
+                  },
+                });
+              }}
+            />
+          </SettingsRow>
+        )}
+        <SettingsRow label="Max Recording Length" description="Recordings stop automatically after this long">
+          <MaxRecordingLengthSelector
+            value={preferences?.maxRecordingMinutes ?? 30}
+            onChange={async (minutes) => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                maxRecordingMinutes: minutes,
+              });
+            }}
+          />
+        </SettingsRow>
+        <SettingsRow label="History Audio Format" description="How recordings are stored for playback in History">
+          <HistoryAudioFormatSelector
+            value={preferences?.historyAudioFormat ?? "Wav"}
+            onChange={async (format) => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                historyAudioFormat: format,
+              });
+            }}
+          />
+        </SettingsRow>
+        <SettingsRow label="Resampler Quality" description="Higher quality converts audio more accurately but uses more CPU">
+          <ResamplerQualitySelector
+            value={preferences?.resamplerQuality ?? "Balanced"}
+            onChange={async (quality) => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                resamplerQuality: quality,
               });
             }}
           />
@@ -333,6 +488,44 @@ export function GeneralPanel() {
             }}
           />
         </SettingsRow>
+        <SettingsRow
+          label="Confirm Unsure Results"
+          description={
+            activeModel && activeModel.modelFamily !== "Whisper"
+              ? `Confidence unavailable: ${activeModel.name} doesn't report it, so its results are never held`
+              : "Ask where to send transcripts Whisper wasn't confident about instead of typing them out"
+          }
+        >
+          <ToggleSwitch
+            checked={preferences?.lowConfidence?.hold ?? false}
+            onChange={async () => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                lowConfidence: {
+                  ...preferences.lowConfidence,
+                  hold: !preferences.lowConfidence.hold,
+                },
+              });
+            }}
+          />
+        </SettingsRow>
+        {preferences?.lowConfidence?.hold && (
+          <SettingsRow label="Confidence Threshold" description="Transcripts below this confidence wait for confirmation">
+            <ConfidenceThresholdSelector
+              value={preferences.lowConfidence.threshold}
+              onChange={async (threshold) => {
+                await updatePreferences({
+                  ...preferences,
+                  lowConfidence: {
+                    ...preferences.lowConfidence,
+                    threshold,
+                  },
+                });
+              }}
+            />
+          </SettingsRow>
+        )}
       </SettingsGroup>
 
       {/* Overlay Mode */}
@@ -367,6 +560,21 @@ export function GeneralPanel() {
             }}
           />
         </div>
+        <SettingsRow label="Show" description="Loudness over time, or frequency bands with peak hold">
+          <VisualizationModeSelector
+            value={preferences?.visualizationModes?.[preferences.overlayVisualization] ?? "Envelope"}
+            onChange={async (mode) => {
+              if (!preferences) return;
+              await updatePreferences({
+                ...preferences,
+                visualizationModes: {
+                  ...preferences.visualizationModes,
+                  [preferences.overlayVisualization]: mode,
+                },
+              });
+            }}
+          />
+        </SettingsRow>
       </SettingsGroup>
 
       <SettingsGroup title="Processing Animation">
@@ -398,7 +606,7 @@ function InlineRecordingModeSelector({
 }) {
   return (
     <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
-      {(["Toggle", "PushToTalk"] as RecordingMode[]).map((mode) => {
+      {(["Toggle", "PushToTalk", "HandsFree"] as RecordingMode[]).map((mode) => {
         const isActive = value === mode;
         return (
           <button
@@ -411,7 +619,7 @@ function InlineRecordingModeSelector({
                 : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
             }`}
           >
-            {mode === "PushToTalk" ? "Push to Talk" : "Toggle"}
+            {mode === "PushToTalk" ? "Push to Talk" : mode === "HandsFree" ? "Hands-free" : "Toggle"}
           </button>
         );
       })}
@@ -607,22 +815,591 @@ function ProcessingAnimationPicker({
   );
 }
 
-function MicrophoneSelector({
+function useAudioDevices(): AudioDevice[] {
+  const [devices, setDevices] = useState<AudioDevice[]>([]);
+
+  useEffect(() => {
+    invoke<AudioDevice[]>("list_audio_devices")
+      .then(setDevices)
+      .catch(() => {});
+    const unlisten = listen<AudioDevice[]>("devices-changed", (event) => {
+      setDevices(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  return devices;
+}
+
+function MaxRecordingLengthSelector({
   value,
   onChange,
 }: {
-  value: string | null;
-  onChange: (device: string | null) => void;
+  value: number;
+  onChange: (minutes: number) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {[10, 30, 60, 120].map((minutes) => {
+        const isActive = value === minutes;
+        return (
+          <button
+            key={minutes}
+            type="button"
+            onClick={() => onChange(minutes)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {minutes < 60 ? `${minutes} min` : `${minutes / 60} hr`}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+function ConfidenceThresholdSelector({
+  value,
+  onChange,
+}: {
+  value: number;
+  onChange: (threshold: number) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {[0.4, 0.5, 0.6, 0.7, 0.8].map((threshold) => {
+        const isActive = Math.abs(value - threshold) < 0.001;
+        return (
+          <button
+            key={threshold}
+            type="button"
+            onClick={() => onChange(threshold)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {Math.round(threshold * 100)}%
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+const WHISPER_DECODE_MODES: { value: WhisperDecodePreset | "Custom"; label: string }[] = [
+  { value: "Fast", label: "Fast" },
+  { value: "Standard", label: "Standard" },
+  { value: "Balanced", label: "Balanced" },
+  { value: "Accurate", label: "Accurate" },
+  { value: "Custom", label: "Custom" },
+];
+
+function WhisperDecodingSettings({
+  value,
+  onChange,
+}: {
+  value: WhisperDecodeSetting;
+  onChange: (setting: WhisperDecodeSetting) => void;
+}) {
+  const mode = value.type === "Custom" ? "Custom" : value.preset;
+
+  const selectMode = async (next: WhisperDecodePreset | "Custom") => {
+    if (next === mode) return;
+    if (next === "Custom") {
+      // Start from whatever the current preset resolves to for this model
+      try {
+        const options = await invoke<WhisperDecodeOptions>("get_whisper_decode_options");
+        onChange({ type: "Custom", options });
+      } catch (err) {
+        console.error("Failed to load decoding options:", err);
+      }
+      return;
+    }
+    onChange({ type: "Preset", preset: next });
+  };
+
+  const updateOptions = (changes: Partial<WhisperDecodeOptions>) => {
+    if (value.type !== "Custom") return;
+    onChange({ type: "Custom", options: { ...value.options, ...changes } });
+  };
+
+  return (
+    <>
+      <SettingsRow label="Decoding" description="Trade transcription speed for accuracy">
+        <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+          {WHISPER_DECODE_MODES.map((m) => {
+            const isActive = mode === m.value;
+            return (
+              <button
+                key={m.value}
+                type="button"
+                onClick={() => selectMode(m.value)}
+                className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+                  isActive
+                    ? "bg-blue-500 text-white"
+                    : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+                }`}
+              >
+                {m.label}
+              </button>
+            );
+          })}
+        </div>
+      </SettingsRow>
+      {value.type === "Custom" && (
+        <>
+          <SettingsRow label="Beam Size" description="Candidates explored per step; 1 decodes greedily">
+            <DecodeNumberSelector
+              choices={[1, 2, 5, 8]}
+              value={value.options.beamSize}
+              format={(n) => String(n)}
+              onChange={(beamSize) => updateOptions({ beamSize })}
+            />
+          </SettingsRow>
+          <SettingsRow label="Threads" description="CPU threads used for decoding">
+            <DecodeNumberSelector
+              choices={[0, 2, 4, 8]}
+              value={value.options.threads}
+              format={(n) => (n === 0 ? "Auto" : String(n))}
+              onChange={(threads) => updateOptions({ threads })}
+            />
+          </SettingsRow>
+          <SettingsRow label="No-Speech Threshold" description="Segments more likely than this to be silence are dropped">
+            <DecodeNumberSelector
+              choices={[0.4, 0.6, 0.8]}
+              value={value.options.noSpeechThreshold}
+              format={(n) => n.toFixed(1)}
+              onChange={(noSpeechThreshold) => updateOptions({ noSpeechThreshold })}
+            />
+          </SettingsRow>
+          <SettingsRow label="Temperature Fallback" description="Retry at higher temperature when decoding gets stuck or repeats">
+            <ToggleSwitch
+              checked={value.options.temperatureIncrement > 0}
+              onChange={() =>
+                updateOptions({ temperatureIncrement: value.options.temperatureIncrement > 0 ? 0 : 0.2 })
+              }
+            />
+          </SettingsRow>
+          <SettingsRow label="Suppress Non-Speech" description="Keep sound effects and music notes out of transcripts">
+            <ToggleSwitch
+              checked={value.options.suppressNonSpeech}
+              onChange={() => updateOptions({ suppressNonSpeech: !value.options.suppressNonSpeech })}
+            />
+          </SettingsRow>
+        </>
+      )}
+    </>
+  );
+}
+
+function DecodeNumberSelector({
+  choices,
+  value,
+  format,
+  onChange,
+}: {
+  choices: number[];
+  value: number;
+  format: (n: number) => string;
+  onChange: (n: number) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {choices.map((choice) => {
+        const isActive = Math.abs(value - choice) < 0.001;
+        return (
+          <button
+            key={choice}
+            type="button"
+            onClick={() => onChange(choice)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {format(choice)}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+const HISTORY_AUDIO_FORMATS: { value: HistoryAudioFormat; label: string }[] = [
+  { value: "Opus", label: "Opus" },
+  { value: "Flac", label: "FLAC" },
+  { value: "Wav", label: "WAV" },
+];
+
+function HistoryAudioFormatSelector({
+  value,
+  onChange,
+}: {
+  value: HistoryAudioFormat;
+  onChange: (format: HistoryAudioFormat) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {HISTORY_AUDIO_FORMATS.map(({ value: format, label }) => {
+        const isActive = value === format;
+        return (
+          <button
+            key={format}
+            type="button"
+            onClick={() => onChange(format)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {label}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+const RESAMPLER_QUALITIES: { value: ResamplerQuality; label: string }[] = [
+  { value: "Fast", label: "Fast" },
+  { value: "Balanced", label: "Balanced" },
+  { value: "High", label: "High" },
+];
+
+function ResamplerQualitySelector({
+  value,
+  onChange,
+}: {
+  value: ResamplerQuality;
+  onChange: (quality: ResamplerQuality) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {RESAMPLER_QUALITIES.map(({ value: quality, label }) => {
+        const isActive = value === quality;
+        return (
+          <button
+            key={quality}
+            type="button"
+            onClick={() => onChange(quality)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {label}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+function SpokenLanguageSelector({
+  languages,
+  value,
+  onChange,
+}: {
+  languages: string[];
+  value: SpokenLanguage;
+  onChange: (language: SpokenLanguage) => void;
 }) {
-  const [devices, setDevices] = useState<AudioDevice[]>([]);
   const [open, setOpen] = useState(false);
   const containerRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
-    invoke<AudioDevice[]>("list_audio_devices")
-      .then(setDevices)
-      .catch(() => {});
-  }, []);
+    if (!open) return;
+    const handler = (e: MouseEvent) => {
+      if (containerRef.current && !containerRef.current.contains(e.target as Node)) {
+        setOpen(false);
+      }
+    };
+    document.addEventListener("mousedown", handler);
+    return () => document.removeEventListener("mousedown", handler);
+  }, [open]);
+
+  // English-only models can't detect anything
+  const options: { label: string; language: SpokenLanguage }[] = [
+    ...(languages.length > 1 ? [{ label: "Detect Automatically", language: { type: "Auto" as const } }] : []),
+    ...languages.map((code) => ({
+      label: formatLanguageName(code),
+      language: { type: "Fixed" as const, code },
+    })),
+  ];
+  const isSelected = (language: SpokenLanguage) =>
+    language.type === value.type && (language.type === "Auto" || (value.type === "Fixed" && language.code === value.code));
+  const selectedLabel = value.type === "Auto" ? "Detect Automatically" : formatLanguageName(value.code);
+
+  return (
+    <div ref={containerRef} className="relative">
+      <button
+        type="button"
+        onClick={() => setOpen((o) => !o)}
+        className="flex items-center gap-1.5 text-sm text-gray-500 dark:text-gray-400 hover:text-gray-700 dark:hover:text-gray-200 transition-colors cursor-pointer"
+      >
+        <span className="truncate max-w-[180px]">{selectedLabel}</span>
+        <svg
+          className={`w-3.5 h-3.5 shrink-0 transition-transform ${open ? "rotate-180" : ""}`}
+          fill="none"
+          viewBox="0 0 24 24"
+          stroke="currentColor"
+          strokeWidth={2}
+        >
+          <path strokeLinecap="round" strokeLinejoin="round" d="M19 9l-7 7-7-7" />
+        </svg>
+      </button>
+
+      {open && (
+        <div className="absolute right-0 z-50 mt-1 min-w-[200px] max-h-[280px] overflow-y-auto rounded-lg bg-white dark:bg-[#2a2a2a] border border-[#e5e5e7] dark:border-[#3a3a3a] shadow-lg">
+          {options.map((opt) => {
+            const isActive = isSelected(opt.language);
+            return (
+              <button
+                key={opt.language.type === "Auto" ? "__auto__" : opt.language.code}
+                type="button"
+                onClick={() => {
+                  onChange(opt.language);
+                  setOpen(false);
+                }}
+                className={`w-full text-left px-3 py-2.5 text-sm flex items-center justify-between transition-colors cursor-pointer ${
+                  isActive
+                    ? "bg-blue-50 dark:bg-blue-900/30 text-blue-600 dark:text-blue-400"
+                    : "text-gray-900 dark:text-gray-100 hover:bg-gray-100 dark:hover:bg-[#333]"
+                }`}
+              >
+                <span className="truncate">{opt.label}</span>
+                {isActive && (
+                  <svg className="w-4 h-4 shrink-0 ml-2" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2.5}>
+                    <path strokeLinecap="round" strokeLinejoin="round" d="M5 13l4 4L19 7" />
+                  </svg>
+                )}
+              </button>
+            );
+          })}
+        </div>
+      )}
+    </div>
+  );
+}
+
+const VISUALIZATION_MODES: { value: VisualizationMode; label: string }[] = [
+  { value: "Envelope", label: "Envelope" },
+  { value: "Spectrum", label: "Spectrum" },
+];
+
+function VisualizationModeSelector({
+  value,
+  onChange,
+}: {
+  value: VisualizationMode;
+  onChange: (mode: VisualizationMode) => void;
+}) {
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {VISUALIZATION_MODES.map(({ value: mode, label }) => {
+        const isActive = value === mode;
+        return (
+          <button
+            key={mode}
+            type="button"
+            onClick={() => onChange(mode)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {label}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+function InputChannelSelector({
+  channels,
+  value,
+  onChange,
+}: {
+  channels: number;
+  value: InputChannel;
+  onChange: (channel: InputChannel) => void;
+}) {
+  const options: { label: string; channel: InputChannel }[] = [
+    { label: "Mix", channel: { type: "Mix" } },
+    ...Array.from({ length: channels }, (_, index) => ({
+      label: String(index + 1),
+      channel: { type: "Single" as const, index },
+    })),
+  ];
+
+  return (
+    <div className="flex rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] overflow-hidden">
+      {options.map((opt) => {
+        const isActive =
+          opt.channel.type === value.type &&
+          (opt.channel.type === "Mix" || (value.type === "Single" && opt.channel.index === value.index));
+        return (
+          <button
+            key={opt.label}
+            type="button"
+            onClick={() => onChange(opt.channel)}
+            className={`px-3 py-1.5 text-xs font-medium transition-colors cursor-pointer ${
+              isActive
+                ? "bg-blue-500 text-white"
+                : "text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a]"
+            }`}
+          >
+            {opt.label}
+          </button>
+        );
+      })}
+    </div>
+  );
+}
+
+function VocabularyEditor() {
+  const { terms, error, addTerm, removeTerm } = useVocabulary();
+  const [draft, setDraft] = useState("");
+
+  const handleAdd = async () => {
+    const term = draft.trim();
+    if (!term) return;
+    try {
+      await addTerm(term);
+      setDraft("");
+    } catch {
+      // Shown below the input
+    }
+  };
+
+  return (
+    <div className="px-4 py-3">
+      <div className="text-xs text-gray-500 dark:text-gray-400 mb-2">
+        Names, products and identifiers to spell exactly as written here
+      </div>
+      <div className="flex gap-2">
+        <input
+          type="text"
+          placeholder="Add a term..."
+          value={draft}
+          onChange={(e) => setDraft(e.target.value)}
+          onKeyDown={(e) => {
+            if (e.key === "Enter") void handleAdd();
+          }}
+          className="flex-1 min-w-0 px-3 py-1.5 text-sm rounded-md bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] text-gray-900 dark:text-gray-100 placeholder-gray-400 dark:placeholder-gray-500 focus:outline-none focus:ring-1 focus:ring-blue-500"
+        />
+        <button
+          type="button"
+          onClick={() => void handleAdd()}
+          disabled={!draft.trim()}
+          className="px-3 py-1.5 text-xs font-medium rounded-md bg-blue-500 text-white hover:bg-blue-600 disabled:opacity-50 disabled:cursor-not-allowed transition-colors cursor-pointer"
+        >
+          Add
+        </button>
+      </div>
+      {error && <div className="text-xs text-red-500 mt-1.5">{error}</div>}
+      {terms.length > 0 && (
+        <div className="flex flex-wrap gap-1.5 mt-3">
+          {terms.map((term) => (
+            <span
+              key={term}
+              className="inline-flex items-center gap-1 pl-2 pr-1 py-0.5 rounded-md bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] text-xs text-gray-700 dark:text-gray-300"
+            >
+              {term}
+              <button
+                type="button"
+                onClick={() => void removeTerm(term).catch(() => {})}
+                title={`Remove ${term}`}
+                className="p-0.5 rounded text-gray-400 hover:text-red-500 transition-colors cursor-pointer"
+              >
+                <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2.5}>
+                  <path strokeLinecap="round" strokeLinejoin="round" d="M6 18L18 6M6 6l12 12" />
+                </svg>
+              </button>
+            </span>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
+
+const MIC_TEST_WARNINGS: Record<LevelWarningKind, string> = {
+  Clipping: "Too loud — lower the input gain",
+  TooQuiet: "Too quiet — check the mic isn't muted",
+};
+
+function MicTestControl() {
+  const { state, rms, warnings, result, error, start, stop } = useMicTest();
+  // Roughly -60..0 dBFS across the meter
+  const level = rms > 0 ? Math.max(0, Math.min(1, 1 + Math.log10(rms) / 3)) : 0;
+
+  let status: string | null = null;
+  if (error) {
+    status = error;
+  } else if (warnings.length > 0) {
+    status = MIC_TEST_WARNINGS[warnings[warnings.length - 1]!];
+  } else if (result) {
+    status = `Peak ${Math.round(result.inputLevels.peakDbfs)} dB · noise ${Math.round(result.inputLevels.noiseFloorDbfs)} dB`;
+  }
+
+  return (
+    <div className="flex items-center gap-3">
+      {status && (
+        <span
+          className={`text-xs truncate max-w-[200px] ${
+            error || warnings.length > 0
+              ? "text-amber-600 dark:text-amber-400"
+              : "text-gray-500 dark:text-gray-400"
+          }`}
+        >
+          {status}
+        </span>
+      )}
+      {state === "testing" && (
+        <div className="w-20 h-1.5 rounded-full bg-gray-200 dark:bg-[#444] overflow-hidden">
+          <div className="h-full bg-blue-500 transition-[width] duration-75" style={{ width: `${level * 100}%` }} />
+        </div>
+      )}
+      <button
+        type="button"
+        onClick={() => void (state === "testing" ? stop() : start())}
+        disabled={state === "playing"}
+        className="px-3 py-1.5 text-xs font-medium rounded-lg bg-white dark:bg-[#333] border border-[#e5e5e7] dark:border-[#444] text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-[#3a3a3a] transition-colors cursor-pointer disabled:opacity-50 disabled:cursor-default"
+      >
+        {state === "testing" ? "Stop" : state === "playing" ? "Playing…" : "Test"}
+      </button>
+    </div>
+  );
+}
+
+function MicrophoneSelector({
+  devices,
+  value,
+  fallbackLabel,
+  defaultLabel = "System Default",
+  onChange,
+}: {
+  devices: AudioDevice[];
+  value: string | null;
+  fallbackLabel: string | null;
+  defaultLabel?: string;
+  onChange: (device: AudioDevice | null) => void;
+}) {
+  const [open, setOpen] = useState(false);
+  const containerRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
     if (!open) return;
@@ -636,14 +1413,15 @@ function MicrophoneSelector({
   }, [open]);
 
   const selectedLabel = value
-    ? devices.find((d) => d.name === value)?.name ?? value
-    : "System Default";
+    ? devices.find((d) => d.id === value)?.name ?? fallbackLabel ?? value
+    : fallbackLabel ?? defaultLabel;
 
-  const options: { label: string; deviceValue: string | null; isDefault: boolean }[] = [
-    { label: "System Default", deviceValue: null, isDefault: false },
+  const options: { label: string; deviceValue: string | null; device: AudioDevice | null; isDefault: boolean }[] = [
+    { label: defaultLabel, deviceValue: null, device: null, isDefault: false },
     ...devices.map((d) => ({
       label: d.name,
-      deviceValue: d.name,
+      deviceValue: d.id,
+      device: d,
       isDefault: d.isDefault,
     })),
   ];
@@ -676,7 +1454,7 @@ function MicrophoneSelector({
                 key={opt.deviceValue ?? "__default__"}
                 type="button"
                 onClick={() => {
-                  onChange(opt.deviceValue);
+                  onChange(opt.device);
                   setOpen(false);
                 }}
                 className={`w-full text-left px-3 py-2.5 text-sm flex items-center justify-between transition-colors cursor-pointer ${
@@ -2,7 +2,8 @@ import { useState, useRef, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useHistory } from "@/hooks/use-history";
 import { SettingsGroup } from "@/components/settings-group";
-import type { HistoryEntry } from "@/types";
+import { formatLanguageName } from "@/utils/format-language";
+import type { HistoryAudio, HistoryEntry, MeetingTrack, Transcript } from "@/types";
 
 function extractPeaks(audioBuffer: AudioBuffer, numBars: number): number[] {
   const channel = audioBuffer.getChannelData(0);
@@ -28,7 +29,12 @@ function extractPeaks(audioBuffer: AudioBuffer, numBars: number): number[] {
   return peaks.map((p) => Math.max(maxPeak > 0 ? p / maxPeak : 0, 0.05));
 }
 
-function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
+/** A request to play a recording from a point; a new object plays again. */
+interface SeekRequest {
+  ms: number;
+}
+
+function WaveformPlayer({ audioFileName, seek }: { audioFileName: string; seek?: SeekRequest | null }) {
   const canvasRef = useRef<HTMLCanvasElement>(null);
   const containerRef = useRef<HTMLDivElement>(null);
   const peaksRef = useRef<number[] | null>(null);
@@ -36,6 +42,7 @@ function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
   const blobUrlRef = useRef<string | null>(null);
   const animFrameRef = useRef<number>(0);
   const playingRef = useRef(false);
+  const handledSeekRef = useRef<SeekRequest | null>(null);
   const [playing, setPlaying] = useState(false);
   const [loaded, setLoaded] = useState(false);
   const [canvasWidth, setCanvasWidth] = useState(0);
@@ -61,12 +68,12 @@ function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
     let cancelled = false;
     (async () => {
       try {
-        const bytes: number[] = await invoke("get_history_audio", {
+        const audio: HistoryAudio = await invoke("get_history_audio", {
           fileName: audioFileName,
         });
         if (cancelled) return;
-        const uint8 = new Uint8Array(bytes);
-        const blob = new Blob([uint8], { type: "audio/wav" });
+        const uint8 = new Uint8Array(audio.bytes);
+        const blob = new Blob([uint8], { type: audio.mimeType });
         const url = URL.createObjectURL(blob);
         blobUrlRef.current = url;
 
@@ -168,6 +175,51 @@ function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
     animFrameRef.current = requestAnimationFrame(loop);
   }, [drawWaveform]);
 
+  const playFrom = useCallback(
+    async (ms: number) => {
+      if (!blobUrlRef.current) return;
+
+      // Already playing: just jump
+      if (playingRef.current && audioRef.current) {
+        audioRef.current.currentTime = ms / 1000;
+        return;
+      }
+
+      try {
+        const audio = new Audio(blobUrlRef.current);
+        audioRef.current = audio;
+        audio.onended = () => {
+          playingRef.current = false;
+          setPlaying(false);
+          cancelAnimationFrame(animFrameRef.current);
+          drawWaveform(0);
+        };
+        audio.onerror = () => {
+          playingRef.current = false;
+          setPlaying(false);
+          cancelAnimationFrame(animFrameRef.current);
+          drawWaveform(0);
+        };
+        audio.currentTime = ms / 1000;
+        await audio.play();
+        playingRef.current = true;
+        setPlaying(true);
+        startAnimationLoop();
+      } catch {
+        playingRef.current = false;
+        setPlaying(false);
+      }
+    },
+    [drawWaveform, startAnimationLoop],
+  );
+
+  // Play from a word picked in the timed transcript
+  useEffect(() => {
+    if (!loaded || !seek || seek === handledSeekRef.current) return;
+    handledSeekRef.current = seek;
+    void playFrom(seek.ms);
+  }, [loaded, seek, playFrom]);
+
   const handleClick = async () => {
     if (playing && audioRef.current) {
       audioRef.current.pause();
@@ -177,32 +229,7 @@ function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
       drawWaveform(0);
       return;
     }
-
-    if (!blobUrlRef.current) return;
-
-    try {
-      const audio = new Audio(blobUrlRef.current);
-      audioRef.current = audio;
-      audio.onended = () => {
-        playingRef.current = false;
-        setPlaying(false);
-        cancelAnimationFrame(animFrameRef.current);
-        drawWaveform(0);
-      };
-      audio.onerror = () => {
-        playingRef.current = false;
-        setPlaying(false);
-        cancelAnimationFrame(animFrameRef.current);
-        drawWaveform(0);
-      };
-      await audio.play();
-      playingRef.current = true;
-      setPlaying(true);
-      startAnimationLoop();
-    } catch {
-      playingRef.current = false;
-      setPlaying(false);
-    }
+    await playFrom(0);
   };
 
   if (!loaded) return null;
@@ -237,6 +264,65 @@ function WaveformPlayer({ audioFileName }: { audioFileName: string }) {
   );
 }
 
+const TRACK_LABELS: Record<MeetingTrack, string> = {
+  Microphone: "Me",
+  System: "Others",
+};
+
+/** One track of a call recording, with its own playback and transcript. */
+function CallTrack({
+  entryId,
+  track,
+  audioFileName,
+  seek,
+}: {
+  entryId: string;
+  track: MeetingTrack;
+  audioFileName: string;
+  seek?: SeekRequest | null;
+}) {
+  const [text, setText] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleTranscribe = async () => {
+    setBusy(true);
+    setError(null);
+    try {
+      setText(await invoke<string>("retranscribe_history_track", { id: entryId, track }));
+    } catch (e) {
+      setError((e as { message?: string })?.message ?? String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="mt-1">
+      <div className="flex items-center gap-2">
+        <span className="w-12 shrink-0 text-[10px] font-medium text-gray-500 dark:text-gray-400">
+          {TRACK_LABELS[track]}
+        </span>
+        <div className="flex-1 min-w-0">
+          <WaveformPlayer audioFileName={audioFileName} seek={seek} />
+        </div>
+        <button
+          type="button"
+          onClick={handleTranscribe}
+          disabled={busy}
+          className="shrink-0 text-[11px] text-blue-500 hover:text-blue-600 disabled:text-gray-400 transition-colors cursor-pointer"
+        >
+          {busy ? "Transcribing..." : "Transcribe"}
+        </button>
+      </div>
+      {text !== null && (
+        <p className="ml-14 mt-1 text-xs text-gray-600 dark:text-gray-300">{text || "No speech"}</p>
+      )}
+      {error && <p className="ml-14 mt-1 text-xs text-red-500">{error}</p>}
+    </div>
+  );
+}
+
 function formatRelativeTime(isoDate: string): string {
   const date = new Date(isoDate);
   const now = Date.now();
@@ -263,6 +349,80 @@ function formatDuration(ms: number): string {
   return `${(ms / 1000).toFixed(1)}s`;
 }
 
+/** Position in a recording as m:ss. */
+function formatTimestamp(ms: number): string {
+  const totalSeconds = Math.floor(ms / 1000);
+  const seconds = totalSeconds % 60;
+  return `${Math.floor(totalSeconds / 60)}:${seconds.toString().padStart(2, "0")}`;
+}
+
+/** Words the engine was less sure of than this are underlined. */
+const LOW_WORD_PROBABILITY = 0.5;
+
+/** Timed transcript of an entry; clicking a word plays the recording from it. */
+function TimedTranscript({ fileName, onSeek }: { fileName: string; onSeek: (ms: number) => void }) {
+  const [transcript, setTranscript] = useState<Transcript | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    invoke<Transcript>("get_history_transcript", { fileName })
+      .then((t) => {
+        if (!cancelled) setTranscript(t);
+      })
+      .catch((e) => {
+        if (!cancelled) setError((e as { message?: string })?.message ?? String(e));
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [fileName]);
+
+  if (error) return <p className="mt-2 text-xs text-red-500">{error}</p>;
+  if (!transcript) return null;
+
+  return (
+    <div className="mt-2 space-y-1">
+      {transcript.segments.map((segment, i) => (
+        <div key={i} className="flex gap-2 text-xs">
+          <button
+            type="button"
+            onClick={() => onSeek(segment.startMs)}
+            className="shrink-0 w-9 text-right tabular-nums text-gray-400 hover:text-blue-500 transition-colors cursor-pointer"
+          >
+            {formatTimestamp(segment.startMs)}
+          </button>
+          <p className="min-w-0 text-gray-700 dark:text-gray-300">
+            {segment.words.length > 0
+              ? segment.words.map((word, j) => (
+                  <span key={j}>
+                    {j > 0 && " "}
+                    <button
+                      type="button"
+                      onClick={() => onSeek(word.startMs)}
+                      title={
+                        word.probability === null
+                          ? formatTimestamp(word.startMs)
+                          : `${formatTimestamp(word.startMs)} · ${Math.round(word.probability * 100)}% confident`
+                      }
+                      className={`inline rounded hover:bg-blue-500/10 hover:text-blue-600 dark:hover:text-blue-400 transition-colors cursor-pointer ${
+                        word.probability !== null && word.probability < LOW_WORD_PROBABILITY
+                          ? "underline decoration-dotted decoration-amber-500 underline-offset-2"
+                          : ""
+                      }`}
+                    >
+                      {word.text}
+                    </button>
+                  </span>
+                ))
+              : segment.text}
+          </p>
+        </div>
+      ))}
+    </div>
+  );
+}
+
 function HistoryCard({
   entry,
   onDelete,
@@ -272,6 +432,8 @@ function HistoryCard({
 }) {
   const [expanded, setExpanded] = useState(false);
   const [copied, setCopied] = useState(false);
+  const [showTimed, setShowTimed] = useState(false);
+  const [seek, setSeek] = useState<SeekRequest | null>(null);
 
   const isLong = entry.text.length > 120;
   const displayText = expanded || !isLong ? entry.text : entry.text.slice(0, 120) + "...";
@@ -295,13 +457,27 @@ function HistoryCard({
         <button
           type="button"
           onClick={() => isLong && setExpanded((e) => !e)}
-          className={`text-left text-sm text-gray-900 dark:text-gray-100 min-w-0 flex-1 ${
+          className={`text-left text-sm text-gray-900 dark:text-gray-100 min-w-0 flex-1 whitespace-pre-line ${
             isLong ? "cursor-pointer" : ""
           }`}
         >
           {displayText}
         </button>
         <div className="flex items-center gap-1 shrink-0 mt-0.5">
+          {entry.transcriptFileName && entry.audioFileName && (
+            <button
+              type="button"
+              onClick={() => setShowTimed((s) => !s)}
+              title={showTimed ? "Hide timed transcript" : "Show timed transcript"}
+              className={`p-1 rounded hover:bg-black/[0.06] dark:hover:bg-white/[0.08] transition-colors cursor-pointer ${
+                showTimed ? "text-blue-500" : "text-gray-400 hover:text-gray-600 dark:hover:text-gray-300"
+              }`}
+            >
+              <svg className="w-3.5 h-3.5" fill="none" viewBox="0 0 24 24" stroke="currentColor" strokeWidth={2}>
+                <path strokeLinecap="round" strokeLinejoin="round" d="M12 6v6h4.5m4.5 0a9 9 0 1 1-18 0 9 9 0 0 1 18 0Z" />
+              </svg>
+            </button>
+          )}
           <button
             type="button"
             onClick={handleCopy}
@@ -330,8 +506,18 @@ function HistoryCard({
           </button>
         </div>
       </div>
-      {entry.audioFileName && (
-        <WaveformPlayer audioFileName={entry.audioFileName} />
+      {entry.systemAudioFileName ? (
+        <>
+          {entry.audioFileName && (
+            <CallTrack entryId={entry.id} track="Microphone" audioFileName={entry.audioFileName} seek={seek} />
+          )}
+          <CallTrack entryId={entry.id} track="System" audioFileName={entry.systemAudioFileName} />
+        </>
+      ) : (
+        entry.audioFileName && <WaveformPlayer audioFileName={entry.audioFileName} seek={seek} />
+      )}
+      {showTimed && entry.transcriptFileName && (
+        <TimedTranscript fileName={entry.transcriptFileName} onSeek={(ms) => setSeek({ ms })} />
       )}
       <div className="flex items-center gap-2 mt-1.5 text-xs text-gray-500 dark:text-gray-400">
         <span>{formatRelativeTime(entry.createdAt)}</span>
@@ -344,6 +530,21 @@ function HistoryCard({
             <span className="text-gray-300 dark:text-gray-600">|</span>
           </>
         )}
+        {entry.language && (
+          <>
+            <span title="Spoken language">
+              {formatLanguageName(entry.language)}
+              {entry.translated && " → English"}
+            </span>
+            <span className="text-gray-300 dark:text-gray-600">|</span>
+          </>
+        )}
+        {entry.confidence !== null && entry.confidence !== undefined && (
+          <>
+            <span title="Mean word confidence">{Math.round(entry.confidence * 100)}% conf</span>
+            <span className="text-gray-300 dark:text-gray-600">|</span>
+          </>
+        )}
         <span title="Recording duration">rec {formatDuration(entry.recordingDurationMs)}</span>
         <span className="text-gray-300 dark:text-gray-600">|</span>
         <span title="Transcription duration">transcribe {formatDuration(entry.transcriptionDurationMs)}</span>
@@ -1,5 +1,5 @@
 import { useRef } from "react";
-import type { VisualizationStyle, ProcessingAnimation, OverlayMode } from "@/types";
+import type { VisualizationStyle, ProcessingAnimation, OverlayMode, LevelWarningKind } from "@/types";
 import { VISUALIZATIONS } from "./visualizations";
 import { PulseAnimation } from "./processing-animations/pulse";
 import { FrozenFrameAnimation } from "./processing-animations/frozen-frame";
@@ -7,6 +7,8 @@ import { TypingParrotAnimation } from "./processing-animations/typing-parrot";
 
 interface RecordingPillProps {
   amplitudes: number[];
+  bands?: number[];
+  peaks?: number[];
   isRecording: boolean;
   isProcessing: boolean;
   visualization?: VisualizationStyle;
@@ -14,8 +16,16 @@ interface RecordingPillProps {
   overlayMode?: OverlayMode;
   hotkey?: string;
   micName?: string;
+  levelWarning?: LevelWarningKind;
+  /** Percent of the transcription done, once the worker reports it. */
+  progress?: number;
 }
 
+const LEVEL_WARNINGS: Record<LevelWarningKind, string> = {
+  Clipping: "Input too loud",
+  TooQuiet: "Input too quiet",
+};
+
 const VIZ_WIDTH = 440;
 const VIZ_PADDING = 24; // px-3 = 12px per side
 const VIZ_HEIGHT = 48;
@@ -26,6 +36,8 @@ const MINI_HEIGHT = 32;
 
 export function RecordingPill({
   amplitudes,
+  bands,
+  peaks,
   isRecording,
   isProcessing,
   visualization = "Bars",
@@ -33,6 +45,8 @@ export function RecordingPill({
   overlayMode = "Full",
   hotkey,
   micName,
+  levelWarning,
+  progress,
 }: RecordingPillProps) {
   const lastAmplitudesRef = useRef<number[]>([]);
 
@@ -56,6 +70,8 @@ export function RecordingPill({
       return (
         <VizComponent
           amplitudes={amplitudes}
+          bands={bands}
+          peaks={peaks}
           width={vizWidth - vizPadding}
           height={vizHeight}
         />
@@ -109,6 +125,11 @@ export function RecordingPill({
             <path strokeLinecap="round" strokeLinejoin="round" d="M12 18.75a6 6 0 0 0 6-6v-1.5m-6 7.5a6 6 0 0 1-6-6v-1.5m6 7.5v3.75m-3.75 0h7.5M12 15.75a3 3 0 0 1-3-3V4.5a3 3 0 1 1 6 0v8.25a3 3 0 0 1-3 3Z" />
           </svg>
           <span>{micName ?? "Default"}</span>
+          {levelWarning && (
+            <span className="text-amber-600 dark:text-amber-400 font-medium">
+              {LEVEL_WARNINGS[levelWarning]}
+            </span>
+          )}
         </div>
 
         {/* Right: action labels + hotkey badges */}
@@ -120,7 +141,9 @@ export function RecordingPill({
             </>
           )}
           {isProcessing && (
-            <span className="text-yellow-600 dark:text-yellow-400 font-medium">Processing...</span>
+            <span className="text-yellow-600 dark:text-yellow-400 font-medium">
+              {progress !== undefined ? `Processing ${Math.round(progress)}%` : "Processing..."}
+            </span>
           )}
           <span className="text-gray-500 dark:text-gray-400">Cancel</span>
           <span className="kbd">esc</span>
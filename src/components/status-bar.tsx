@@ -1,5 +1,6 @@
 import { useEffect, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { usePreferences } from "@/hooks/use-preferences";
 import type { AudioDevice } from "@/types";
 
@@ -20,6 +21,12 @@ export function StatusBar({ onToggleSidebar, sidebarCollapsed }: StatusBarProps)
     invoke<AudioDevice[]>("list_audio_devices")
       .then(setDevices)
       .catch(() => {});
+    const unlisten = listen<AudioDevice[]>("devices-changed", (event) => {
+      setDevices(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   useEffect(() => {
@@ -33,21 +40,29 @@ export function StatusBar({ onToggleSidebar, sidebarCollapsed }: StatusBarProps)
     return () => document.removeEventListener("mousedown", handler);
   }, [open]);
 
-  const options: { label: string; deviceValue: string | null; isDefault: boolean }[] = [
-    { label: "System Default", deviceValue: null, isDefault: false },
+  const options: { label: string; deviceValue: string | null; deviceName: string | null; isDefault: boolean }[] = [
+    { label: "System Default", deviceValue: null, deviceName: null, isDefault: false },
     ...devices.map((d) => ({
       label: d.name,
-      deviceValue: d.name,
+      deviceValue: d.id,
+      deviceName: d.name,
       isDefault: d.isDefault,
     })),
   ];
 
-  const handleSelect = async (deviceValue: string | null) => {
+  // Preferences saved before device ids existed only have the name
+  const isSelected = (opt: (typeof options)[number]) =>
+    preferences?.selectedAudioDeviceId
+      ? opt.deviceValue === preferences.selectedAudioDeviceId
+      : opt.deviceName === (preferences?.selectedAudioDevice ?? null);
+
+  const handleSelect = async (opt: (typeof options)[number]) => {
     setOpen(false);
     if (!preferences) return;
     await updatePreferences({
       ...preferences,
-      selectedAudioDevice: deviceValue,
+      selectedAudioDevice: opt.deviceName,
+      selectedAudioDeviceId: opt.deviceValue,
     });
   };
 
@@ -94,12 +109,12 @@ export function StatusBar({ onToggleSidebar, sidebarCollapsed }: StatusBarProps)
         {open && (
           <div className="absolute right-0 z-50 mt-1 min-w-[240px] rounded-lg bg-white dark:bg-[#2a2a2a] border border-[#e5e5e7] dark:border-[#3a3a3a] shadow-lg overflow-hidden">
             {options.map((opt) => {
-              const isActive = opt.deviceValue === (preferences?.selectedAudioDevice ?? null);
+              const isActive = isSelected(opt);
               return (
                 <button
                   key={opt.deviceValue ?? "__default__"}
                   type="button"
-                  onClick={() => handleSelect(opt.deviceValue)}
+                  onClick={() => handleSelect(opt)}
                   className={`w-full text-left px-3 py-2 text-xs flex items-center justify-between transition-colors cursor-pointer ${
                     isActive
                       ? "bg-blue-50 dark:bg-blue-900/30 text-blue-600 dark:text-blue-400"
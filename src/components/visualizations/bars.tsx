@@ -2,16 +2,20 @@ import { useRef, useEffect } from "react";
 
 interface SoundwaveBarsProps {
   amplitudes: number[];
+  bands?: number[];
+  peaks?: number[];
   width: number;
   height: number;
   paused?: boolean;
 }
 
-export function SoundwaveBars({ amplitudes, width, height, paused = false }: SoundwaveBarsProps) {
+export function SoundwaveBars({ amplitudes, bands, peaks, width, height, paused = false }: SoundwaveBarsProps) {
   const canvasRef = useRef<HTMLCanvasElement>(null);
   const animationRef = useRef<number>(0);
   const targetAmplitudes = useRef<number[]>([]);
   const currentAmplitudes = useRef<number[]>([]);
+  const targetPeaks = useRef<number[] | null>(null);
+  const spectrumRef = useRef(false);
   const pausedRef = useRef(false);
 
   useEffect(() => {
@@ -20,10 +24,13 @@ export function SoundwaveBars({ amplitudes, width, height, paused = false }: Sou
 
   useEffect(() => {
     if (!pausedRef.current) {
+      // Spectrum mode: band levels are already scaled to 0..1
+      spectrumRef.current = bands !== undefined;
       targetAmplitudes.current =
-        amplitudes.length > 0 ? amplitudes : new Array(48).fill(0);
+        bands ?? (amplitudes.length > 0 ? amplitudes : new Array(48).fill(0));
+      targetPeaks.current = peaks ?? null;
     }
-  }, [amplitudes]);
+  }, [amplitudes, bands, peaks]);
 
   useEffect(() => {
     const canvas = canvasRef.current;
@@ -56,7 +63,7 @@ export function SoundwaveBars({ amplitudes, width, height, paused = false }: Sou
           const raw = target[srcIdx] ?? 0;
 
           // Light boost, no artificial baseline — silence = flat
-          const boosted = Math.min(raw * 8, 1);
+          const boosted = spectrumRef.current ? raw : Math.min(raw * 8, 1);
 
           const prev = current[i] ?? 0;
           // Fast attack (0.6), slower release (0.12) — snappy response, smooth decay
@@ -89,6 +96,22 @@ export function SoundwaveBars({ amplitudes, width, height, paused = false }: Sou
         ctx.fill();
       }
 
+      // Peak-hold markers above and below each band
+      const peaks = targetPeaks.current;
+      if (peaks && peaks.length > 0) {
+        ctx.shadowBlur = 0;
+        ctx.fillStyle = isDark ? "#f3f4f6" : "#374151";
+        for (let i = 0; i < numBars; i++) {
+          const peak = Math.min(peaks[Math.floor((i / numBars) * peaks.length)] ?? 0, 1);
+          if (peak <= 0) continue;
+          const offset = (peak * height * 0.92) / 2;
+          const x = i * (barWidth + gap);
+          ctx.globalAlpha = 0.4 + peak * 0.6;
+          ctx.fillRect(x, centerY - offset - 1, barWidth, 1.5);
+          ctx.fillRect(x, centerY + offset - 0.5, barWidth, 1.5);
+        }
+      }
+
       ctx.shadowColor = "transparent";
       ctx.shadowBlur = 0;
       ctx.globalAlpha = 1;
@@ -9,6 +9,10 @@ export { SoundwaveRainbow } from "./rainbow";
 
 export interface VisualizationProps {
   amplitudes: number[];
+  /** Frequency band levels (0..1), present when the style uses the spectrum mode. */
+  bands?: number[];
+  /** Peak-hold level of each band. */
+  peaks?: number[];
   width: number;
   height: number;
   paused?: boolean;
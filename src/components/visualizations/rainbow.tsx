@@ -2,6 +2,8 @@ import { useRef, useEffect } from "react";
 
 interface SoundwaveRainbowProps {
   amplitudes: number[];
+  bands?: number[];
+  peaks?: number[];
   width: number;
   height: number;
   paused?: boolean;
@@ -33,11 +35,13 @@ function barColor(index: number, total: number): string {
   return `rgb(${r},${g},${b})`;
 }
 
-export function SoundwaveRainbow({ amplitudes, width, height, paused = false }: SoundwaveRainbowProps) {
+export function SoundwaveRainbow({ amplitudes, bands, peaks, width, height, paused = false }: SoundwaveRainbowProps) {
   const canvasRef = useRef<HTMLCanvasElement>(null);
   const animationRef = useRef<number>(0);
   const targetAmplitudes = useRef<number[]>([]);
   const currentAmplitudes = useRef<number[]>([]);
+  const targetPeaks = useRef<number[] | null>(null);
+  const spectrumRef = useRef(false);
   const pausedRef = useRef(false);
 
   useEffect(() => {
@@ -46,10 +50,13 @@ export function SoundwaveRainbow({ amplitudes, width, height, paused = false }:
 
   useEffect(() => {
     if (!pausedRef.current) {
+      // Spectrum mode: band levels are already scaled to 0..1
+      spectrumRef.current = bands !== undefined;
       targetAmplitudes.current =
-        amplitudes.length > 0 ? amplitudes : new Array(48).fill(0);
+        bands ?? (amplitudes.length > 0 ? amplitudes : new Array(48).fill(0));
+      targetPeaks.current = peaks ?? null;
     }
-  }, [amplitudes]);
+  }, [amplitudes, bands, peaks]);
 
   useEffect(() => {
     const canvas = canvasRef.current;
@@ -76,6 +83,12 @@ export function SoundwaveRainbow({ amplitudes, width, height, paused = false }:
         const current = currentAmplitudes.current;
 
         for (let i = 0; i < numBars; i++) {
+          if (spectrumRef.current) {
+            // Spread the bands across all bars, no boost needed
+            const raw = target[Math.floor((i / numBars) * target.length)] ?? 0;
+            current[i] = (current[i] ?? 0) + (raw - (current[i] ?? 0)) * 0.28;
+            continue;
+          }
           const raw = target[i] ?? 0;
           // Aggressively boost so even quiet speech fills most of the height
           const boosted = Math.min(raw * 14 + 0.25, 1);
@@ -104,6 +117,22 @@ export function SoundwaveRainbow({ amplitudes, width, height, paused = false }:
         ctx.fill();
       }
 
+      // Peak-hold markers above and below each band
+      const peaks = targetPeaks.current;
+      if (peaks && peaks.length > 0) {
+        ctx.shadowBlur = 0;
+        for (let i = 0; i < numBars; i++) {
+          const peak = Math.min(peaks[Math.floor((i / numBars) * peaks.length)] ?? 0, 1);
+          if (peak <= 0) continue;
+          const offset = (peak * height * 0.95) / 2;
+          const x = i * (barWidth + 2);
+          ctx.fillStyle = barColor(i, numBars);
+          ctx.globalAlpha = 0.5 + peak * 0.5;
+          ctx.fillRect(x, centerY - offset - 1, barWidth, 1.5);
+          ctx.fillRect(x, centerY + offset - 0.5, barWidth, 1.5);
+        }
+      }
+
       ctx.shadowColor = "transparent";
       ctx.shadowBlur = 0;
       ctx.globalAlpha = 1;
@@ -5,11 +5,15 @@ import type { AudioEvent } from "@/types";
 interface UseAudioStreamReturn {
   amplitudes: number[];
   rms: number;
+  bands?: number[];
+  peaks?: number[];
 }
 
 export function useAudioStream(): UseAudioStreamReturn {
   const [amplitudes, setAmplitudes] = useState<number[]>([]);
   const [rms, setRms] = useState(0);
+  const [bands, setBands] = useState<number[] | undefined>();
+  const [peaks, setPeaks] = useState<number[] | undefined>();
 
   useEffect(() => {
     const unlisten = listen<AudioEvent>("amplitude-update", (event) => {
@@ -17,6 +21,8 @@ export function useAudioStream(): UseAudioStreamReturn {
       if (payload.type === "AmplitudeUpdate") {
         setAmplitudes(payload.amplitudes);
         setRms(payload.rms);
+        setBands(payload.bands);
+        setPeaks(payload.peaks);
       }
     });
 
@@ -25,5 +31,5 @@ export function useAudioStream(): UseAudioStreamReturn {
     };
   }, []);
 
-  return { amplitudes, rms };
+  return { amplitudes, rms, bands, peaks };
 }
@@ -0,0 +1,84 @@
+import { useState, useCallback, useEffect, useRef } from "react";
+import { invoke, Channel } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import type { AudioEvent, LevelWarningKind, MicTestResult } from "@/types";
+
+type MicTestState = "idle" | "testing" | "playing";
+
+interface UseMicTestReturn {
+  state: MicTestState;
+  rms: number;
+  warnings: LevelWarningKind[];
+  result: MicTestResult | null;
+  error: string | null;
+  start: () => Promise<void>;
+  stop: () => Promise<void>;
+}
+
+export function useMicTest(): UseMicTestReturn {
+  const [state, setState] = useState<MicTestState>("idle");
+  const [rms, setRms] = useState(0);
+  const [warnings, setWarnings] = useState<LevelWarningKind[]>([]);
+  const [result, setResult] = useState<MicTestResult | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const stateRef = useRef<MicTestState>("idle");
+  stateRef.current = state;
+  const playbackTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+  const stop = useCallback(async () => {
+    if (stateRef.current !== "testing") return;
+    try {
+      const res = await invoke<MicTestResult>("stop_mic_test");
+      setResult(res);
+      setRms(0);
+      if (res.playbackError) {
+        setError(res.playbackError);
+        setState("idle");
+        return;
+      }
+      // The recording plays back in the background for as long as it was recorded
+      setState("playing");
+      playbackTimerRef.current = setTimeout(() => setState("idle"), res.durationMs);
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+      setState("idle");
+    }
+  }, []);
+
+  const start = useCallback(async () => {
+    if (playbackTimerRef.current) clearTimeout(playbackTimerRef.current);
+    setError(null);
+    setResult(null);
+    setWarnings([]);
+
+    const channel = new Channel<AudioEvent>();
+    channel.onmessage = (event: AudioEvent) => {
+      if (event.type === "AmplitudeUpdate") {
+        setRms(event.rms);
+      } else if (event.type === "LevelWarning") {
+        setWarnings((prev) => (prev.includes(event.kind) ? prev : [...prev, event.kind]));
+      }
+    };
+
+    try {
+      await invoke("start_mic_test", { onEvent: channel });
+      setState("testing");
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+      setState("idle");
+    }
+  }, []);
+
+  // The backend stops recording at its time limit; collect the result then
+  useEffect(() => {
+    const unlisten = listen("mic-test-limit-reached", () => {
+      void stop();
+    });
+    return () => {
+      void unlisten.then((f) => f());
+      if (playbackTimerRef.current) clearTimeout(playbackTimerRef.current);
+    };
+  }, [stop]);
+
+  return { state, rms, warnings, result, error, start, stop };
+}
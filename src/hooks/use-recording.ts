@@ -2,15 +2,20 @@ import { useState, useCallback, useRef, useEffect } from "react";
 import { invoke, Channel } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { emit } from "@tauri-apps/api/event";
-import type { TranscriptionResult, AudioEvent, UserPreferences, PermissionStatus } from "@/types";
+import type { TranscriptionResult, AudioEvent, UserPreferences, PermissionStatus, CommandError } from "@/types";
 
 type RecordingState = "idle" | "recording" | "transcribing" | "injecting";
 
+interface StartRecordingOptions {
+  /** Translate the speech into English instead of transcribing it as spoken. */
+  translate?: boolean;
+}
+
 interface UseRecordingReturn {
   state: RecordingState;
   error: string | null;
   lastResult: TranscriptionResult | null;
-  startRecording: () => Promise<string>;
+  startRecording: (options?: StartRecordingOptions) => Promise<string>;
   stopRecording: () => Promise<TranscriptionResult>;
   cancelRecording: () => Promise<void>;
   injectText: (text: string) => Promise<void>;
@@ -28,7 +33,7 @@ export function useRecording(
   const stateRef = useRef<RecordingState>("idle");
   stateRef.current = state;
 
-  const startRecording = useCallback(async () => {
+  const startRecording = useCallback(async (options?: StartRecordingOptions) => {
     try {
       setError(null);
 
@@ -53,6 +58,7 @@ export function useRecording(
 
       const sessionId = await invoke<string>("start_recording", {
         onEvent: channel,
+        translate: options?.translate ?? false,
       });
       setState("recording");
       return sessionId;
@@ -80,7 +86,10 @@ export function useRecording(
         // Fetch current preferences from Rust to get the latest target mode
         const prefs = await invoke<UserPreferences>("get_preferences");
 
-        if (prefs.targetMode.type === "WindowPicker") {
+        if (result.held) {
+          // Let the user look at an unsure transcript before it goes anywhere
+          await emit("show-picker", { text: result.text, confidence: result.confidence });
+        } else if (prefs.targetMode.type === "WindowPicker") {
           await emit("show-picker", { text: result.text });
         } else {
           setState("injecting");
@@ -91,9 +100,13 @@ export function useRecording(
       setState("idle");
       return result;
     } catch (e) {
+      setState("idle");
+      // Cancelled with Escape while transcribing; nothing went wrong
+      if ((e as CommandError)?.code === "TranscriptionCancelled") {
+        throw e;
+      }
       const msg = e instanceof Error ? e.message : String(e);
       setError(msg);
-      setState("idle");
       throw e;
     }
   }, []);
@@ -122,9 +135,9 @@ export function useRecording(
 
   // Listen for hotkey events from the backend (supports toggle and push-to-talk)
   useEffect(() => {
-    const unlisten1 = listen("hotkey-start-recording", () => {
+    const unlisten1 = listen<{ translate: boolean }>("hotkey-start-recording", (event) => {
       if (stateRef.current === "idle") {
-        void startRecording();
+        void startRecording({ translate: event.payload?.translate ?? false });
       }
     });
 
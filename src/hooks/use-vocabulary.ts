@@ -0,0 +1,42 @@
+import { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+interface UseVocabularyReturn {
+  terms: string[];
+  error: string | null;
+  addTerm: (term: string) => Promise<void>;
+  removeTerm: (term: string) => Promise<void>;
+}
+
+export function useVocabulary(): UseVocabularyReturn {
+  const [terms, setTerms] = useState<string[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<string[]>("get_vocabulary")
+      .then(setTerms)
+      .catch((e) => setError(e instanceof Error ? e.message : String(e)));
+  }, []);
+
+  const addTerm = useCallback(async (term: string) => {
+    try {
+      setError(null);
+      setTerms(await invoke<string[]>("add_vocabulary_term", { term }));
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+      throw e;
+    }
+  }, []);
+
+  const removeTerm = useCallback(async (term: string) => {
+    try {
+      setError(null);
+      setTerms(await invoke<string[]>("remove_vocabulary_term", { term }));
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+      throw e;
+    }
+  }, []);
+
+  return { terms, error, addTerm, removeTerm };
+}
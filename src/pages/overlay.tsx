@@ -5,7 +5,7 @@ import { getCurrentWindow } from "@tauri-apps/api/window";
 import { RecordingPill } from "@/components/recording-pill";
 import { useAudioStream } from "@/hooks/use-audio-stream";
 import { formatHotkeyForDisplay } from "@/utils/format-hotkey";
-import type { VisualizationStyle, ProcessingAnimation, OverlayMode, UserPreferences } from "@/types";
+import type { AudioEvent, LevelWarningKind, VisualizationStyle, ProcessingAnimation, OverlayMode, UserPreferences, TranscriptionJobEvent } from "@/types";
 
 export function OverlayPage() {
   const [visible, setVisible] = useState(false);
@@ -16,7 +16,10 @@ export function OverlayPage() {
   const [hotkeyDisplay, setHotkeyDisplay] = useState<string>("");
   const [micName, setMicName] = useState<string>("Default");
   const [isDragging, setIsDragging] = useState(false);
-  const { amplitudes } = useAudioStream();
+  const [levelWarning, setLevelWarning] = useState<LevelWarningKind | null>(null);
+  const [progress, setProgress] = useState<number | undefined>(undefined);
+  const dictationJobRef = useRef<number | null>(null);
+  const { amplitudes, bands, peaks } = useAudioStream();
   const saveTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null);
 
   // Extract display hotkey and mic name from preferences
@@ -56,6 +59,9 @@ export function OverlayPage() {
         .catch(() => {});
       setVisible(true);
       setProcessing(false);
+      setLevelWarning(null);
+      setProgress(undefined);
+      dictationJobRef.current = null;
     });
     const unlisten2 = listen("recording-stopped", () => {
       setProcessing(true);
@@ -68,12 +74,32 @@ export function OverlayPage() {
       setVisible(false);
       setProcessing(false);
     });
+    const unlisten5 = listen<AudioEvent>("level-warning", (event) => {
+      if (event.payload.type === "LevelWarning") {
+        setLevelWarning(event.payload.kind);
+      }
+    });
+    const unlisten6 = listen("transcription-cancelled", () => {
+      setVisible(false);
+      setProcessing(false);
+    });
+    const unlisten7 = listen<TranscriptionJobEvent>("transcription-job", (event) => {
+      const job = event.payload;
+      if (job.type === "Started" && job.kind === "Dictation") {
+        dictationJobRef.current = job.jobId;
+      } else if (job.type === "Progress" && job.jobId === dictationJobRef.current) {
+        setProgress(job.percent);
+      }
+    });
 
     return () => {
       void unlisten1.then((f) => f());
       void unlisten2.then((f) => f());
       void unlisten3.then((f) => f());
       void unlisten4.then((f) => f());
+      void unlisten5.then((f) => f());
+      void unlisten6.then((f) => f());
+      void unlisten7.then((f) => f());
     };
   }, []);
 
@@ -121,6 +147,8 @@ export function OverlayPage() {
       >
         <RecordingPill
           amplitudes={amplitudes}
+          bands={bands}
+          peaks={peaks}
           isRecording={!processing}
           isProcessing={processing}
           visualization={visualization}
@@ -128,6 +156,8 @@ export function OverlayPage() {
           overlayMode={overlayMode}
           hotkey={hotkeyDisplay || undefined}
           micName={micName}
+          levelWarning={levelWarning ?? undefined}
+          progress={progress}
         />
       </div>
     </div>
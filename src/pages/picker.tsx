@@ -9,15 +9,21 @@ const ITEM_WIDTH = 80;
 const PADDING = 24;
 const MIN_WIDTH = 200;
 const WINDOW_HEIGHT = 140;
+/** Room for the transcript preview shown with held, low-confidence results. */
+const HELD_EXTRA_HEIGHT = 76;
+const HELD_MIN_WIDTH = 360;
 
 export function PickerPage() {
   const [windows, setWindows] = useState<TargetWindow[]>([]);
   const [pendingText, setPendingText] = useState<string | null>(null);
+  /** Set when the text was held back for its low confidence. */
+  const [confidence, setConfidence] = useState<number | null>(null);
   const [selectedIndex, setSelectedIndex] = useState(0);
   const buttonsRef = useRef<(HTMLButtonElement | null)[]>([]);
 
   const hidePicker = useCallback(async () => {
     setPendingText(null);
+    setConfidence(null);
     setWindows([]);
     setSelectedIndex(0);
     await getCurrentWindow().hide();
@@ -52,8 +58,10 @@ export function PickerPage() {
 
   // Listen for show-picker event
   useEffect(() => {
-    const unlisten = listen<{ text: string }>("show-picker", async (event) => {
+    const unlisten = listen<{ text: string; confidence?: number | null }>("show-picker", async (event) => {
+      const held = event.payload.confidence ?? null;
       setPendingText(event.payload.text);
+      setConfidence(held);
       setSelectedIndex(0);
 
       let apps: TargetWindow[] = [];
@@ -66,8 +74,9 @@ export function PickerPage() {
 
       // Resize window to fit content
       const win = getCurrentWindow();
-      const width = Math.max(MIN_WIDTH, apps.length * ITEM_WIDTH + PADDING);
-      await win.setSize(new LogicalSize(width, WINDOW_HEIGHT));
+      const width = Math.max(held === null ? MIN_WIDTH : HELD_MIN_WIDTH, apps.length * ITEM_WIDTH + PADDING);
+      const height = WINDOW_HEIGHT + (held === null ? 0 : HELD_EXTRA_HEIGHT);
+      await win.setSize(new LogicalSize(width, height));
       await win.center();
       await win.show();
       await win.setFocus();
@@ -141,40 +150,50 @@ export function PickerPage() {
 
   return (
     <div className="flex items-center justify-center h-screen bg-transparent p-2">
-      <div className="flex gap-1 bg-gray-900/95 backdrop-blur-sm rounded-xl p-3 border border-gray-700/50 shadow-2xl">
-        {windows.length === 0 ? (
-          <p className="text-gray-400 text-sm px-4 py-2">No windows found</p>
-        ) : (
-          windows.map((w, i) => (
-            <button
-              key={w.windowId}
-              ref={(el) => {
-                buttonsRef.current[i] = el;
-              }}
-              onClick={() => handleSelect(w.processId)}
-              className={`flex flex-col items-center gap-1 px-2 py-2 rounded-lg transition-colors w-[72px] ${
-                i === selectedIndex
-                  ? "bg-blue-600/40 ring-2 ring-blue-500"
-                  : "hover:bg-gray-700/60"
-              }`}
-            >
-              {w.icon ? (
-                <img
-                  src={w.icon}
-                  alt={w.appName}
-                  className="w-10 h-10 rounded-lg shrink-0"
-                />
-              ) : (
-                <div className="w-10 h-10 rounded-lg bg-gray-700 flex items-center justify-center text-lg font-bold text-white shrink-0">
-                  {w.appName.charAt(0).toUpperCase()}
-                </div>
-              )}
-              <span className="text-xs text-gray-300 truncate w-full text-center">
-                {w.appName}
-              </span>
-            </button>
-          ))
+      <div className="flex flex-col gap-2 max-w-full bg-gray-900/95 backdrop-blur-sm rounded-xl p-3 border border-gray-700/50 shadow-2xl">
+        {confidence !== null && (
+          <div className="px-1">
+            <p className="text-[11px] text-amber-400">
+              Low confidence ({Math.round(confidence * 100)}%) — pick where to send it, or Esc to copy
+            </p>
+            <p className="mt-1 text-sm text-gray-100 line-clamp-2">{pendingText}</p>
+          </div>
         )}
+        <div className="flex gap-1">
+          {windows.length === 0 ? (
+            <p className="text-gray-400 text-sm px-4 py-2">No windows found</p>
+          ) : (
+            windows.map((w, i) => (
+              <button
+                key={w.windowId}
+                ref={(el) => {
+                  buttonsRef.current[i] = el;
+                }}
+                onClick={() => handleSelect(w.processId)}
+                className={`flex flex-col items-center gap-1 px-2 py-2 rounded-lg transition-colors w-[72px] ${
+                  i === selectedIndex
+                    ? "bg-blue-600/40 ring-2 ring-blue-500"
+                    : "hover:bg-gray-700/60"
+                }`}
+              >
+                {w.icon ? (
+                  <img
+                    src={w.icon}
+                    alt={w.appName}
+                    className="w-10 h-10 rounded-lg shrink-0"
+                  />
+                ) : (
+                  <div className="w-10 h-10 rounded-lg bg-gray-700 flex items-center justify-center text-lg font-bold text-white shrink-0">
+                    {w.appName.charAt(0).toUpperCase()}
+                  </div>
+                )}
+                <span className="text-xs text-gray-300 truncate w-full text-center">
+                  {w.appName}
+                </span>
+              </button>
+            ))
+          )}
+        </div>
       </div>
     </div>
   );
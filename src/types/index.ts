@@ -11,6 +11,58 @@ export interface HistoryEntry {
   transcriptionDurationMs: number;
   audioDevice: string | null;
   audioFileName: string | null;
+  sourceFileName: string | null;
+  trimmedStartMs: number;
+  trimmedEndMs: number;
+  inputLevels: InputLevelMetrics | null;
+  systemAudioFileName: string | null;
+  language: string | null;
+  translated: boolean;
+  transcriptFileName: string | null;
+  confidence: number | null;
+}
+
+export interface TimedWord {
+  text: string;
+  startMs: number;
+  endMs: number;
+  probability: number | null;
+}
+
+export interface TranscriptSegment {
+  text: string;
+  startMs: number;
+  endMs: number;
+  words: TimedWord[];
+}
+
+export interface Transcript {
+  text: string;
+  language: string | null;
+  segments: TranscriptSegment[];
+}
+
+export type MeetingTrack = "Microphone" | "System";
+
+export interface InputLevelMetrics {
+  peakDbfs: number;
+  clippingRatio: number;
+  noiseFloorDbfs: number;
+  speechLevelDbfs: number;
+}
+
+export type LevelWarningKind = "Clipping" | "TooQuiet";
+
+export interface MicTestResult {
+  deviceName: string;
+  durationMs: number;
+  inputLevels: InputLevelMetrics;
+  playbackError: string | null;
+}
+
+export interface HistoryAudio {
+  bytes: number[];
+  mimeType: string;
 }
 
 export interface TranscriptionModel {
@@ -70,6 +122,7 @@ export interface HotkeyBinding {
   action: HotkeyAction;
   keyCombination: string;
   enabled: boolean;
+  translate: boolean;
 }
 
 export type HotkeyAction =
@@ -95,15 +148,90 @@ export interface UserPreferences {
   overlayPosition: OverlayPosition;
   overlayOpacity: number;
   overlayVisualization: VisualizationStyle;
+  visualizationModes: Partial<Record<VisualizationStyle, VisualizationMode>>;
   overlayProcessingAnimation: ProcessingAnimation;
   overlayMode: OverlayMode;
   overlayCustomPosition: OverlayCustomPosition | null;
   selectedAudioDevice: string | null;
+  selectedAudioDeviceId: string | null;
   launchAtLogin: boolean;
   removeFillerWords: boolean;
+  handsFree: HandsFreeSettings;
+  liveTranscription: boolean;
+  warmMicrophone: WarmMicrophoneSettings;
+  audioProcessing: AudioProcessingSettings;
+  deviceFailover: boolean;
+  inputChannels: Record<string, InputChannel>;
+  maxRecordingMinutes: number;
+  audioSource: AudioSourceSetting;
+  historyAudioFormat: HistoryAudioFormat;
+  resamplerQuality: ResamplerQuality;
+  systemAudio: SystemAudioSettings;
+  spokenLanguage: SpokenLanguage;
+  lowConfidence: LowConfidenceSettings;
+  whisperDecoding: WhisperDecodeSetting;
 }
 
-export type RecordingMode = "PushToTalk" | "Toggle";
+export type RecordingMode = "PushToTalk" | "Toggle" | "HandsFree";
+
+export interface HandsFreeSettings {
+  silenceThreshold: number;
+  trailingSilenceMs: number;
+  minSpeechMs: number;
+}
+
+export interface SystemAudioSettings {
+  enabled: boolean;
+  deviceId: string | null;
+}
+
+export interface LowConfidenceSettings {
+  hold: boolean;
+  threshold: number;
+}
+
+export interface WhisperDecodeOptions {
+  beamSize: number;
+  temperature: number;
+  temperatureIncrement: number;
+  noSpeechThreshold: number;
+  threads: number;
+  suppressNonSpeech: boolean;
+}
+
+export type WhisperDecodePreset = "Fast" | "Standard" | "Balanced" | "Accurate";
+
+export type WhisperDecodeSetting =
+  | { type: "Preset"; preset: WhisperDecodePreset }
+  | { type: "Custom"; options: WhisperDecodeOptions };
+
+export interface WarmMicrophoneSettings {
+  enabled: boolean;
+  preRollMs: number;
+}
+
+export type HistoryAudioFormat = "Wav" | "Flac" | "Opus";
+
+export type ResamplerQuality = "Fast" | "Balanced" | "High";
+
+export type SpokenLanguage =
+  | { type: "Auto" }
+  | { type: "Fixed"; code: string };
+
+export type AudioSourceSetting =
+  | { type: "Microphone" }
+  | { type: "File"; path: string }
+  | { type: "Synthetic" };
+
+export type InputChannel =
+  | { type: "Mix" }
+  | { type: "Single"; index: number };
+
+export interface AudioProcessingSettings {
+  highPass: boolean;
+  noiseSuppression: boolean;
+  autoGain: boolean;
+}
 
 export type TargetMode =
   | { type: "ActiveWindow" }
@@ -119,6 +247,8 @@ export type OverlayPosition =
 
 export type VisualizationStyle = "Bars" | "Sine" | "Rainbow";
 
+export type VisualizationMode = "Envelope" | "Spectrum";
+
 export type ProcessingAnimation = "Pulse" | "FrozenFrame" | "TypingParrot";
 
 export type OverlayMode = "None" | "Full" | "Mini";
@@ -129,22 +259,58 @@ export interface OverlayCustomPosition {
 }
 
 export interface AudioDevice {
+  id: string;
   name: string;
   isDefault: boolean;
+  channels: number;
+  isLoopback: boolean;
 }
 
 export interface TranscriptionResult {
   sessionId: string;
   text: string;
   durationMs: number;
+  noSpeech: boolean;
+  language: string | null;
+  confidence: number | null;
+  /** Confidence was below the threshold; confirm before injecting. */
+  held: boolean;
 }
 
 export type AudioEvent =
   | { type: "RecordingStarted" }
   | { type: "RecordingStopped" }
-  | { type: "AmplitudeUpdate"; amplitudes: number[]; rms: number }
+  | {
+      type: "AmplitudeUpdate";
+      amplitudes: number[];
+      rms: number;
+      bands?: number[];
+      peaks?: number[];
+    }
   | { type: "TranscriptionStarted" }
-  | { type: "TranscriptionCompleted"; text: string };
+  | {
+      type: "TranscriptionCompleted";
+      text: string;
+      confidence: number | null;
+      /** False for engines that never report confidence (Parakeet). */
+      confidenceAvailable: boolean;
+      segments: TranscriptSegment[];
+    }
+  | { type: "NoSpeechDetected" }
+  | { type: "PartialTranscription"; text: string; isFinal: boolean }
+  | { type: "DeviceLost"; fallbackDevice: string | null }
+  | { type: "LevelWarning"; kind: LevelWarningKind }
+  | { type: "RecordingLimitWarning"; remainingSecs: number };
+
+export type TranscriptionJobKind = "Dictation" | "LivePreview" | "File" | "ModelLoad";
+
+export type TranscriptionJobEvent =
+  | { type: "Queued"; jobId: number; kind: TranscriptionJobKind }
+  | { type: "Started"; jobId: number; kind: TranscriptionJobKind }
+  | { type: "Progress"; jobId: number; percent: number }
+  | { type: "Finished"; jobId: number }
+  | { type: "Cancelled"; jobId: number }
+  | { type: "Failed"; jobId: number; message: string };
 
 export interface PermissionStatus {
   microphone: boolean;
@@ -156,6 +322,14 @@ export interface CommandError {
   message: string;
 }
 
+export type FileTranscriptionStage = "Decoding" | "Transcribing";
+
+export interface FileTranscriptionProgress {
+  fileName: string;
+  stage: FileTranscriptionStage;
+  percent: number;
+}
+
 export interface DownloadProgress {
   modelId: string;
   percent: number;
@@ -0,0 +1,13 @@
+import { describe, it, expect } from "vitest";
+import { formatLanguageName } from "./format-language";
+
+describe("formatLanguageName", () => {
+  it("names known language codes", () => {
+    expect(formatLanguageName("en")).toBe("English");
+    expect(formatLanguageName("de")).toBe("German");
+  });
+
+  it("falls back to the code when it is not a valid language tag", () => {
+    expect(formatLanguageName("not a code")).toBe("not a code");
+  });
+});
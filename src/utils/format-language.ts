@@ -0,0 +1,10 @@
+const displayNames = new Intl.DisplayNames(["en"], { type: "language" });
+
+/** English name of an ISO 639-1 language code, e.g. "de" -> "German". */
+export function formatLanguageName(code: string): string {
+  try {
+    return displayNames.of(code) ?? code;
+  } catch {
+    return code;
+  }
+}